@@ -0,0 +1,83 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(set = 0, binding = 0) uniform UniformBufferObject {
+    mat4 view;
+    mat4 proj;
+    vec4 cameraPos;
+} ubo;
+
+layout(set = 1, binding = 0) uniform GridParams {
+    uint plane;
+    float spacing;
+    float majorEvery;
+    float fadeDistance;
+} grid;
+
+layout(location = 0) in vec3 nearPoint;
+layout(location = 1) in vec3 farPoint;
+
+layout(location = 0) out vec4 outColor;
+
+const vec3 AXIS_COLORS[3] = vec3[](vec3(0.9, 0.2, 0.2), vec3(0.2, 0.9, 0.2), vec3(0.2, 0.4, 1.0));
+const vec3 MINOR_COLOR = vec3(0.35);
+const vec3 MAJOR_COLOR = vec3(0.6);
+
+// Indices of the two axes spanning the plane and of its normal
+ivec3 planeAxes() {
+    if (grid.plane == 0) {
+        return ivec3(0, 1, 2);
+    } else if (grid.plane == 1) {
+        return ivec3(0, 2, 1);
+    }
+    return ivec3(1, 2, 0);
+}
+
+// Coverage of the lines at every whole coordinate, about one pixel wide at any distance
+float lines(vec2 coordinates) {
+    vec2 width = fwidth(coordinates);
+    vec2 distance = abs(fract(coordinates - 0.5) - 0.5) / width;
+    return 1.0 - min(min(distance.x, distance.y), 1.0);
+}
+
+void main() {
+    ivec3 axes = planeAxes();
+    float nearHeight = nearPoint[axes.z];
+    float farHeight = farPoint[axes.z];
+
+    // Fraction of the way from the near to the far plane where the view ray crosses the grid plane, the pixel is only
+    // discarded at the end since the line widths need the derivatives of its neighbours
+    float t = nearHeight / (nearHeight - farHeight);
+    bool crossed = !isnan(t) && t >= 0.0 && t <= 1.0;
+
+    vec3 point = mix(nearPoint, farPoint, t);
+    vec4 clip = ubo.proj * ubo.view * vec4(point, 1.0);
+    gl_FragDepth = clip.z / clip.w;
+
+    vec2 coordinates = vec2(point[axes.x], point[axes.y]) / grid.spacing;
+    float minor = lines(coordinates);
+    float major = lines(coordinates / grid.majorEvery);
+
+    vec3 color = MINOR_COLOR;
+    float alpha = minor * 0.4;
+    if (major > 0.0) {
+        color = MAJOR_COLOR;
+        alpha = max(alpha, major * 0.8);
+    }
+
+    // Each axis lies along the line where the other coordinate of the plane is zero
+    vec2 width = fwidth(coordinates);
+    if (abs(coordinates.y) < width.y) {
+        color = AXIS_COLORS[axes.x];
+        alpha = 1.0;
+    } else if (abs(coordinates.x) < width.x) {
+        color = AXIS_COLORS[axes.y];
+        alpha = 1.0;
+    }
+
+    alpha *= 1.0 - smoothstep(0.0, grid.fadeDistance, distance(point, ubo.cameraPos.xyz));
+    if (!crossed || alpha <= 0.0) {
+        discard;
+    }
+    outColor = vec4(color, alpha);
+}
\ No newline at end of file
@@ -0,0 +1,23 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(binding = 0) uniform UniformBufferObject {
+    mat4 view;
+    mat4 proj;
+} ubo;
+
+layout(location = 0) out vec3 nearPoint;
+layout(location = 1) out vec3 farPoint;
+
+vec3 unproject(vec2 position, float depth) {
+    vec4 world = inverse(ubo.view) * inverse(ubo.proj) * vec4(position, depth, 1.0);
+    return world.xyz / world.w;
+}
+
+// Covers the view like the fullscreen triangle, with the points of the near and far plane behind each vertex
+void main() {
+    vec2 position = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2) * 2.0 - 1.0;
+    nearPoint = unproject(position, 0.0);
+    farPoint = unproject(position, 1.0);
+    gl_Position = vec4(position, 0.0, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,124 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+#define MAX_LIGHTS 16
+#define MAX_SHADOW_MAPS 8
+#define DIRECTIONAL 0
+#define POINT 1
+#define SPOT 2
+
+struct Light {
+    vec4 position;
+    vec4 direction;
+    vec4 color;
+    vec4 cone;
+    vec4 shadow;
+};
+
+layout(binding = 0) uniform UniformBufferObject {
+    mat4 view;
+    mat4 proj;
+    vec4 cameraPos;
+} ubo;
+
+layout(binding = 1) uniform Lights {
+    vec4 ambient;
+    uint count;
+    Light lights[MAX_LIGHTS];
+    mat4 shadowMatrices[MAX_SHADOW_MAPS];
+} lights;
+
+layout(binding = 5) uniform sampler2DArray shadowMap;
+
+layout(set = 1, binding = 0) uniform sampler2D texSampler;
+layout(set = 1, binding = 1) uniform MaterialParams {
+    vec4 color;
+    vec3 specular;
+    float shininess;
+} material;
+
+layout(location = 0) in vec3 fragColor;
+layout(location = 1) in vec2 fragUv;
+layout(location = 2) in vec3 fragPosition;
+layout(location = 3) in vec3 fragNormal;
+
+layout(location = 0) out vec4 outColor;
+
+// Fraction of the light reaching the fragment, read from the first of the light's shadow map layers covering it
+float shadowFactor(Light light, vec3 normal, vec3 lightDir) {
+    int firstLayer = int(light.shadow.x);
+    if (firstLayer < 0) {
+        return 1.0;
+    }
+
+    // Surfaces at grazing angles to the light need a larger bias to not shadow themselves
+    float bias = 0.0005 + 0.002 * (1.0 - dot(normal, lightDir));
+    vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0).xy);
+    for (int layer = firstLayer; layer < firstLayer + int(light.shadow.y); layer++) {
+        vec4 projected = lights.shadowMatrices[layer] * vec4(fragPosition, 1.0);
+        if (projected.w <= 0.0) {
+            continue;
+        }
+        vec3 coords = projected.xyz / projected.w;
+        vec2 uv = coords.xy * 0.5 + 0.5;
+        if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0))) || coords.z < 0.0 || coords.z > 1.0) {
+            continue;
+        }
+
+        // Percentage closer filtering over the 3x3 texels around the fragment
+        float lit = 0.0;
+        for (int x = -1; x <= 1; x++) {
+            for (int y = -1; y <= 1; y++) {
+                float depth = texture(shadowMap, vec3(uv + vec2(x, y) * texelSize, layer)).r;
+                lit += coords.z - bias <= depth ? 1.0 : 0.0;
+            }
+        }
+        return lit / 9.0;
+    }
+    return 1.0;
+}
+
+void main() {
+    vec4 albedo = vec4(fragColor, 1.0) * material.color * texture(texSampler, fragUv);
+    vec3 normal = normalize(fragNormal);
+    vec3 viewDir = normalize(ubo.cameraPos.xyz - fragPosition);
+
+    vec3 diffuse = vec3(0.0);
+    vec3 specular = vec3(0.0);
+    for (uint i = 0; i < min(lights.count, MAX_LIGHTS); i++) {
+        Light light = lights.lights[i];
+        int kind = int(light.direction.w);
+
+        vec3 lightDir;
+        float attenuation = 1.0;
+        if (kind == DIRECTIONAL) {
+            lightDir = -normalize(light.direction.xyz);
+        } else {
+            vec3 toLight = light.position.xyz - fragPosition;
+            float distance = length(toLight);
+            lightDir = toLight / distance;
+
+            // Inverse square falloff windowed to reach zero at the light's range
+            float window = clamp(1.0 - pow(distance / light.position.w, 4.0), 0.0, 1.0);
+            attenuation = window * window / (distance * distance + 1.0);
+
+            if (kind == SPOT) {
+                float cosAngle = dot(-lightDir, normalize(light.direction.xyz));
+                attenuation *= smoothstep(light.cone.y, light.cone.x, cosAngle);
+            }
+        }
+
+        float lambert = max(dot(normal, lightDir), 0.0);
+        if (lambert <= 0.0) {
+            continue;
+        }
+        attenuation *= shadowFactor(light, normal, lightDir);
+
+        vec3 halfway = normalize(lightDir + viewDir);
+        diffuse += light.color.rgb * lambert * attenuation;
+        specular += light.color.rgb * pow(max(dot(normal, halfway), 0.0), material.shininess) * attenuation;
+    }
+
+    vec3 color = albedo.rgb * (lights.ambient.rgb + diffuse) + material.specular * specular;
+    outColor = vec4(color, albedo.a);
+}
\ No newline at end of file
@@ -0,0 +1,188 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+#define MAX_LIGHTS 16
+#define MAX_SHADOW_MAPS 8
+#define DIRECTIONAL 0
+#define POINT 1
+#define SPOT 2
+#define PI 3.14159265359
+
+struct Light {
+    vec4 position;
+    vec4 direction;
+    vec4 color;
+    vec4 cone;
+    vec4 shadow;
+};
+
+layout(binding = 0) uniform UniformBufferObject {
+    mat4 view;
+    mat4 proj;
+    vec4 cameraPos;
+} ubo;
+
+layout(binding = 1) uniform Lights {
+    vec4 ambient;
+    uint count;
+    Light lights[MAX_LIGHTS];
+    mat4 shadowMatrices[MAX_SHADOW_MAPS];
+} lights;
+
+layout(binding = 2) uniform samplerCube irradianceMap;
+layout(binding = 3) uniform samplerCube prefilteredMap;
+layout(binding = 4) uniform sampler2D brdfLut;
+layout(binding = 5) uniform sampler2DArray shadowMap;
+
+layout(set = 1, binding = 0) uniform sampler2D baseColorMap;
+layout(set = 1, binding = 1) uniform MaterialParams {
+    vec4 color;
+    vec3 specular;
+    float shininess;
+    vec3 emissive;
+    float metallic;
+    float roughness;
+    float normalScale;
+    float occlusionStrength;
+} material;
+layout(set = 1, binding = 2) uniform sampler2D metallicRoughnessMap;
+layout(set = 1, binding = 3) uniform sampler2D normalMap;
+layout(set = 1, binding = 4) uniform sampler2D occlusionMap;
+layout(set = 1, binding = 5) uniform sampler2D emissiveMap;
+
+layout(location = 0) in vec3 fragColor;
+layout(location = 1) in vec2 fragUv;
+layout(location = 2) in vec3 fragPosition;
+layout(location = 3) in vec3 fragNormal;
+layout(location = 4) in vec4 fragTangent;
+
+layout(location = 0) out vec4 outColor;
+
+// Fraction of the light reaching the fragment, read from the first of the light's shadow map layers covering it
+float shadowFactor(Light light, vec3 normal, vec3 lightDir) {
+    int firstLayer = int(light.shadow.x);
+    if (firstLayer < 0) {
+        return 1.0;
+    }
+
+    // Surfaces at grazing angles to the light need a larger bias to not shadow themselves
+    float bias = 0.0005 + 0.002 * (1.0 - dot(normal, lightDir));
+    vec2 texelSize = 1.0 / vec2(textureSize(shadowMap, 0).xy);
+    for (int layer = firstLayer; layer < firstLayer + int(light.shadow.y); layer++) {
+        vec4 projected = lights.shadowMatrices[layer] * vec4(fragPosition, 1.0);
+        if (projected.w <= 0.0) {
+            continue;
+        }
+        vec3 coords = projected.xyz / projected.w;
+        vec2 uv = coords.xy * 0.5 + 0.5;
+        if (any(lessThan(uv, vec2(0.0))) || any(greaterThan(uv, vec2(1.0))) || coords.z < 0.0 || coords.z > 1.0) {
+            continue;
+        }
+
+        // Percentage closer filtering over the 3x3 texels around the fragment
+        float lit = 0.0;
+        for (int x = -1; x <= 1; x++) {
+            for (int y = -1; y <= 1; y++) {
+                float depth = texture(shadowMap, vec3(uv + vec2(x, y) * texelSize, layer)).r;
+                lit += coords.z - bias <= depth ? 1.0 : 0.0;
+            }
+        }
+        return lit / 9.0;
+    }
+    return 1.0;
+}
+
+float distributionGgx(float nDotH, float roughness) {
+    float a2 = roughness * roughness * roughness * roughness;
+    float denominator = nDotH * nDotH * (a2 - 1.0) + 1.0;
+    return a2 / (PI * denominator * denominator);
+}
+
+float geometrySmith(float nDotV, float nDotL, float roughness) {
+    float k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    return nDotV / (nDotV * (1.0 - k) + k) * nDotL / (nDotL * (1.0 - k) + k);
+}
+
+vec3 fresnelSchlick(float cosTheta, vec3 f0) {
+    return f0 + (1.0 - f0) * pow(1.0 - cosTheta, 5.0);
+}
+
+vec3 fresnelSchlickRoughness(float cosTheta, vec3 f0, float roughness) {
+    return f0 + (max(vec3(1.0 - roughness), f0) - f0) * pow(1.0 - cosTheta, 5.0);
+}
+
+vec3 surfaceNormal() {
+    vec3 normal = normalize(fragNormal);
+    vec3 tangent = fragTangent.xyz - dot(fragTangent.xyz, normal) * normal;
+    if (dot(tangent, tangent) < 0.000001) {
+        return normal;
+    }
+    tangent = normalize(tangent);
+    vec3 bitangent = cross(normal, tangent) * fragTangent.w;
+
+    vec3 mapped = texture(normalMap, fragUv).xyz * 2.0 - 1.0;
+    mapped.xy *= material.normalScale;
+    return normalize(mat3(tangent, bitangent, normal) * mapped);
+}
+
+void main() {
+    vec4 baseColor = vec4(fragColor, 1.0) * material.color * texture(baseColorMap, fragUv);
+    vec4 metallicRoughness = texture(metallicRoughnessMap, fragUv);
+    float metallic = clamp(material.metallic * metallicRoughness.b, 0.0, 1.0);
+    float roughness = clamp(material.roughness * metallicRoughness.g, 0.04, 1.0);
+
+    vec3 normal = surfaceNormal();
+    vec3 viewDir = normalize(ubo.cameraPos.xyz - fragPosition);
+    float nDotV = max(dot(normal, viewDir), 0.0001);
+    vec3 f0 = mix(vec3(0.04), baseColor.rgb, metallic);
+
+    vec3 direct = vec3(0.0);
+    for (uint i = 0; i < min(lights.count, MAX_LIGHTS); i++) {
+        Light light = lights.lights[i];
+        int kind = int(light.direction.w);
+
+        vec3 lightDir;
+        float attenuation = 1.0;
+        if (kind == DIRECTIONAL) {
+            lightDir = -normalize(light.direction.xyz);
+        } else {
+            vec3 toLight = light.position.xyz - fragPosition;
+            float distance = length(toLight);
+            lightDir = toLight / distance;
+
+            // Inverse square falloff windowed to reach zero at the light's range
+            float window = clamp(1.0 - pow(distance / light.position.w, 4.0), 0.0, 1.0);
+            attenuation = window * window / (distance * distance + 1.0);
+
+            if (kind == SPOT) {
+                float cosAngle = dot(-lightDir, normalize(light.direction.xyz));
+                attenuation *= smoothstep(light.cone.y, light.cone.x, cosAngle);
+            }
+        }
+
+        float nDotL = max(dot(normal, lightDir), 0.0);
+        if (nDotL <= 0.0) {
+            continue;
+        }
+        attenuation *= shadowFactor(light, normal, lightDir);
+
+        vec3 halfway = normalize(lightDir + viewDir);
+        vec3 fresnel = fresnelSchlick(max(dot(halfway, viewDir), 0.0), f0);
+        vec3 specular = distributionGgx(max(dot(normal, halfway), 0.0), roughness) * geometrySmith(nDotV, nDotL, roughness) * fresnel / (4.0 * nDotV * nDotL + 0.0001);
+        vec3 diffuse = (1.0 - fresnel) * (1.0 - metallic) * baseColor.rgb / PI;
+        direct += (diffuse + specular) * light.color.rgb * attenuation * nDotL;
+    }
+
+    // Split sum image based lighting, scaled by the ambient light and darkened by the occlusion map
+    vec3 fresnel = fresnelSchlickRoughness(nDotV, f0, roughness);
+    vec3 diffuse = (1.0 - fresnel) * (1.0 - metallic) * texture(irradianceMap, normal).rgb * baseColor.rgb;
+    float lod = roughness * float(textureQueryLevels(prefilteredMap) - 1);
+    vec3 prefiltered = textureLod(prefilteredMap, reflect(-viewDir, normal), lod).rgb;
+    vec2 brdf = texture(brdfLut, vec2(nDotV, roughness)).rg;
+    vec3 specular = prefiltered * (fresnel * brdf.x + brdf.y);
+    float occlusion = mix(1.0, texture(occlusionMap, fragUv).r, material.occlusionStrength);
+    vec3 ambient = (diffuse + specular) * lights.ambient.rgb * occlusion;
+
+    vec3 emissive = material.emissive * texture(emissiveMap, fragUv).rgb;
+    outColor = vec4(ambient + direct + emissive, baseColor.a);
+}
\ No newline at end of file
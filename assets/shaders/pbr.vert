@@ -0,0 +1,33 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(binding = 0) uniform UniformBufferObject {
+    mat4 view;
+    mat4 proj;
+    vec4 cameraPos;
+} ubo;
+
+layout(location = 0) in vec3 inPosition;
+layout(location = 1) in vec3 inNormal;
+layout(location = 2) in vec2 inUv;
+layout(location = 3) in vec4 inTangent;
+layout(location = 4) in vec3 inColor;
+
+layout(location = 5) in mat4 instanceModel;
+layout(location = 9) in vec3 instanceColor;
+
+layout(location = 0) out vec3 fragColor;
+layout(location = 1) out vec2 fragUv;
+layout(location = 2) out vec3 fragPosition;
+layout(location = 3) out vec3 fragNormal;
+layout(location = 4) out vec4 fragTangent;
+
+void main() {
+    vec4 worldPosition = instanceModel * vec4(inPosition, 1.0);
+    gl_Position = ubo.proj * ubo.view * worldPosition;
+    fragColor = inColor * instanceColor;
+    fragUv = inUv;
+    fragPosition = worldPosition.xyz;
+    fragNormal = transpose(inverse(mat3(instanceModel))) * inNormal;
+    fragTangent = vec4(mat3(instanceModel) * inTangent.xyz, inTangent.w);
+}
\ No newline at end of file
@@ -0,0 +1,31 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(binding = 0) uniform PostParams {
+    vec2 direction;
+    float exposure;
+    float bloomThreshold;
+    float bloomIntensity;
+    float vignetteStrength;
+    uint flags;
+} params;
+
+layout(binding = 1) uniform sampler2D sourceMap;
+
+layout(location = 0) in vec2 fragUv;
+
+layout(location = 0) out vec4 outColor;
+
+// 9 tap gaussian along the direction, reading two texels with each linear tap off the center
+const float offsets[3] = float[](0.0, 1.3846153846, 3.2307692308);
+const float weights[3] = float[](0.2270270270, 0.3162162162, 0.0702702703);
+
+void main() {
+    vec2 texelStep = params.direction / vec2(textureSize(sourceMap, 0));
+    vec3 color = texture(sourceMap, fragUv).rgb * weights[0];
+    for (int i = 1; i < 3; i++) {
+        color += texture(sourceMap, fragUv + texelStep * offsets[i]).rgb * weights[i];
+        color += texture(sourceMap, fragUv - texelStep * offsets[i]).rgb * weights[i];
+    }
+    outColor = vec4(color, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,30 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(binding = 0) uniform PostParams {
+    vec2 direction;
+    float exposure;
+    float bloomThreshold;
+    float bloomIntensity;
+    float vignetteStrength;
+    uint flags;
+} params;
+
+layout(binding = 1) uniform sampler2D sourceMap;
+
+layout(location = 0) in vec2 fragUv;
+
+layout(location = 0) out vec4 outColor;
+
+// Averages the 4x4 source texels under each bloom texel and keeps only the light above the threshold
+void main() {
+    vec2 texel = 1.0 / vec2(textureSize(sourceMap, 0));
+    vec3 color = texture(sourceMap, fragUv + vec2(-texel.x, -texel.y)).rgb;
+    color += texture(sourceMap, fragUv + vec2(texel.x, -texel.y)).rgb;
+    color += texture(sourceMap, fragUv + vec2(-texel.x, texel.y)).rgb;
+    color += texture(sourceMap, fragUv + vec2(texel.x, texel.y)).rgb;
+    color *= 0.25;
+
+    float brightness = max(color.r, max(color.g, color.b));
+    outColor = vec4(color * max(brightness - params.bloomThreshold, 0.0) / max(brightness, 0.0001), 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,61 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+#define TONEMAPPING 1u
+#define BLOOM 2u
+#define VIGNETTE 4u
+#define COLOR_GRADING 8u
+#define LUT_SIZE 16.0
+
+layout(binding = 0) uniform PostParams {
+    vec2 direction;
+    float exposure;
+    float bloomThreshold;
+    float bloomIntensity;
+    float vignetteStrength;
+    uint flags;
+} params;
+
+layout(binding = 1) uniform sampler2D sourceMap;
+layout(binding = 2) uniform sampler2D bloomMap;
+layout(binding = 3) uniform sampler2D lutMap;
+
+layout(location = 0) in vec2 fragUv;
+
+layout(location = 0) out vec4 outColor;
+
+// Narkowicz's fit of the ACES filmic curve
+vec3 tonemap(vec3 color) {
+    return clamp((color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14), 0.0, 1.0);
+}
+
+// The table is a strip of LUT_SIZE slices of LUT_SIZE x LUT_SIZE texels indexed by gamma encoded colors, red along
+// each slice, green down it and blue picking the slice
+vec3 grade(vec3 color) {
+    vec3 index = pow(color, vec3(1.0 / 2.2)) * (LUT_SIZE - 1.0);
+    float slice = floor(index.b);
+    vec2 uv = (index.rg + 0.5) / vec2(LUT_SIZE * LUT_SIZE, LUT_SIZE);
+    vec3 lower = texture(lutMap, uv + vec2(slice / LUT_SIZE, 0.0)).rgb;
+    vec3 upper = texture(lutMap, uv + vec2(min(slice + 1.0, LUT_SIZE - 1.0) / LUT_SIZE, 0.0)).rgb;
+    return pow(mix(lower, upper, index.b - slice), vec3(2.2));
+}
+
+void main() {
+    vec3 color = texture(sourceMap, fragUv).rgb;
+    if ((params.flags & BLOOM) != 0u) {
+        color += texture(bloomMap, fragUv).rgb * params.bloomIntensity;
+    }
+
+    color *= params.exposure;
+    color = (params.flags & TONEMAPPING) != 0u ? tonemap(color) : clamp(color, 0.0, 1.0);
+
+    if ((params.flags & COLOR_GRADING) != 0u) {
+        color = grade(color);
+    }
+    if ((params.flags & VIGNETTE) != 0u) {
+        vec2 offset = fragUv - 0.5;
+        color *= 1.0 - params.vignetteStrength * smoothstep(0.2, 0.8, dot(offset, offset) * 2.0);
+    }
+
+    outColor = vec4(color, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,41 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+#define REDUCE_MIN (1.0 / 128.0)
+#define REDUCE_MUL (1.0 / 8.0)
+#define SPAN_MAX 8.0
+
+layout(binding = 1) uniform sampler2D sourceMap;
+
+layout(location = 0) in vec2 fragUv;
+
+layout(location = 0) out vec4 outColor;
+
+// Perceptual brightness of a linear color
+float luma(vec3 color) {
+    return sqrt(dot(color, vec3(0.299, 0.587, 0.114)));
+}
+
+// Blurs along the edge found from the luma of the diagonal neighbours, unless that overshoots the local luma range
+void main() {
+    vec2 texel = 1.0 / vec2(textureSize(sourceMap, 0));
+    vec3 colorM = texture(sourceMap, fragUv).rgb;
+    float lumaNW = luma(texture(sourceMap, fragUv + vec2(-1.0, -1.0) * texel).rgb);
+    float lumaNE = luma(texture(sourceMap, fragUv + vec2(1.0, -1.0) * texel).rgb);
+    float lumaSW = luma(texture(sourceMap, fragUv + vec2(-1.0, 1.0) * texel).rgb);
+    float lumaSE = luma(texture(sourceMap, fragUv + vec2(1.0, 1.0) * texel).rgb);
+    float lumaM = luma(colorM);
+    float lumaMin = min(lumaM, min(min(lumaNW, lumaNE), min(lumaSW, lumaSE)));
+    float lumaMax = max(lumaM, max(max(lumaNW, lumaNE), max(lumaSW, lumaSE)));
+
+    vec2 dir = vec2(-((lumaNW + lumaNE) - (lumaSW + lumaSE)), (lumaNW + lumaSW) - (lumaNE + lumaSE));
+    float dirReduce = max((lumaNW + lumaNE + lumaSW + lumaSE) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float rcpDirMin = 1.0 / (min(abs(dir.x), abs(dir.y)) + dirReduce);
+    dir = clamp(dir * rcpDirMin, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * texel;
+
+    vec3 colorA = 0.5 * (texture(sourceMap, fragUv + dir * (1.0 / 3.0 - 0.5)).rgb + texture(sourceMap, fragUv + dir * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 colorB = colorA * 0.5 + 0.25 * (texture(sourceMap, fragUv - dir * 0.5).rgb + texture(sourceMap, fragUv + dir * 0.5).rgb);
+    float lumaB = luma(colorB);
+
+    outColor = vec4(lumaB < lumaMin || lumaB > lumaMax ? colorA : colorB, 1.0);
+}
\ No newline at end of file
@@ -1,10 +1,16 @@
 #version 450
 #extension GL_ARB_separate_shader_objects : enable
 
+layout(set = 1, binding = 0) uniform sampler2D texSampler;
+layout(set = 1, binding = 1) uniform MaterialParams {
+    vec4 color;
+} material;
+
 layout(location = 0) in vec3 fragColor;
+layout(location = 1) in vec2 fragUv;
 
 layout(location = 0) out vec4 outColor;
 
 void main() {
-    outColor = vec4(fragColor, 1.0);
+    outColor = vec4(fragColor, 1.0) * material.color * texture(texSampler, fragUv);
 }
\ No newline at end of file
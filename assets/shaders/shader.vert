@@ -2,17 +2,22 @@
 #extension GL_ARB_separate_shader_objects : enable
 
 layout(binding = 0) uniform UniformBufferObject {
-    mat4 model;
     mat4 view;
     mat4 proj;
 } ubo;
 
-layout(location = 0) in vec2 inPosition;
-layout(location = 1) in vec3 inColor;
+layout(location = 0) in vec3 inPosition;
+layout(location = 2) in vec2 inUv;
+layout(location = 4) in vec3 inColor;
+
+layout(location = 5) in mat4 instanceModel;
+layout(location = 9) in vec3 instanceColor;
 
 layout(location = 0) out vec3 fragColor;
+layout(location = 1) out vec2 fragUv;
 
 void main() {
-    gl_Position = ubo.proj * ubo.view * ubo.model * vec4(inPosition, 0.0, 1.0);
-    fragColor = inColor;
+    gl_Position = ubo.proj * ubo.view * instanceModel * vec4(inPosition, 1.0);
+    fragColor = inColor * instanceColor;
+    fragUv = inUv;
 }
\ No newline at end of file
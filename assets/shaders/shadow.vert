@@ -0,0 +1,16 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(binding = 0) uniform UniformBufferObject {
+    mat4 view;
+    mat4 proj;
+    vec4 cameraPos;
+} ubo;
+
+layout(location = 0) in vec3 inPosition;
+
+layout(location = 5) in mat4 instanceModel;
+
+void main() {
+    gl_Position = ubo.proj * ubo.view * instanceModel * vec4(inPosition, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,15 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(set = 1, binding = 0) uniform samplerCube skySampler;
+layout(set = 1, binding = 1) uniform MaterialParams {
+    vec4 color;
+} material;
+
+layout(location = 0) in vec3 fragDirection;
+
+layout(location = 0) out vec4 outColor;
+
+void main() {
+    outColor = vec4(texture(skySampler, fragDirection).rgb, 1.0) * material.color;
+}
\ No newline at end of file
@@ -0,0 +1,19 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(binding = 0) uniform UniformBufferObject {
+    mat4 view;
+    mat4 proj;
+} ubo;
+
+layout(location = 0) in vec3 inPosition;
+
+layout(location = 0) out vec3 fragDirection;
+
+void main() {
+    // Only the camera rotation applies, so the sky stays around the camera wherever it moves
+    fragDirection = inPosition;
+    vec4 position = ubo.proj * vec4(mat3(ubo.view) * inPosition, 1.0);
+    // Depth of the far plane, behind everything the scene draws
+    gl_Position = position.xyww;
+}
\ No newline at end of file
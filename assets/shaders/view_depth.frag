@@ -0,0 +1,14 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(location = 2) in float fragDistance;
+
+layout(location = 0) out vec4 outColor;
+
+// Distance at which surfaces have faded to a third of their brightness
+const float FALLOFF = 20.0;
+
+// Close surfaces are white and fade to black with their distance from the camera
+void main() {
+    outColor = vec4(vec3(exp(-fragDistance / FALLOFF)), 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,11 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(location = 0) in vec3 fragNormal;
+
+layout(location = 0) out vec4 outColor;
+
+// World space normals mapped from -1..1 to 0..1
+void main() {
+    outColor = vec4(normalize(fragNormal) * 0.5 + 0.5, 1.0);
+}
\ No newline at end of file
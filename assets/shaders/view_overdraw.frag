@@ -0,0 +1,9 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(location = 0) out vec4 outColor;
+
+// Added once per fragment shaded, pixels go from dark red to orange and then yellow as more layers are drawn on them
+void main() {
+    outColor = vec4(0.1, 0.04, 0.01, 1.0);
+}
\ No newline at end of file
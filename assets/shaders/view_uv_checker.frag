@@ -0,0 +1,15 @@
+#version 450
+#extension GL_ARB_separate_shader_objects : enable
+
+layout(location = 1) in vec2 fragUv;
+
+layout(location = 0) out vec4 outColor;
+
+const float CHECKS = 8.0;
+
+// Checkerboard of 8x8 squares over the 0..1 uv range, tinted by the uvs to show their orientation
+void main() {
+    vec2 cell = floor(fragUv * CHECKS);
+    float check = mod(cell.x + cell.y, 2.0) == 0.0 ? 1.0 : 0.25;
+    outColor = vec4(vec3(fract(fragUv), 1.0) * check, 1.0);
+}
\ No newline at end of file
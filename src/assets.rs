@@ -0,0 +1,19 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
+
+/// Directory the `assets` folder is in, every asset is loaded relative to it
+///
+/// `VOYAGER_ASSET_ROOT` overrides it, otherwise it is the crate root when run through cargo and the working directory when not
+pub fn asset_root() -> PathBuf {
+    env::var_os("VOYAGER_ASSET_ROOT")
+        .or_else(|| env::var_os("CARGO_MANIFEST_DIR"))
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Path of an asset given relative to the asset root, e.g. `assets/models/grid.obj`
+pub fn asset_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    asset_root().join(path)
+}
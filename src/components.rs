@@ -1,9 +1,20 @@
-use std::time::{Duration, Instant};
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use specs::*;
 use winit::event::Event;
 
-use crate::render::models::Mesh;
+use crate::render::{
+    images::{Background, Environment, RenderTexture},
+    materials::Material,
+    models::Mesh,
+    GridSettings, PostSettings, ViewMode, Viewport,
+};
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum MouseState {
@@ -28,17 +39,146 @@ pub struct Movement {
     pub rot: uv::Rotor3,
 }
 
-#[derive(Component, Default, Debug)]
+#[derive(Component, Debug)]
 #[storage(VecStorage)]
 pub struct Transform {
     pub pos: uv::Vec3,
     pub dir: uv::Rotor3,
+    pub scale: uv::Vec3,
+}
+
+impl Transform {
+    /// Matrix scaling, then rotating, then translating from the entity's space into its parent's
+    pub fn model_matrix(&self) -> uv::Mat4 {
+        uv::Mat4::from_translation(self.pos) * self.dir.into_matrix().into_homogeneous() * uv::Mat4::from_nonuniform_scale(self.scale)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            pos: uv::Vec3::zero(),
+            dir: uv::Rotor3::identity(),
+            scale: uv::Vec3::one(),
+        }
+    }
+}
+
+/// Makes the entity's `Transform` relative to another entity's
+#[derive(Component, Debug)]
+#[storage(DenseVecStorage)]
+pub struct Parent {
+    pub entity: Entity,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// Vertical field of view in radians
+    Perspective { fov_y: f32 },
+    /// World units visible from the bottom to the top of the view
+    Orthographic { height: f32 },
+}
+
+/// Views the world from the entity's `Transform`, looking along its local z axis
+///
+/// Every active camera is drawn into its viewport, in increasing `order`.
+#[derive(Component, Clone)]
+#[storage(DenseVecStorage)]
+pub struct Camera {
+    pub projection: Projection,
+    pub near: f32,
+    pub far: f32,
+    pub active: bool,
+    pub viewport: Viewport,
+    pub order: i32,
+    /// Only `Renderable`s sharing a layer with the mask are shown
+    pub layer_mask: u32,
+    /// Renders into the texture instead of the frame, the viewport then covers a part of the texture
+    pub target: Option<Arc<RenderTexture>>,
+}
+
+impl Camera {
+    pub fn projection_matrix(&self, aspect: f32) -> uv::Mat4 {
+        match self.projection {
+            Projection::Perspective { fov_y } => uv::projection::perspective_vk(fov_y, aspect, self.near, self.far),
+            Projection::Orthographic { height } => {
+                let (half_width, half_height) = (height * aspect / 2.0, height / 2.0);
+                uv::projection::orthographic_vk(-half_width, half_width, -half_height, half_height, self.near, self.far)
+            }
+        }
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            projection: Projection::Perspective { fov_y: 45f32.to_radians() },
+            near: 0.1,
+            far: 1000.0,
+            active: true,
+            viewport: Viewport::default(),
+            order: 0,
+            layer_mask: !0,
+            target: None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LightKind {
+    /// Lights everything from the direction of the entity's local z axis
+    Directional,
+    /// Shines in all directions from the entity's position, fading out to nothing at `range`
+    Point { range: f32 },
+    /// Point light limited to a cone around the entity's local z axis, the angles are in radians from that axis
+    Spot { range: f32, inner_angle: f32, outer_angle: f32 },
+}
+
+/// Light shading the materials using the lit shaders, placed by the entity's `Transform`
+#[derive(Component, Clone, Debug)]
+#[storage(DenseVecStorage)]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: [f32; 3],
+    pub intensity: f32,
+    /// Throws shadows of the opaque renderables, only supported by directional and spot lights
+    pub cast_shadows: bool,
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self {
+            kind: LightKind::Directional,
+            color: [1.0, 1.0, 1.0],
+            intensity: 1.0,
+            cast_shadows: false,
+        }
+    }
 }
 
 #[derive(Component)]
 #[storage(VecStorage)]
 pub struct Renderable {
-    pub mesh: Mesh,
+    pub mesh: Arc<Mesh>,
+    /// Multiplies the mesh's vertex colors
+    pub color: [f32; 3],
+    /// Bit set of the layers the renderable is part of, see `Camera::layer_mask`
+    pub layers: u32,
+    /// Drawn with `GraphicContext::default_material` when `None`
+    pub material: Option<Arc<Material>>,
+}
+
+impl Renderable {
+    pub const DEFAULT_LAYERS: u32 = 1;
+
+    pub fn new(mesh: Arc<Mesh>) -> Self {
+        Self {
+            mesh,
+            color: [1.0, 1.0, 1.0],
+            layers: Self::DEFAULT_LAYERS,
+            material: None,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -66,3 +206,56 @@ impl Default for DeltaTime {
 pub struct WinitEventData {
     pub events: Vec<Event<'static, ()>>,
 }
+
+/// Last frame read back from a headless `RenderSystem` as RGBA8 rows
+#[derive(Default)]
+pub struct FrameCapture {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl FrameCapture {
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), self.width, self.height);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)
+    }
+}
+
+pub struct RenderSettings {
+    pub show_ui: bool,
+    /// Light reaching every surface shaded by the lit shaders, the PBR shaders scale the environment's light by it instead
+    pub ambient_light: [f32; 3],
+    /// Image based lighting of the PBR shaders, a uniform white environment when `None`
+    pub environment: Option<Arc<Environment>>,
+    /// Flat color or skybox shown behind the scene
+    pub background: Background,
+    /// Distance from the camera up to which directional lights throw shadows
+    pub shadow_distance: f32,
+    /// Samples per pixel of the frame, 1 disables multisampling and counts the device doesn't support are lowered
+    pub msaa_samples: u32,
+    /// Passes taking the HDR scene to the frame, also toggled from the debug ui
+    pub post: PostSettings,
+    /// Editor grid drawn over the scene, also toggled from the debug ui
+    pub grid: GridSettings,
+    /// Shading of the scene, switched from the debug ui to see wireframes, normals, uvs, depth or overdraw
+    pub view_mode: ViewMode,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            show_ui: true,
+            ambient_light: [0.05, 0.05, 0.05],
+            environment: None,
+            background: Background::default(),
+            shadow_distance: 50.0,
+            msaa_samples: 4,
+            post: PostSettings::default(),
+            grid: GridSettings::default(),
+            view_mode: ViewMode::default(),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::f32::consts::PI;
+
+/// Segments of each circle of a debug sphere
+const SPHERE_SEGMENTS: usize = 24;
+/// Length of an arrow's head relative to the arrow
+const ARROW_HEAD: f32 = 0.2;
+
+/// Colors of the x, y and z axes
+const AXIS_COLORS: [[f32; 3]; 3] = [[1.0, 0.2, 0.2], [0.2, 1.0, 0.2], [0.2, 0.4, 1.0]];
+
+#[derive(Copy, Clone, Debug)]
+pub struct DebugLine {
+    pub from: uv::Vec3,
+    pub to: uv::Vec3,
+    pub color: [f32; 3],
+    /// Seconds the line stays after the current frame
+    remaining: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct DebugLabel {
+    pub position: uv::Vec3,
+    pub text: String,
+    pub color: [f32; 3],
+    remaining: f32,
+}
+
+/// Shapes any system can draw in world space to debug what it is doing, drawn by the `RenderSystem` over the scene
+///
+/// Shapes are drawn in the next frame only, unless they are added within `lasting`. Labels are drawn as part of the
+/// debug ui and are hidden with it.
+#[derive(Default)]
+pub struct DebugDraw {
+    lines: Vec<DebugLine>,
+    labels: Vec<DebugLabel>,
+    /// Lifetime given to the shapes being added
+    lifetime: f32,
+}
+
+impl DebugDraw {
+    pub fn line(&mut self, from: uv::Vec3, to: uv::Vec3, color: [f32; 3]) {
+        self.lines.push(DebugLine {
+            from,
+            to,
+            color,
+            remaining: self.lifetime,
+        });
+    }
+
+    /// Line from `from` to `to` with a head at `to`
+    pub fn arrow(&mut self, from: uv::Vec3, to: uv::Vec3, color: [f32; 3]) {
+        self.line(from, to, color);
+
+        let direction = to - from;
+        let length = direction.mag();
+        if length <= f32::EPSILON {
+            return;
+        }
+        let (side, up) = perpendiculars(direction / length);
+        let base = to - direction * ARROW_HEAD;
+        let spread = length * ARROW_HEAD * 0.5;
+        for offset in [side, -side, up, -up].iter() {
+            self.line(to, base + *offset * spread, color);
+        }
+    }
+
+    /// Axis aligned box between the corners `min` and `max`
+    pub fn aabb(&mut self, min: uv::Vec3, max: uv::Vec3, color: [f32; 3]) {
+        let corner = |index: usize| {
+            uv::Vec3::new(
+                if index & 1 == 0 { min.x } else { max.x },
+                if index & 2 == 0 { min.y } else { max.y },
+                if index & 4 == 0 { min.z } else { max.z },
+            )
+        };
+        self.box_edges(corner, color);
+    }
+
+    /// Three circles around `center`, one in each axis plane
+    pub fn sphere(&mut self, center: uv::Vec3, radius: f32, color: [f32; 3]) {
+        let point = |axis: usize, segment: usize| {
+            let angle = segment as f32 / SPHERE_SEGMENTS as f32 * 2.0 * PI;
+            let (sin, cos) = angle.sin_cos();
+            let offset = match axis {
+                0 => uv::Vec3::new(0.0, cos, sin),
+                1 => uv::Vec3::new(cos, 0.0, sin),
+                _ => uv::Vec3::new(cos, sin, 0.0),
+            };
+            center + offset * radius
+        };
+        for axis in 0..3 {
+            for segment in 0..SPHERE_SEGMENTS {
+                self.line(point(axis, segment), point(axis, segment + 1), color);
+            }
+        }
+    }
+
+    /// Volume seen by a camera with the view projection `view_proj`, using Vulkan's depth range from 0 to 1
+    pub fn frustum(&mut self, view_proj: &uv::Mat4, color: [f32; 3]) {
+        let inverse = view_proj.inversed();
+        let corner = |index: usize| {
+            let ndc = uv::Vec4::new(
+                if index & 1 == 0 { -1.0 } else { 1.0 },
+                if index & 2 == 0 { -1.0 } else { 1.0 },
+                if index & 4 == 0 { 0.0 } else { 1.0 },
+                1.0,
+            );
+            let world = inverse * ndc;
+            world.xyz() / world.w
+        };
+        self.box_edges(corner, color);
+    }
+
+    /// Red, green and blue lines of `size` along the x, y and z axes of `transform`
+    pub fn axes(&mut self, transform: &uv::Mat4, size: f32) {
+        let origin = transform.transform_point3(uv::Vec3::zero());
+        let axes = [uv::Vec3::unit_x(), uv::Vec3::unit_y(), uv::Vec3::unit_z()];
+        for (axis, color) in axes.iter().zip(AXIS_COLORS.iter()) {
+            self.arrow(origin, transform.transform_point3(*axis * size), *color);
+        }
+    }
+
+    /// Text drawn on the screen where `position` is seen by the first camera drawing into the frame
+    pub fn label<T: Into<String>>(&mut self, position: uv::Vec3, text: T, color: [f32; 3]) {
+        self.labels.push(DebugLabel {
+            position,
+            text: text.into(),
+            color,
+            remaining: self.lifetime,
+        });
+    }
+
+    /// Keeps the shapes `draw` adds on screen for `seconds`
+    pub fn lasting<F: FnOnce(&mut DebugDraw)>(&mut self, seconds: f32, draw: F) {
+        let lifetime = self.lifetime;
+        self.lifetime = seconds;
+        draw(self);
+        self.lifetime = lifetime;
+    }
+
+    pub fn lines(&self) -> &[DebugLine] {
+        &self.lines
+    }
+
+    pub fn labels(&self) -> &[DebugLabel] {
+        &self.labels
+    }
+
+    /// Removes the shapes which were drawn for long enough, called once a frame after they were drawn
+    pub fn expire(&mut self, delta: f32) {
+        self.lines.retain(|line| line.remaining > delta);
+        self.labels.retain(|label| label.remaining > delta);
+        for line in &mut self.lines {
+            line.remaining -= delta;
+        }
+        for label in &mut self.labels {
+            label.remaining -= delta;
+        }
+    }
+
+    /// Twelve edges of a box whose corners are numbered with x in bit 0, y in bit 1 and z in bit 2
+    fn box_edges(&mut self, corner: impl Fn(usize) -> uv::Vec3, color: [f32; 3]) {
+        for index in 0..8 {
+            for bit in [1, 2, 4].iter() {
+                if index & bit == 0 {
+                    self.line(corner(index), corner(index | bit), color);
+                }
+            }
+        }
+    }
+}
+
+/// Two unit vectors perpendicular to `direction` and to each other
+fn perpendiculars(direction: uv::Vec3) -> (uv::Vec3, uv::Vec3) {
+    let reference = if direction.x.abs() < 0.9 { uv::Vec3::unit_x() } else { uv::Vec3::unit_y() };
+    let side = direction.cross(reference).normalized();
+    (side, direction.cross(side))
+}
@@ -1,19 +1,31 @@
-use specs::{Builder, World, WorldExt};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex, Weak},
+};
+
+use specs::{Builder, Entity, World, WorldExt};
 
 use crate::{
-    render::models::{MeshFactory, Vertex},
-    Movement, Player, Renderable, Transform,
+    assets,
+    render::models::{Mesh, MeshFactory, ObjError, SceneData, SceneError},
+    Camera, Movement, Parent, Player, Renderable, Transform,
 };
 
-//TODO: Use a file loader instead of hardcoded vertices
+const GRID_MODEL_PATH: &str = "assets/models/grid.obj";
+
 //TODO: Need a way to modify vertices for a skeleton system
 pub struct EntityFactory {
     mesh_factory: MeshFactory,
+    /// Shared by every grid, loaded again once all of them are gone
+    grid_mesh: Mutex<Weak<Mesh>>,
 }
 
 impl EntityFactory {
     pub fn new(mesh_factory: MeshFactory) -> EntityFactory {
-        EntityFactory { mesh_factory }
+        EntityFactory {
+            mesh_factory,
+            grid_mesh: Mutex::new(Weak::new()),
+        }
     }
 
     pub fn create_player(&self, world: &mut World, pos: [f32; 3]) {
@@ -21,40 +33,72 @@ impl EntityFactory {
             .create_entity()
             .with(Player::default())
             .with(Movement::default())
+            .with(Camera::default())
             .with(Transform {
                 pos: pos.into(),
                 dir: uv::Rotor3::from_euler_angles(0.0f32.to_radians(), 0.0, 180.0f32.to_radians()), // Look at center from above due to colinearity
+                ..Transform::default()
             })
             .build();
     }
 
-    pub fn create_grid(&self, world: &mut World) {
-        let vertices = [
-            Vertex {
-                pos: [-0.5f32, -0.5f32],
-                color: [1.0f32, 0.0f32, 0.0f32],
-            },
-            Vertex {
-                pos: [0.5f32, -0.5f32],
-                color: [0.0f32, 1.0f32, 0.0f32],
-            },
-            Vertex {
-                pos: [0.5f32, 0.5f32],
-                color: [0.0f32, 0.0f32, 1.0f32],
-            },
-            Vertex {
-                pos: [-0.5f32, 0.5f32],
-                color: [1.0f32, 1.0f32, 1.0f32],
-            },
-        ];
-        let indices: [u16; 6] = [0, 1, 2, 2, 3, 0];
+    pub fn create_grid(&self, world: &mut World) -> Result<Entity, ObjError> {
+        let mesh = self.grid_mesh()?;
 
-        world
-            .create_entity()
-            .with(Transform::default())
-            .with(Renderable {
-                mesh: self.mesh_factory.create_mesh(&vertices, Some(&indices)),
-            })
-            .build();
+        Ok(world.create_entity().with(Transform::default()).with(Renderable::new(mesh)).build())
+    }
+
+    fn grid_mesh(&self) -> Result<Arc<Mesh>, ObjError> {
+        let mut grid_mesh = self.grid_mesh.lock().unwrap();
+
+        if let Some(mesh) = grid_mesh.upgrade() {
+            Ok(mesh)
+        } else {
+            let mesh = Arc::new(self.mesh_factory.load_obj(assets::asset_path(GRID_MODEL_PATH))?);
+            *grid_mesh = Arc::downgrade(&mesh);
+            Ok(mesh)
+        }
+    }
+
+    /// Spawns one entity per node of a glTF scene and returns them, parents before their children
+    ///
+    /// Meshes are uploaded once and shared between the nodes using them. A node whose mesh has
+    /// several primitives gets a child entity for each of them.
+    pub fn create_scene<P: AsRef<Path>>(&self, world: &mut World, path: P) -> Result<Vec<Entity>, SceneError> {
+        let scene = SceneData::load(path)?;
+        world.register::<Parent>();
+
+        let meshes = scene
+            .meshes
+            .iter()
+            .map(|primitives| primitives.iter().map(|data| Arc::new(self.mesh_factory.upload(data))).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let mut entities = Vec::<Entity>::with_capacity(scene.nodes.len());
+        for node in &scene.nodes {
+            let mut builder = world.create_entity().with(Transform {
+                pos: node.translation,
+                dir: node.rotation,
+                scale: node.scale,
+            });
+            if let Some(parent) = node.parent {
+                builder = builder.with(Parent { entity: entities[parent] });
+            }
+
+            let primitives = node.mesh.map_or(&[][..], |mesh| &meshes[mesh]);
+            if let [mesh] = primitives {
+                builder = builder.with(Renderable::new(mesh.clone()));
+            }
+            let entity = builder.build();
+
+            if primitives.len() > 1 {
+                for mesh in primitives {
+                    world.create_entity().with(Transform::default()).with(Parent { entity }).with(Renderable::new(mesh.clone())).build();
+                }
+            }
+            entities.push(entity);
+        }
+
+        Ok(entities)
     }
 }
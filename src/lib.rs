@@ -0,0 +1,12 @@
+#[macro_use]
+extern crate log;
+extern crate ultraviolet as uv;
+
+pub mod assets;
+pub mod components;
+pub mod debug_draw;
+pub mod entity_factory;
+pub mod render;
+pub mod systems;
+
+use components::*;
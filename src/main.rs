@@ -1,35 +1,48 @@
 #[macro_use]
 extern crate log;
-extern crate ultraviolet as uv;
-
-mod components;
-mod entity_factory;
-mod render;
-mod systems;
 
 use std::time::Instant;
 
-use components::*;
-use entity_factory::EntityFactory;
-use render::GraphicContext;
-use systems::*;
+use voyager::{components::*, entity_factory::EntityFactory, render::GraphicContext, systems::*};
 
 use specs::*;
 use winit::{
     event::{ElementState, Event, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    platform::windows::WindowExtWindows,
     window::WindowBuilder,
 };
 
+const HEADLESS_EXTENT: (u32, u32) = (1280, 720);
+
 fn main() {
     env_logger::init();
     debug!("Program started");
 
+    // Usage: voyager [--headless <frames> [output.png]]
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.get(1).map(String::as_str) == Some("--headless") {
+        let frames = args.get(2).map_or(1, |frames| frames.parse::<usize>().expect("Frame count must be a number"));
+        run_headless(frames, args.get(3).map(String::as_str));
+    } else {
+        run_windowed();
+    }
+}
+
+fn populate_world(world: &mut World, entity_factory: &EntityFactory) {
+    // Player
+    entity_factory.create_player(world, [0.0, 0.0, 4.0]);
+
+    // XY Grid
+    if let Err(err) = entity_factory.create_grid(world) {
+        error!("Failed to load the grid: {}", err);
+    }
+}
+
+fn run_windowed() {
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new().with_title("Voyager 0.01").build(&event_loop).unwrap();
 
-    let graphic_context = GraphicContext::new(window.hwnd(), window.hinstance());
+    let graphic_context = GraphicContext::new(&window, window.inner_size().into());
     let entity_factory = EntityFactory::new(graphic_context.create_mesh_factory());
 
     let mut world = World::new();
@@ -40,11 +53,7 @@ fn main() {
         .build();
     dispatcher.setup(&mut world);
 
-    // Player
-    entity_factory.create_player(&mut world, [0.0, 0.0, 4.0]);
-
-    // XY Grid
-    entity_factory.create_grid(&mut world);
+    populate_world(&mut world, &entity_factory);
 
     let mut last_frame = Instant::now();
     event_loop.run(move |event, _, control_flow| {
@@ -74,3 +83,32 @@ fn main() {
         }
     });
 }
+
+fn run_headless(frames: usize, output: Option<&str>) {
+    let graphic_context = GraphicContext::new_headless(HEADLESS_EXTENT).unwrap();
+    let entity_factory = EntityFactory::new(graphic_context.create_mesh_factory());
+
+    let mut world = World::new();
+    let mut dispatcher = DispatcherBuilder::new()
+        .with(ControlSystem::new(), "Control", &[])
+        .with(MoveSystem::new(), "Move", &[])
+        .with_thread_local(RenderSystem::headless(graphic_context))
+        .build();
+    dispatcher.setup(&mut world);
+
+    populate_world(&mut world, &entity_factory);
+
+    let mut last_frame = Instant::now();
+    for _ in 0..frames {
+        world.write_resource::<DeltaTime>().delta = last_frame.elapsed();
+        last_frame = Instant::now();
+
+        dispatcher.dispatch(&world);
+        world.maintain();
+    }
+
+    if let Some(output) = output {
+        world.read_resource::<FrameCapture>().save_png(output).unwrap();
+        info!("Wrote frame {} to {}", frames, output);
+    }
+}
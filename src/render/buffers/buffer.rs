@@ -2,7 +2,7 @@ use std::sync::Arc;
 
 use ash::{version::DeviceV1_0, vk};
 
-use crate::render::{device::Device, VulkanObject};
+use crate::render::{commands::submit_single_time_commands, device::Device, VulkanObject};
 
 pub struct Buffer {
     device: Arc<Device>,
@@ -32,7 +32,7 @@ impl Buffer {
         Buffer { device, buffer, buffer_memory }
     }
 
-    fn find_memory_type(type_filter: u32, properties: vk::MemoryPropertyFlags, physical_mem_properties: &vk::PhysicalDeviceMemoryProperties) -> u32 {
+    pub fn find_memory_type(type_filter: u32, properties: vk::MemoryPropertyFlags, physical_mem_properties: &vk::PhysicalDeviceMemoryProperties) -> u32 {
         for i in 0..physical_mem_properties.memory_type_count {
             if (type_filter & (1 << i)) > 0 && (physical_mem_properties.memory_types[i as usize].property_flags & properties) == properties {
                 return i;
@@ -55,28 +55,23 @@ impl Buffer {
         }
     }
 
-    //TODO: Move this to some sort of common command buffer commands class
-    pub fn copy_buffer(src: &Buffer, dst: &Buffer, size: vk::DeviceSize, device: &Arc<Device>) {
-        let command_pool = device.command_pool();
-        let alloc_info = vk::CommandBufferAllocateInfo::builder()
-            .command_pool(*command_pool.vk())
-            .level(vk::CommandBufferLevel::PRIMARY)
-            .command_buffer_count(1)
-            .build();
+    pub fn read_memory(&self, size: vk::DeviceSize) -> Vec<u8> {
+        unsafe {
+            let data_ptr = self.device.vk().map_memory(self.buffer_memory, 0, size, vk::MemoryMapFlags::empty()).unwrap();
 
-        let command_buffers = unsafe { device.vk().allocate_command_buffers(&alloc_info).unwrap() };
+            let data = std::slice::from_raw_parts(data_ptr as *const u8, size as usize).to_vec();
 
-        let begin_info = vk::CommandBufferBeginInfo::builder().flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT).build();
+            self.device.vk().unmap_memory(self.buffer_memory);
+            data
+        }
+    }
+
+    pub fn copy_buffer(src: &Buffer, dst: &Buffer, size: vk::DeviceSize, device: &Arc<Device>) {
         let copy_region = vk::BufferCopy::builder().size(size).build();
-        let submit_info = vk::SubmitInfo::builder().command_buffers(&command_buffers).build();
 
-        unsafe {
-            device.vk().begin_command_buffer(command_buffers[0], &begin_info).unwrap();
-            device.vk().cmd_copy_buffer(command_buffers[0], *src.vk(), *dst.vk(), &[copy_region]);
-            device.vk().end_command_buffer(command_buffers[0]).unwrap();
-            device.vk().queue_submit(*device.graphics_queue(), &[submit_info], vk::Fence::null()).unwrap();
-            device.vk().device_wait_idle().unwrap();
-        }
+        submit_single_time_commands(device, |command_buffer| unsafe {
+            device.vk().cmd_copy_buffer(command_buffer, *src.vk(), *dst.vk(), &[copy_region]);
+        });
     }
 }
 
@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use super::Buffer;
+use crate::render::{device::Device, models::InstanceData, VulkanObject};
+
+use ash::vk;
+
+/// Host visible per instance vertex buffer rewritten every frame, grows to fit the instances drawn
+pub struct InstanceBuffer {
+    device: Arc<Device>,
+    capacity: usize,
+    buffer: Buffer,
+}
+
+impl InstanceBuffer {
+    const MIN_CAPACITY: usize = 64;
+
+    pub fn new(device: &Arc<Device>) -> InstanceBuffer {
+        InstanceBuffer {
+            device: device.clone(),
+            capacity: Self::MIN_CAPACITY,
+            buffer: Self::create_buffer(device, Self::MIN_CAPACITY),
+        }
+    }
+
+    fn create_buffer(device: &Arc<Device>, capacity: usize) -> Buffer {
+        Buffer::new(
+            (capacity * std::mem::size_of::<InstanceData>()) as vk::DeviceSize,
+            vk::BufferUsageFlags::VERTEX_BUFFER,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            device.clone(),
+        )
+    }
+
+    /// The buffer must not be in use by a pending command buffer
+    pub fn update(&mut self, instances: &[InstanceData]) {
+        if instances.len() > self.capacity {
+            self.capacity = instances.len().next_power_of_two();
+            self.buffer = Self::create_buffer(&self.device, self.capacity);
+        }
+
+        if !instances.is_empty() {
+            self.buffer.map_memory::<f32, _>(instances);
+        }
+    }
+}
+
+impl VulkanObject for InstanceBuffer {
+    type Object = vk::Buffer;
+
+    fn vk(&self) -> &Self::Object {
+        self.buffer.vk()
+    }
+}
+
+impl Drop for InstanceBuffer {
+    fn drop(&mut self) {
+        trace!("Dropping Instance Buffer");
+    }
+}
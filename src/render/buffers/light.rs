@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use super::Buffer;
+use crate::render::{
+    constants::{MAX_LIGHTS, MAX_SHADOW_MAPS},
+    device::Device,
+    VulkanObject,
+};
+
+use ash::vk;
+
+/// One light as read by the lit shaders, positions and directions are in world space
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LightData {
+    /// Position, range in `w`
+    position: [f32; 4],
+    /// Direction the light shines in, kind in `w`: 0 directional, 1 point, 2 spot
+    direction: [f32; 4],
+    /// Color premultiplied by the intensity
+    color: [f32; 4],
+    /// Cosines of the inner and outer spot cone angles
+    cone: [f32; 4],
+    /// First shadow map layer of the light, or -1 without shadows, and the number of layers it uses
+    shadow: [f32; 4],
+}
+
+impl LightData {
+    const DIRECTIONAL: f32 = 0.0;
+    const POINT: f32 = 1.0;
+    const SPOT: f32 = 2.0;
+    const NO_SHADOW: [f32; 4] = [-1.0, 0.0, 0.0, 0.0];
+
+    pub fn directional(direction: uv::Vec3, color: [f32; 3], intensity: f32) -> LightData {
+        let direction = direction.normalized();
+        LightData {
+            position: [0.0; 4],
+            direction: [direction.x, direction.y, direction.z, Self::DIRECTIONAL],
+            color: Self::scale(color, intensity),
+            cone: [0.0; 4],
+            shadow: Self::NO_SHADOW,
+        }
+    }
+
+    /// Light fading out to nothing at `range`
+    pub fn point(position: uv::Vec3, range: f32, color: [f32; 3], intensity: f32) -> LightData {
+        LightData {
+            position: [position.x, position.y, position.z, range],
+            direction: [0.0, 0.0, 0.0, Self::POINT],
+            color: Self::scale(color, intensity),
+            cone: [0.0; 4],
+            shadow: Self::NO_SHADOW,
+        }
+    }
+
+    /// Point light restricted to a cone, fully lit within `inner_angle` and fading out up to `outer_angle` radians
+    pub fn spot(position: uv::Vec3, direction: uv::Vec3, range: f32, inner_angle: f32, outer_angle: f32, color: [f32; 3], intensity: f32) -> LightData {
+        let direction = direction.normalized();
+        let outer_angle = outer_angle.max(inner_angle);
+        LightData {
+            position: [position.x, position.y, position.z, range],
+            direction: [direction.x, direction.y, direction.z, Self::SPOT],
+            color: Self::scale(color, intensity),
+            cone: [inner_angle.cos(), outer_angle.cos(), 0.0, 0.0],
+            shadow: Self::NO_SHADOW,
+        }
+    }
+
+    /// Makes the light read its shadows from `layers` shadow map layers starting at `first_layer`
+    pub fn set_shadow(&mut self, first_layer: usize, layers: usize) {
+        self.shadow = [first_layer as f32, layers as f32, 0.0, 0.0];
+    }
+
+    pub fn position(&self) -> [f32; 4] {
+        self.position
+    }
+
+    pub fn direction(&self) -> [f32; 4] {
+        self.direction
+    }
+
+    pub fn color(&self) -> [f32; 4] {
+        self.color
+    }
+
+    pub fn cone(&self) -> [f32; 4] {
+        self.cone
+    }
+
+    pub fn shadow(&self) -> [f32; 4] {
+        self.shadow
+    }
+
+    fn scale(color: [f32; 3], intensity: f32) -> [f32; 4] {
+        [color[0] * intensity, color[1] * intensity, color[2] * intensity, 1.0]
+    }
+}
+
+/// Uniform block of the lights, laid out to match std140
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct LightBufferObject {
+    ambient: [f32; 4],
+    count: u32,
+    _padding: [u32; 3],
+    lights: [LightData; MAX_LIGHTS],
+    /// World to shadow map clip space of each shadow map layer
+    shadow_matrices: [uv::Mat4; MAX_SHADOW_MAPS],
+}
+
+/// Host visible uniform buffer holding the lights of one frame
+pub struct LightBuffer {
+    buffer: Buffer,
+}
+
+impl LightBuffer {
+    pub fn new(device: &Arc<Device>) -> LightBuffer {
+        let buffer = Buffer::new(
+            Self::size(),
+            vk::BufferUsageFlags::UNIFORM_BUFFER,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            device.clone(),
+        );
+
+        let light_buffer = LightBuffer { buffer };
+        light_buffer.update([0.0; 3], &[], &[]);
+        light_buffer
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    pub fn size() -> vk::DeviceSize {
+        std::mem::size_of::<LightBufferObject>() as vk::DeviceSize
+    }
+
+    /// Only the first `MAX_LIGHTS` lights and `MAX_SHADOW_MAPS` matrices are kept, the buffer must not be in use by a
+    /// pending command buffer
+    pub fn update(&self, ambient: [f32; 3], lights: &[LightData], shadow_matrices: &[uv::Mat4]) {
+        let count = lights.len().min(MAX_LIGHTS);
+        let mut object = LightBufferObject {
+            ambient: [ambient[0], ambient[1], ambient[2], 1.0],
+            count: count as u32,
+            _padding: [0; 3],
+            lights: [LightData::default(); MAX_LIGHTS],
+            shadow_matrices: [uv::Mat4::identity(); MAX_SHADOW_MAPS],
+        };
+        object.lights[..count].copy_from_slice(&lights[..count]);
+        let matrix_count = shadow_matrices.len().min(MAX_SHADOW_MAPS);
+        object.shadow_matrices[..matrix_count].copy_from_slice(&shadow_matrices[..matrix_count]);
+
+        self.buffer.map_memory::<f32, _>(&[object]);
+    }
+}
+
+impl VulkanObject for LightBuffer {
+    type Object = vk::Buffer;
+
+    fn vk(&self) -> &Self::Object {
+        self.buffer.vk()
+    }
+}
+
+impl Drop for LightBuffer {
+    fn drop(&mut self) {
+        trace!("Dropping Light Buffer");
+    }
+}
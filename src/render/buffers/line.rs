@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use super::Buffer;
+use crate::render::{device::Device, models::Vertex, VulkanObject};
+
+use ash::vk;
+
+/// Host visible vertex buffer of debug lines rewritten every frame, grows to fit the lines drawn
+pub struct LineBuffer {
+    device: Arc<Device>,
+    capacity: usize,
+    buffer: Buffer,
+    /// Vertices written by the last update, two per line
+    vertex_count: u32,
+}
+
+impl LineBuffer {
+    const MIN_CAPACITY: usize = 64;
+
+    pub fn new(device: &Arc<Device>) -> LineBuffer {
+        LineBuffer {
+            device: device.clone(),
+            capacity: Self::MIN_CAPACITY,
+            buffer: Self::create_buffer(device, Self::MIN_CAPACITY),
+            vertex_count: 0,
+        }
+    }
+
+    fn create_buffer(device: &Arc<Device>, capacity: usize) -> Buffer {
+        Buffer::new(
+            (capacity * std::mem::size_of::<Vertex>()) as vk::DeviceSize,
+            vk::BufferUsageFlags::VERTEX_BUFFER,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            device.clone(),
+        )
+    }
+
+    /// The buffer must not be in use by a pending command buffer
+    pub fn update(&mut self, vertices: &[Vertex]) {
+        if vertices.len() > self.capacity {
+            self.capacity = vertices.len().next_power_of_two();
+            self.buffer = Self::create_buffer(&self.device, self.capacity);
+        }
+
+        if !vertices.is_empty() {
+            self.buffer.map_memory::<f32, _>(vertices);
+        }
+        self.vertex_count = vertices.len() as u32;
+    }
+
+    pub fn vertex_count(&self) -> u32 {
+        self.vertex_count
+    }
+}
+
+impl VulkanObject for LineBuffer {
+    type Object = vk::Buffer;
+
+    fn vk(&self) -> &Self::Object {
+        self.buffer.vk()
+    }
+}
+
+impl Drop for LineBuffer {
+    fn drop(&mut self) {
+        trace!("Dropping Line Buffer");
+    }
+}
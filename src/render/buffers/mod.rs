@@ -1,9 +1,15 @@
 mod buffer;
 mod index;
+mod instance;
+mod light;
+mod line;
 mod uniform;
 mod vertex;
 
 pub use buffer::Buffer;
 pub use index::IndexBuffer;
+pub use instance::InstanceBuffer;
+pub use light::{LightBuffer, LightData};
+pub use line::LineBuffer;
 pub use uniform::{UniformBufferObject, UniformTestObject};
 pub use vertex::VertexBuffer;
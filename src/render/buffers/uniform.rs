@@ -8,9 +8,10 @@ use ash::vk;
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
 pub struct UniformTestObject {
-    pub model: uv::Mat4,
     pub view: uv::Mat4,
     pub proj: uv::Mat4,
+    /// World position of the camera, `w` is unused
+    pub camera_pos: uv::Vec4,
 }
 
 impl UniformTestObject {
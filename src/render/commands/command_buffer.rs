@@ -31,11 +31,22 @@ impl CommandBuffer {
         .into()
     }
 
-    pub fn begin(&self, index: usize, render_pass_info: &vk::RenderPassBeginInfo) {
+    pub fn begin(&self, index: usize) {
         let begin_info = vk::CommandBufferBeginInfo::default();
         unsafe {
             self.device.vk().begin_command_buffer(self.command_buffers[index], &begin_info).unwrap();
-            self.device.vk().cmd_begin_render_pass(self.command_buffers[index], &render_pass_info, vk::SubpassContents::INLINE);
+        };
+    }
+
+    pub fn begin_render_pass(&self, index: usize, render_pass_info: &vk::RenderPassBeginInfo) {
+        unsafe {
+            self.device.vk().cmd_begin_render_pass(self.command_buffers[index], render_pass_info, vk::SubpassContents::INLINE);
+        };
+    }
+
+    pub fn end_render_pass(&self, index: usize) {
+        unsafe {
+            self.device.vk().cmd_end_render_pass(self.command_buffers[index]);
         };
     }
 
@@ -57,18 +68,24 @@ impl CommandBuffer {
         }
     }
 
-    pub fn bind_descriptor_sets(&self, index: usize, pipeline_layout: &vk::PipelineLayout, descriptor_sets: &[vk::DescriptorSet]) {
+    /// Clears regions of the current subpass attachments without ending the render pass
+    pub fn clear_attachments(&self, index: usize, attachments: &[vk::ClearAttachment], rects: &[vk::ClearRect]) {
+        unsafe {
+            self.device.vk().cmd_clear_attachments(self.command_buffers[index], attachments, rects);
+        }
+    }
+
+    pub fn bind_descriptor_sets(&self, index: usize, pipeline_layout: &vk::PipelineLayout, first_set: u32, descriptor_sets: &[vk::DescriptorSet]) {
         unsafe {
             let null = [];
             self.device
                 .vk()
-                .cmd_bind_descriptor_sets(self.command_buffers[index], vk::PipelineBindPoint::GRAPHICS, *pipeline_layout, 0, descriptor_sets, &null);
+                .cmd_bind_descriptor_sets(self.command_buffers[index], vk::PipelineBindPoint::GRAPHICS, *pipeline_layout, first_set, descriptor_sets, &null);
         }
     }
 
     pub fn end(&self, index: usize) {
         unsafe {
-            self.device.vk().cmd_end_render_pass(self.command_buffers[index]);
             self.device.vk().end_command_buffer(self.command_buffers[index]).unwrap();
         };
     }
@@ -1,5 +1,7 @@
 mod command_buffer;
 mod command_pool;
+mod single_time;
 
 pub use command_buffer::CommandBuffer;
 pub use command_pool::CommandPool;
+pub use single_time::submit_single_time_commands;
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::render::{device::Device, VulkanObject};
+
+use ash::{version::DeviceV1_0, vk};
+
+// Records a one off command buffer and blocks until the graphics queue has executed it
+pub fn submit_single_time_commands<F: FnOnce(vk::CommandBuffer)>(device: &Arc<Device>, record: F) {
+    let command_pool = device.command_pool();
+    let alloc_info = vk::CommandBufferAllocateInfo::builder()
+        .command_pool(*command_pool.vk())
+        .level(vk::CommandBufferLevel::PRIMARY)
+        .command_buffer_count(1)
+        .build();
+
+    let command_buffers = unsafe { device.vk().allocate_command_buffers(&alloc_info).unwrap() };
+
+    let begin_info = vk::CommandBufferBeginInfo::builder().flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT).build();
+    let submit_info = vk::SubmitInfo::builder().command_buffers(&command_buffers).build();
+
+    unsafe {
+        device.vk().begin_command_buffer(command_buffers[0], &begin_info).unwrap();
+        record(command_buffers[0]);
+        device.vk().end_command_buffer(command_buffers[0]).unwrap();
+        device.vk().queue_submit(*device.graphics_queue(), &[submit_info], vk::Fence::null()).unwrap();
+        device.vk().queue_wait_idle(*device.graphics_queue()).unwrap();
+        device.vk().free_command_buffers(*command_pool.vk(), &command_buffers);
+    }
+}
@@ -1 +1,18 @@
+use ash::vk;
+
 pub const MAX_FRAMES_IN_FLIGHT: usize = 2;
+/// Encodes the frame to sRGB like the swapchain's format, so captures look the same as the window
+pub const HEADLESS_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+/// Format the scene and render textures are drawn in, before post processing brings the frame into the target's range
+pub const HDR_FORMAT: vk::Format = vk::Format::R16G16B16A16_SFLOAT;
+/// Views drawn into one frame, each one needs its own uniform buffer per swapchain image
+pub const MAX_VIEWS: usize = 4;
+/// Lights in the light uniform buffer, has to match the array size in the lit shaders
+pub const MAX_LIGHTS: usize = 16;
+/// Layers of the shadow map, shared by the cascades of directional lights and one layer per spot light
+pub const MAX_SHADOW_MAPS: usize = 8;
+/// Width and height of each shadow map layer
+pub const SHADOW_MAP_SIZE: u32 = 1024;
+pub const CLEAR_DEPTH: vk::ClearValue = vk::ClearValue {
+    depth_stencil: vk::ClearDepthStencilValue { depth: 1.0, stencil: 0 },
+};
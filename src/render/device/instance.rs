@@ -1,17 +1,15 @@
 use crate::render::VulkanObject;
 
 use ash::{
-    extensions::{
-        ext::DebugUtils,
-        khr::{Surface, Win32Surface},
-    },
+    extensions::{ext::DebugUtils, khr::Surface},
     version::{EntryV1_0, InstanceV1_0},
-    vk, Entry,
+    vk, Entry, InstanceError,
 };
+use raw_window_handle::RawWindowHandle;
 
 use std::{ffi::CString, os::raw::c_void, ptr, sync::Arc};
 
-use super::DebugMessenger;
+use super::{DebugMessenger, DeviceError};
 
 pub struct Instance {
     entry: Entry,
@@ -19,8 +17,8 @@ pub struct Instance {
 }
 
 impl Instance {
-    pub fn new(validation: bool) -> Arc<Self> {
-        let entry = Entry::new().unwrap();
+    pub fn new(validation: bool, window_handle: Option<&RawWindowHandle>) -> Result<Arc<Self>, DeviceError> {
+        let entry = Entry::new().map_err(|error| DeviceError::NoVulkan(error.to_string()))?;
 
         if validation && !DebugMessenger::check_validation_layer_support(&entry) {
             panic!("Validation layers requested not supported");
@@ -38,7 +36,10 @@ impl Instance {
 
         let (_names, validation_layers) = DebugMessenger::get_validation_layers_vk();
 
-        let mut extensions = Self::required_extension_names();
+        let mut extensions = match window_handle {
+            Some(window_handle) => Self::required_extension_names(window_handle),
+            None => Vec::new(),
+        };
         if validation {
             extensions.push(DebugUtils::name().as_ptr());
         }
@@ -59,13 +60,30 @@ impl Instance {
             ..Default::default()
         };
 
-        let instance: ash::Instance = unsafe { entry.create_instance(&create_info, None).expect("Failed to create instance") };
+        // The loader reports an incompatible driver when it found none at all
+        let instance: ash::Instance = match unsafe { entry.create_instance(&create_info, None) } {
+            Ok(instance) => instance,
+            Err(InstanceError::VkError(vk::Result::ERROR_INCOMPATIBLE_DRIVER)) => return Err(DeviceError::NoVulkan("no driver found".to_string())),
+            Err(error) => panic!("Failed to create instance: {}", error),
+        };
 
-        Arc::new(Instance { entry, instance })
+        Ok(Arc::new(Instance { entry, instance }))
     }
 
-    fn required_extension_names() -> Vec<*const i8> {
-        vec![Surface::name().as_ptr(), Win32Surface::name().as_ptr()]
+    fn required_extension_names(window_handle: &RawWindowHandle) -> Vec<*const i8> {
+        let platform_surface = match window_handle {
+            #[cfg(windows)]
+            RawWindowHandle::Windows(_) => ash::extensions::khr::Win32Surface::name(),
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            RawWindowHandle::Xlib(_) => ash::extensions::khr::XlibSurface::name(),
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            RawWindowHandle::Xcb(_) => ash::extensions::khr::XcbSurface::name(),
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            RawWindowHandle::Wayland(_) => ash::extensions::khr::WaylandSurface::name(),
+            _ => panic!("Unsupported window handle"),
+        };
+
+        vec![Surface::name().as_ptr(), platform_surface.as_ptr()]
     }
 
     pub fn entry(&self) -> &Entry {
@@ -21,14 +21,36 @@ use ash::{
 
 use std::{
     collections::HashSet,
+    error::Error,
+    fmt,
     sync::{Arc, Mutex, Weak},
 };
 
+/// Vulkan can't be used on this machine at all, unlike failures of a device which is there
+#[derive(Debug)]
+pub enum DeviceError {
+    /// The Vulkan loader is missing or found no driver
+    NoVulkan(String),
+    NoSuitableDevice,
+}
+
+impl fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceError::NoVulkan(message) => write!(f, "Vulkan is not available: {}", message),
+            DeviceError::NoSuitableDevice => write!(f, "No suitable physical device"),
+        }
+    }
+}
+
+impl Error for DeviceError {}
+
 pub struct Device {
     physical_device: PhysicalDevice,
     logical_device: ash::Device,
     graphics_queue: vk::Queue,
     present_queue: vk::Queue,
+    features: vk::PhysicalDeviceFeatures,
 
     command_pool: Mutex<Weak<CommandPool>>,
     descriptor_pool: Mutex<Weak<DescriptorPool>>,
@@ -44,8 +66,11 @@ impl Device {
             queue_create_infos.push(vk::DeviceQueueCreateInfo::builder().queue_family_index(queue_index).queue_priorities(&priority).build());
         }
 
-        let device_features = vk::PhysicalDeviceFeatures::default();
-        let extensions = PhysicalDevice::required_extension_names();
+        let device_features = vk::PhysicalDeviceFeatures::builder()
+            .sampler_anisotropy(physical_device.get_features().sampler_anisotropy == vk::TRUE)
+            .fill_mode_non_solid(physical_device.get_features().fill_mode_non_solid == vk::TRUE)
+            .build();
+        let extensions = PhysicalDevice::required_extension_names(physical_device.is_presentable());
         let (_names, validation_layers) = DebugMessenger::get_validation_layers_vk();
         let create_info = vk::DeviceCreateInfo::builder()
             .queue_create_infos(&queue_create_infos)
@@ -69,6 +94,7 @@ impl Device {
             logical_device: device,
             graphics_queue,
             present_queue,
+            features: device_features,
             command_pool: Mutex::new(Weak::new()),
             descriptor_pool: Mutex::new(Weak::new()),
         })
@@ -82,6 +108,11 @@ impl Device {
         &self.present_queue
     }
 
+    /// Features enabled when the device was created
+    pub fn features(&self) -> &vk::PhysicalDeviceFeatures {
+        &self.features
+    }
+
     pub fn physical_device(&self) -> &PhysicalDevice {
         &self.physical_device
     }
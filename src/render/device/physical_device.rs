@@ -1,4 +1,4 @@
-use super::{Instance, QueueFamily, Surface};
+use super::{DeviceError, Instance, QueueFamily, Surface};
 use crate::render::{renderpasses::SwapChain, utilities::*, VulkanObject};
 
 use ash::{version::InstanceV1_0, vk};
@@ -9,30 +9,38 @@ pub struct PhysicalDevice {
     instance: Arc<Instance>,
     physical_device: vk::PhysicalDevice,
     mem_properties: vk::PhysicalDeviceMemoryProperties,
+    properties: vk::PhysicalDeviceProperties,
+    features: vk::PhysicalDeviceFeatures,
     graphics_index: u32,
     present_index: u32,
+    presentable: bool,
 }
 
 impl PhysicalDevice {
-    pub fn new(instance: Arc<Instance>, surface: &Arc<Surface>) -> Self {
-        let physical_device = Self::pick_suitable_device(&instance, surface);
+    pub fn new(instance: Arc<Instance>, surface: Option<&Arc<Surface>>) -> Result<Self, DeviceError> {
+        let physical_device = Self::pick_suitable_device(&instance, surface).ok_or(DeviceError::NoSuitableDevice)?;
         let (graphics_index, present_index) = Self::get_queue_indices(&instance, physical_device, surface).unwrap();
         let mem_properties = unsafe { instance.vk().get_physical_device_memory_properties(physical_device) };
+        let properties = unsafe { instance.vk().get_physical_device_properties(physical_device) };
+        let features = unsafe { instance.vk().get_physical_device_features(physical_device) };
 
-        PhysicalDevice {
+        Ok(PhysicalDevice {
             instance,
             physical_device,
             mem_properties,
+            properties,
+            features,
             graphics_index,
             present_index,
-        }
+            presentable: surface.is_some(),
+        })
     }
 
     pub fn instance(&self) -> &Arc<Instance> {
         &self.instance
     }
 
-    fn pick_suitable_device(instance: &Arc<Instance>, surface: &Arc<Surface>) -> vk::PhysicalDevice {
+    fn pick_suitable_device(instance: &Arc<Instance>, surface: Option<&Arc<Surface>>) -> Option<vk::PhysicalDevice> {
         let physical_devices = unsafe { instance.vk().enumerate_physical_devices().expect("Failed to enumerate physical devices") };
         debug!("{} devices (GPU) found with vulkan support.", physical_devices.len());
 
@@ -44,16 +52,13 @@ impl PhysicalDevice {
             }
         }
 
-        match result {
-            None => panic!("No suitable physical device"),
-            Some(device) => device,
-        }
+        result
     }
 
-    fn is_device_suitable(instance: &Arc<Instance>, device: vk::PhysicalDevice, surface: &Arc<Surface>) -> bool {
-        let extensions_supported = Self::check_device_extension_support(instance, device);
+    fn is_device_suitable(instance: &Arc<Instance>, device: vk::PhysicalDevice, surface: Option<&Arc<Surface>>) -> bool {
+        let extensions_supported = Self::check_device_extension_support(instance, device, surface.is_some());
         let mut swapchain_adequate = true;
-        if extensions_supported {
+        if let (true, Some(surface)) = (extensions_supported, surface) {
             let swapchain_support = SwapChain::query_support(device, surface);
             swapchain_adequate = !swapchain_support.formats.is_empty() && !swapchain_support.present_modes.is_empty();
         }
@@ -61,7 +66,8 @@ impl PhysicalDevice {
         Self::get_queue_indices(instance, device, surface).is_ok() && extensions_supported && swapchain_adequate
     }
 
-    fn get_queue_indices(instance: &Arc<Instance>, device: vk::PhysicalDevice, surface: &Arc<Surface>) -> Result<(u32, u32), &'static str> {
+    // Without a surface nothing is presented, so the graphics queue doubles as the present queue
+    fn get_queue_indices(instance: &Arc<Instance>, device: vk::PhysicalDevice, surface: Option<&Arc<Surface>>) -> Result<(u32, u32), &'static str> {
         let queue_families = QueueFamily::all(instance.vk(), device);
 
         let mut graphics_index = None;
@@ -72,8 +78,12 @@ impl PhysicalDevice {
                     graphics_index = Some(queue_family.index);
                 }
 
-                if present_index.is_none() && unsafe { surface.get_loader().get_physical_device_surface_support(device, queue_family.index, *surface.vk()).unwrap() } {
-                    present_index = Some(queue_family.index)
+                if present_index.is_none() {
+                    present_index = match surface {
+                        Some(surface) if unsafe { surface.get_loader().get_physical_device_surface_support(device, queue_family.index, *surface.vk()).unwrap() } => Some(queue_family.index),
+                        None if queue_family.flags.contains(vk::QueueFlags::GRAPHICS) => Some(queue_family.index),
+                        _ => None,
+                    };
                 }
             }
         }
@@ -87,9 +97,12 @@ impl PhysicalDevice {
         }
     }
 
-    fn check_device_extension_support(instance: &Arc<Instance>, device: vk::PhysicalDevice) -> bool {
+    fn check_device_extension_support(instance: &Arc<Instance>, device: vk::PhysicalDevice, presentable: bool) -> bool {
         let available_extensions = unsafe { instance.vk().enumerate_device_extension_properties(device).unwrap() };
-        let required_extensions: Vec<&str> = Self::required_extension_names().iter().map(|name| unsafe { CStr::from_ptr(*name).to_str().unwrap() }).collect();
+        let required_extensions: Vec<&str> = Self::required_extension_names(presentable)
+            .iter()
+            .map(|name| unsafe { CStr::from_ptr(*name).to_str().unwrap() })
+            .collect();
 
         debug!("Device Extensions Available:");
         for extension in available_extensions.iter() {
@@ -108,8 +121,17 @@ impl PhysicalDevice {
         result
     }
 
-    pub fn required_extension_names() -> Vec<*const i8> {
-        vec![ash::extensions::khr::Swapchain::name().as_ptr()]
+    pub fn required_extension_names(presentable: bool) -> Vec<*const i8> {
+        if presentable {
+            vec![ash::extensions::khr::Swapchain::name().as_ptr()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Whether the device was picked against a surface and can present to it
+    pub fn is_presentable(&self) -> bool {
+        self.presentable
     }
 
     pub fn graphics_index(&self) -> u32 {
@@ -123,6 +145,56 @@ impl PhysicalDevice {
     pub fn get_mem_properties(&self) -> &vk::PhysicalDeviceMemoryProperties {
         &self.mem_properties
     }
+
+    pub fn get_properties(&self) -> &vk::PhysicalDeviceProperties {
+        &self.properties
+    }
+
+    /// Features the device supports, not all of them are enabled on the logical `Device`
+    pub fn get_features(&self) -> &vk::PhysicalDeviceFeatures {
+        &self.features
+    }
+
+    /// Whether optimally tiled images of `format` can be the source of a linearly filtered blit
+    pub fn supports_linear_blit(&self, format: vk::Format) -> bool {
+        let properties = unsafe { self.instance.vk().get_physical_device_format_properties(self.physical_device, format) };
+        properties
+            .optimal_tiling_features
+            .contains(vk::FormatFeatureFlags::BLIT_SRC | vk::FormatFeatureFlags::BLIT_DST | vk::FormatFeatureFlags::SAMPLED_IMAGE_FILTER_LINEAR)
+    }
+
+    /// Highest sample count up to `requested` which color and depth attachments both support
+    pub fn supported_samples(&self, requested: u32) -> vk::SampleCountFlags {
+        let limits = &self.properties.limits;
+        let supported = limits.framebuffer_color_sample_counts & limits.framebuffer_depth_sample_counts;
+        let candidates = [
+            (64, vk::SampleCountFlags::TYPE_64),
+            (32, vk::SampleCountFlags::TYPE_32),
+            (16, vk::SampleCountFlags::TYPE_16),
+            (8, vk::SampleCountFlags::TYPE_8),
+            (4, vk::SampleCountFlags::TYPE_4),
+            (2, vk::SampleCountFlags::TYPE_2),
+        ];
+
+        candidates
+            .iter()
+            .find(|&&(count, flag)| count <= requested && supported.contains(flag))
+            .map_or(vk::SampleCountFlags::TYPE_1, |&(_, flag)| flag)
+    }
+
+    /// Picks the first depth format usable as an optimally tiled depth attachment
+    pub fn find_depth_format(&self) -> vk::Format {
+        let candidates = [vk::Format::D32_SFLOAT, vk::Format::D32_SFLOAT_S8_UINT, vk::Format::D24_UNORM_S8_UINT];
+
+        candidates
+            .iter()
+            .copied()
+            .find(|&format| {
+                let properties = unsafe { self.instance.vk().get_physical_device_format_properties(self.physical_device, format) };
+                properties.optimal_tiling_features.contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
+            })
+            .expect("No supported depth format")
+    }
 }
 
 impl VulkanObject for PhysicalDevice {
@@ -1,11 +1,9 @@
 use std::sync::Arc;
 
-use crate::render::{
-    device::window::{HINSTANCE, HWND},
-    VulkanObject,
-};
+use crate::render::{device::Window, VulkanObject};
 
 use ash::{extensions::khr, vk};
+use raw_window_handle::RawWindowHandle;
 
 use super::Instance;
 
@@ -16,15 +14,9 @@ pub struct Surface {
 }
 
 impl Surface {
-    pub fn new(hwnd: HWND, hinstance: HINSTANCE, instance: Arc<Instance>) -> Arc<Self> {
-        let create_info = vk::Win32SurfaceCreateInfoKHR::builder().hwnd(hwnd).hinstance(hinstance).build();
-
+    pub fn new(window: &Window, instance: Arc<Instance>) -> Arc<Self> {
         let surface_loader = khr::Surface::new(instance.entry(), instance.vk());
-        let surface = unsafe {
-            khr::Win32Surface::new(instance.entry(), instance.vk())
-                .create_win32_surface(&create_info, None)
-                .expect("Failed to create a window surface")
-        };
+        let surface = unsafe { Self::create_platform_surface(window.handle(), &instance).expect("Failed to create a window surface") };
 
         Arc::new(Surface {
             _instance: instance,
@@ -33,6 +25,32 @@ impl Surface {
         })
     }
 
+    unsafe fn create_platform_surface(handle: &RawWindowHandle, instance: &Arc<Instance>) -> ash::prelude::VkResult<vk::SurfaceKHR> {
+        match handle {
+            #[cfg(windows)]
+            RawWindowHandle::Windows(handle) => {
+                let create_info = vk::Win32SurfaceCreateInfoKHR::builder().hwnd(handle.hwnd).hinstance(handle.hinstance).build();
+                khr::Win32Surface::new(instance.entry(), instance.vk()).create_win32_surface(&create_info, None)
+            }
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            RawWindowHandle::Xlib(handle) => {
+                let create_info = vk::XlibSurfaceCreateInfoKHR::builder().dpy(handle.display as *mut vk::Display).window(handle.window).build();
+                khr::XlibSurface::new(instance.entry(), instance.vk()).create_xlib_surface(&create_info, None)
+            }
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            RawWindowHandle::Xcb(handle) => {
+                let create_info = vk::XcbSurfaceCreateInfoKHR::builder().connection(handle.connection).window(handle.window).build();
+                khr::XcbSurface::new(instance.entry(), instance.vk()).create_xcb_surface(&create_info, None)
+            }
+            #[cfg(any(target_os = "linux", target_os = "dragonfly", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+            RawWindowHandle::Wayland(handle) => {
+                let create_info = vk::WaylandSurfaceCreateInfoKHR::builder().display(handle.display).surface(handle.surface).build();
+                khr::WaylandSurface::new(instance.entry(), instance.vk()).create_wayland_surface(&create_info, None)
+            }
+            _ => panic!("Unsupported window handle"),
+        }
+    }
+
     pub fn get_loader(&self) -> &khr::Surface {
         &self.surface_loader
     }
@@ -1,27 +1,45 @@
-pub type HWND = *mut std::ffi::c_void;
-pub type HINSTANCE = *mut std::ffi::c_void;
+use raw_window_handle::RawWindowHandle;
 
-use std::{cell::Cell, mem::zeroed};
+use std::cell::Cell;
 
 pub struct Window {
+    handle: RawWindowHandle,
     window_size: Cell<(u32, u32)>,
-    hwnd: HWND,
+    reported_size: Cell<(u32, u32)>,
 }
 
 impl Window {
-    pub fn new(hwnd: HWND) -> Window {
-        Window {
-            window_size: Cell::new(get_window_size(hwnd)),
-            hwnd,
-        }
+    pub fn new(handle: RawWindowHandle, size: (u32, u32)) -> Window {
+        let window = Window {
+            handle,
+            window_size: Cell::new(size),
+            reported_size: Cell::new(size),
+        };
+        window.window_size.set(window.get_window_size());
+        window
+    }
+
+    pub fn handle(&self) -> &RawWindowHandle {
+        &self.handle
     }
 
     pub fn get_window_size(&self) -> (u32, u32) {
-        get_window_size(self.hwnd)
+        platform::get_window_size(&self.handle).unwrap_or_else(|| self.reported_size.get())
     }
 
     pub fn is_window_visible(&self) -> bool {
-        unsafe { (winapi::um::winuser::GetWindowLongA(self.hwnd as *mut winapi::shared::windef::HWND__, winapi::um::winuser::GWL_STYLE) as u32 & winapi::um::winuser::WS_MINIMIZE) == 0 }
+        match platform::is_window_minimized(&self.handle) {
+            Some(minimized) => !minimized,
+            None => {
+                let (width, height) = self.reported_size.get();
+                width > 0 && height > 0
+            }
+        }
+    }
+
+    /// Records the size reported by the windowing system, used on platforms that can't be queried directly
+    pub fn set_reported_size(&self, size: (u32, u32)) {
+        self.reported_size.set(size);
     }
 
     pub fn has_window_resized(&self) -> bool {
@@ -31,12 +49,47 @@ impl Window {
     }
 }
 
-fn get_window_size(hwnd: HWND) -> (u32, u32) {
-    unsafe {
-        let mut rect = zeroed::<winapi::shared::windef::RECT>();
+#[cfg(windows)]
+mod platform {
+    use raw_window_handle::RawWindowHandle;
+    use winapi::{
+        shared::windef::{HWND__, RECT},
+        um::winuser,
+    };
+
+    use std::mem::zeroed;
+
+    pub fn get_window_size(handle: &RawWindowHandle) -> Option<(u32, u32)> {
+        match handle {
+            RawWindowHandle::Windows(handle) => unsafe {
+                let mut rect = zeroed::<RECT>();
+
+                winuser::GetWindowRect(handle.hwnd as *mut HWND__, &mut rect);
+
+                Some(((rect.right - rect.left).abs() as u32, (rect.bottom - rect.top).abs() as u32))
+            },
+            _ => None,
+        }
+    }
+
+    pub fn is_window_minimized(handle: &RawWindowHandle) -> Option<bool> {
+        match handle {
+            RawWindowHandle::Windows(handle) => unsafe { Some((winuser::GetWindowLongA(handle.hwnd as *mut HWND__, winuser::GWL_STYLE) as u32 & winuser::WS_MINIMIZE) != 0) },
+            _ => None,
+        }
+    }
+}
+
+// X11 and Wayland have no cheap synchronous query, so the size reported through winit events is used instead
+#[cfg(not(windows))]
+mod platform {
+    use raw_window_handle::RawWindowHandle;
 
-        winapi::um::winuser::GetWindowRect(hwnd as *mut winapi::shared::windef::HWND__, &mut rect);
+    pub fn get_window_size(_handle: &RawWindowHandle) -> Option<(u32, u32)> {
+        None
+    }
 
-        ((rect.right - rect.left).abs() as u32, (rect.bottom - rect.top).abs() as u32)
+    pub fn is_window_minimized(_handle: &RawWindowHandle) -> Option<bool> {
+        None
     }
 }
@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use ash::vk;
+
+use super::{
+    buffers::Buffer,
+    device::Device,
+    materials::{BlendMode, RenderState},
+    pipelines::{DescriptorLayout, DescriptorPoolAlloc, Pipeline},
+    renderpasses::RenderPass,
+    VulkanObject,
+};
+
+const GRID_VERTEX_PATH: &str = "assets/gen/shaders/grid.vert.spv";
+const GRID_FRAGMENT_PATH: &str = "assets/gen/shaders/grid.frag.spv";
+
+/// World plane the editor grid lies in, named after the two axes spanning it
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GridPlane {
+    XY,
+    XZ,
+    YZ,
+}
+
+/// Infinite editor grid drawn over the scene of every view, toggled at runtime through `RenderSettings::grid`
+#[derive(Clone, Debug)]
+pub struct GridSettings {
+    pub enabled: bool,
+    pub plane: GridPlane,
+    /// Distance between two minor lines
+    pub spacing: f32,
+    /// Minor cells between two major lines
+    pub major_every: u32,
+    /// Distance from the camera at which the lines have faded out
+    pub fade_distance: f32,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            plane: GridPlane::XY,
+            spacing: 1.0,
+            major_every: 10,
+            fade_distance: 100.0,
+        }
+    }
+}
+
+/// Uniform block of the grid shader, laid out to match std140
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct GridParams {
+    plane: u32,
+    spacing: f32,
+    major_every: f32,
+    fade_distance: f32,
+}
+
+/// Fullscreen pass intersecting each pixel's view ray with the grid plane, depth tested against the scene
+pub struct Grid {
+    device: Arc<Device>,
+    layouts: [Arc<DescriptorLayout>; 2],
+    /// One pipeline per sample count of the passes the grid was drawn in
+    pipelines: Mutex<HashMap<vk::SampleCountFlags, Arc<Pipeline>>>,
+    params_buffers: Vec<Buffer>,
+    sets: Arc<DescriptorPoolAlloc>,
+}
+
+impl Grid {
+    /// `scene_layout` is the layout of the view's set the grid reads the camera from
+    pub fn new(device: Arc<Device>, scene_layout: &Arc<DescriptorLayout>, image_count: usize) -> Grid {
+        let layout = DescriptorLayout::grid(device.clone());
+        let params_size = std::mem::size_of::<GridParams>() as vk::DeviceSize;
+        let params_buffers = (0..image_count)
+            .map(|_| {
+                Buffer::new(
+                    params_size,
+                    vk::BufferUsageFlags::UNIFORM_BUFFER,
+                    vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+                    device.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let set_layouts = params_buffers.iter().map(|_| layout.clone()).collect::<Vec<_>>();
+        let sets = device.descriptor_pool().alloc(&set_layouts);
+        sets.update_buffers(0, &params_buffers.iter().collect::<Vec<_>>(), params_size);
+
+        Grid {
+            device,
+            layouts: [scene_layout.clone(), layout],
+            pipelines: Mutex::new(HashMap::new()),
+            params_buffers,
+            sets,
+        }
+    }
+
+    pub fn update(&self, image_index: usize, settings: &GridSettings) {
+        let plane = match settings.plane {
+            GridPlane::XY => 0,
+            GridPlane::XZ => 1,
+            GridPlane::YZ => 2,
+        };
+        self.params_buffers[image_index].map_memory::<f32, _>(&[GridParams {
+            plane,
+            spacing: settings.spacing,
+            major_every: settings.major_every.max(1) as f32,
+            fade_distance: settings.fade_distance,
+        }]);
+    }
+
+    /// Pipeline drawing the grid in `render_pass`, created the first time a pass with its sample count draws it
+    pub fn pipeline(&self, render_pass: &Arc<RenderPass>) -> Arc<Pipeline> {
+        let mut pipelines = self.pipelines.lock().unwrap();
+        pipelines
+            .entry(render_pass.samples())
+            .or_insert_with(|| {
+                // Blended over the scene without hiding what is drawn after it
+                let state = RenderState {
+                    blend: BlendMode::Alpha,
+                    cull_mode: vk::CullModeFlags::NONE,
+                    depth_test: true,
+                    depth_write: false,
+                    ..RenderState::default()
+                };
+                Pipeline::screen_space(self.device.clone(), render_pass, &self.layouts, GRID_VERTEX_PATH, GRID_FRAGMENT_PATH, &state)
+            })
+            .clone()
+    }
+
+    /// Set holding the grid parameters of a swapchain image, bound at set 1
+    pub fn set(&self, image_index: usize) -> vk::DescriptorSet {
+        self.sets.vk()[image_index]
+    }
+}
+
+impl Drop for Grid {
+    fn drop(&mut self) {
+        trace!("Dropping Grid");
+    }
+}
@@ -0,0 +1,414 @@
+use std::{f32::consts::PI, path::Path, sync::Arc};
+
+use ash::vk;
+
+use image::{
+    error::{ImageError, ParameterError, ParameterErrorKind},
+    RgbaImage,
+};
+
+use super::{image::mip_size, texture::decode, Image, Texture, TextureError};
+use crate::render::device::Device;
+
+const CUBE_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+const BRDF_LUT_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+const IRRADIANCE_SIZE: u32 = 16;
+const PREFILTERED_SIZE: u32 = 64;
+/// Levels of the prefiltered map, spread evenly from a roughness of 0 to 1
+const PREFILTERED_LEVELS: u32 = 5;
+const SAMPLE_COUNT: u32 = 64;
+const BRDF_LUT_SIZE: u32 = 64;
+const BRDF_LUT_SAMPLE_COUNT: u32 = 128;
+
+/// Image based lighting of the PBR shaders, generated from the six faces of an environment cubemap
+pub struct Environment {
+    irradiance: Arc<Texture>,
+    prefiltered: Arc<Texture>,
+}
+
+impl Environment {
+    /// Cosine weighted light arriving from every direction, divided by pi
+    pub fn irradiance(&self) -> &Arc<Texture> {
+        &self.irradiance
+    }
+
+    /// Environment blurred by the GGX distribution, one mip level per roughness step
+    pub fn prefiltered(&self) -> &Arc<Texture> {
+        &self.prefiltered
+    }
+}
+
+impl Drop for Environment {
+    fn drop(&mut self) {
+        trace!("Dropping Environment");
+    }
+}
+
+pub struct EnvironmentFactory {
+    device: Arc<Device>,
+}
+
+impl EnvironmentFactory {
+    pub fn new(device: Arc<Device>) -> EnvironmentFactory {
+        EnvironmentFactory { device }
+    }
+
+    /// Generates the irradiance and prefiltered maps from square RGBA8 sRGB faces in the +X, -X, +Y, -Y, +Z, -Z order
+    ///
+    /// The maps are computed on the CPU, which takes a moment for large faces.
+    pub fn create_environment(&self, face_size: u32, faces: [&[u8]; 6]) -> Arc<Environment> {
+        for face in faces.iter() {
+            assert_eq!(face.len(), (face_size * face_size * 4) as usize, "Environment faces must be {0}x{0} RGBA8", face_size);
+        }
+
+        let source = CubeMap::from_srgb(face_size, &faces);
+        let mips = source.mip_chain();
+
+        let irradiance_size = IRRADIANCE_SIZE.min(face_size);
+        let irradiance = irradiance_map(&mips, irradiance_size);
+
+        let prefiltered_size = PREFILTERED_SIZE.min(face_size);
+        let prefiltered_levels = PREFILTERED_LEVELS.min(Image::full_mip_levels(vk::Extent2D {
+            width: prefiltered_size,
+            height: prefiltered_size,
+        }));
+        let prefiltered = (0..prefiltered_levels)
+            .map(|level| {
+                let roughness = if prefiltered_levels > 1 { level as f32 / (prefiltered_levels - 1) as f32 } else { 0.0 };
+                prefiltered_map(&mips, mip_size(prefiltered_size, level), roughness).to_srgb()
+            })
+            .collect::<Vec<_>>();
+
+        Environment {
+            irradiance: self.create_cube(irradiance_size, &[irradiance.to_srgb()]),
+            prefiltered: self.create_cube(prefiltered_size, &prefiltered),
+        }
+        .into()
+    }
+
+    /// Decodes six PNG or JPEG faces, see `create_environment`
+    pub fn load<P: AsRef<Path>>(&self, faces: [P; 6]) -> Result<Arc<Environment>, TextureError> {
+        let decoded = decode_faces(&faces)?;
+        let raw = decoded.iter().map(|image| image.as_raw().as_slice()).collect::<Vec<_>>();
+        Ok(self.create_environment(decoded[0].width(), [raw[0], raw[1], raw[2], raw[3], raw[4], raw[5]]))
+    }
+
+    /// Environment of the same color in every direction, lighting like a constant ambient term
+    pub fn create_uniform(&self, color: [f32; 3]) -> Arc<Environment> {
+        let texel = CubeMap::encode(color);
+        self.create_environment(1, [&texel, &texel, &texel, &texel, &texel, &texel])
+    }
+
+    /// Scale and bias applied to the specular color by the split sum approximation, indexed by the cosine between
+    /// the normal and the view direction along `u` and the roughness along `v`
+    pub fn create_brdf_lut(&self) -> Arc<Texture> {
+        let mut pixels = Vec::with_capacity((BRDF_LUT_SIZE * BRDF_LUT_SIZE * 4) as usize);
+        for y in 0..BRDF_LUT_SIZE {
+            for x in 0..BRDF_LUT_SIZE {
+                let n_dot_v = (x as f32 + 0.5) / BRDF_LUT_SIZE as f32;
+                let roughness = (y as f32 + 0.5) / BRDF_LUT_SIZE as f32;
+                let (scale, bias) = integrate_brdf(n_dot_v, roughness);
+                pixels.extend_from_slice(&[to_unorm(scale), to_unorm(bias), 0, 255]);
+            }
+        }
+
+        let extent = vk::Extent2D {
+            width: BRDF_LUT_SIZE,
+            height: BRDF_LUT_SIZE,
+        };
+        let image = Image::new(
+            self.device.clone(),
+            extent,
+            BRDF_LUT_FORMAT,
+            vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
+            vk::ImageAspectFlags::COLOR,
+        );
+        Texture::from_levels(self.device.clone(), image, &[pixels])
+    }
+
+    fn create_cube(&self, size: u32, levels: &[Vec<u8>]) -> Arc<Texture> {
+        let image = Image::cube(
+            self.device.clone(),
+            size,
+            CUBE_FORMAT,
+            levels.len() as u32,
+            vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
+        );
+        Texture::from_levels(self.device.clone(), image, levels)
+    }
+}
+
+/// Decodes six PNG or JPEG cube faces, which all have to be squares of the same size
+pub fn decode_faces<P: AsRef<Path>>(faces: &[P; 6]) -> Result<Vec<RgbaImage>, TextureError> {
+    let mut decoded = Vec::with_capacity(6);
+    for face in faces.iter() {
+        let path = face.as_ref();
+        let image = decode(path)?;
+        let face_size = decoded.first().map_or(image.width(), |first: &RgbaImage| first.width());
+        if image.width() != face_size || image.height() != face_size {
+            return Err(TextureError {
+                path: path.to_path_buf(),
+                source: ImageError::Parameter(ParameterError::from_kind(ParameterErrorKind::DimensionMismatch)),
+            });
+        }
+        decoded.push(image);
+    }
+    Ok(decoded)
+}
+
+/// Linear colors of the six faces of a cube, in the same order as the layers of a Vulkan cube image
+pub struct CubeMap {
+    size: u32,
+    texels: Vec<uv::Vec3>,
+}
+
+impl CubeMap {
+    pub fn new(size: u32, texel: impl Fn(uv::Vec3) -> uv::Vec3) -> CubeMap {
+        let mut texels = Vec::with_capacity((6 * size * size) as usize);
+        for face in 0..6 {
+            for y in 0..size {
+                for x in 0..size {
+                    texels.push(texel(texel_direction(face, x, y, size)));
+                }
+            }
+        }
+        CubeMap { size, texels }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    fn from_srgb(size: u32, faces: &[&[u8]; 6]) -> CubeMap {
+        let texels = faces
+            .iter()
+            .flat_map(|face| face.chunks(4))
+            .map(|texel| uv::Vec3::new(srgb_to_linear(texel[0]), srgb_to_linear(texel[1]), srgb_to_linear(texel[2])))
+            .collect();
+        CubeMap { size, texels }
+    }
+
+    fn encode(color: [f32; 3]) -> [u8; 4] {
+        [linear_to_srgb(color[0]), linear_to_srgb(color[1]), linear_to_srgb(color[2]), 255]
+    }
+
+    pub fn to_srgb(&self) -> Vec<u8> {
+        self.texels.iter().flat_map(|texel| Self::encode([texel.x, texel.y, texel.z]).to_vec()).collect()
+    }
+
+    /// Averages every 2x2 block of texels until the faces are a single texel
+    fn mip_chain(self) -> Vec<CubeMap> {
+        let mut mips = vec![self];
+        while mips.last().unwrap().size > 1 {
+            let previous = mips.last().unwrap();
+            let size = previous.size / 2;
+            let mut texels = Vec::with_capacity((6 * size * size) as usize);
+            for face in 0..6 {
+                for y in 0..size {
+                    for x in 0..size {
+                        let texel = |dx: u32, dy: u32| previous.texel(face, (x * 2 + dx).min(previous.size - 1), (y * 2 + dy).min(previous.size - 1));
+                        texels.push((texel(0, 0) + texel(1, 0) + texel(0, 1) + texel(1, 1)) / 4.0);
+                    }
+                }
+            }
+            mips.push(CubeMap { size, texels });
+        }
+        mips
+    }
+
+    fn texel(&self, face: u32, x: u32, y: u32) -> uv::Vec3 {
+        self.texels[((face * self.size + y) * self.size + x) as usize]
+    }
+
+    /// Adds `color` to a texel, coordinates past the face's edge are ignored
+    pub fn add(&mut self, face: u32, x: i64, y: i64, color: uv::Vec3) {
+        let size = self.size as i64;
+        if (0..size).contains(&x) && (0..size).contains(&y) {
+            self.texels[((face as i64 * size + y) * size + x) as usize] += color;
+        }
+    }
+
+    /// Nearest texel in `direction`
+    fn sample(&self, direction: uv::Vec3) -> uv::Vec3 {
+        let (face, s, t) = face_coordinates(direction);
+        let to_texel = |coordinate: f32| ((coordinate * self.size as f32) as u32).min(self.size - 1);
+        self.texel(face, to_texel(s), to_texel(t))
+    }
+
+    /// Solid angle covered by the texel, as seen from the center of the cube
+    fn texel_solid_angle(&self, direction: uv::Vec3) -> f32 {
+        let (_, s, t) = face_coordinates(direction);
+        let (s, t) = (s * 2.0 - 1.0, t * 2.0 - 1.0);
+        let area = (2.0 / self.size as f32).powi(2);
+        area / (1.0 + s * s + t * t).powf(1.5)
+    }
+}
+
+/// Direction through the center of a texel, following the face orientations of Vulkan cube images
+fn texel_direction(face: u32, x: u32, y: u32, size: u32) -> uv::Vec3 {
+    let s = (x as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let t = (y as f32 + 0.5) / size as f32 * 2.0 - 1.0;
+    let direction = match face {
+        0 => uv::Vec3::new(1.0, -t, -s),
+        1 => uv::Vec3::new(-1.0, -t, s),
+        2 => uv::Vec3::new(s, 1.0, t),
+        3 => uv::Vec3::new(s, -1.0, -t),
+        4 => uv::Vec3::new(s, -t, 1.0),
+        _ => uv::Vec3::new(-s, -t, -1.0),
+    };
+    direction.normalized()
+}
+
+/// Face hit by `direction` and where on it, with both coordinates from 0 to 1
+pub fn face_coordinates(direction: uv::Vec3) -> (u32, f32, f32) {
+    let abs = uv::Vec3::new(direction.x.abs(), direction.y.abs(), direction.z.abs());
+    let (face, s, t, major) = if abs.x >= abs.y && abs.x >= abs.z {
+        if direction.x > 0.0 {
+            (0, -direction.z, -direction.y, abs.x)
+        } else {
+            (1, direction.z, -direction.y, abs.x)
+        }
+    } else if abs.y >= abs.z {
+        if direction.y > 0.0 {
+            (2, direction.x, direction.z, abs.y)
+        } else {
+            (3, direction.x, -direction.z, abs.y)
+        }
+    } else if direction.z > 0.0 {
+        (4, direction.x, -direction.y, abs.z)
+    } else {
+        (5, -direction.x, -direction.y, abs.z)
+    };
+    (face, (s / major + 1.0) / 2.0, (t / major + 1.0) / 2.0)
+}
+
+/// Convolves a low resolution copy of the environment with the cosine lobe around each texel's direction
+fn irradiance_map(mips: &[CubeMap], size: u32) -> CubeMap {
+    let source = mips.iter().find(|mip| mip.size <= IRRADIANCE_SIZE).unwrap_or_else(|| mips.last().unwrap());
+    let samples = (0..6)
+        .flat_map(|face| (0..source.size * source.size).map(move |index| (face, index % source.size, index / source.size)))
+        .map(|(face, x, y)| {
+            let direction = texel_direction(face, x, y, source.size);
+            (direction, source.texel(face, x, y), source.texel_solid_angle(direction))
+        })
+        .collect::<Vec<_>>();
+
+    // Dividing by the summed weights instead of pi keeps coarse cubes from over or under estimating the integral
+    CubeMap::new(size, |normal| {
+        let mut irradiance = uv::Vec3::zero();
+        let mut weight = 0.0;
+        for (direction, radiance, solid_angle) in &samples {
+            let sample_weight = normal.dot(*direction).max(0.0) * solid_angle;
+            irradiance += *radiance * sample_weight;
+            weight += sample_weight;
+        }
+        irradiance / weight.max(0.0001)
+    })
+}
+
+/// Importance samples the GGX distribution around each texel's direction, reading blurrier mips for sparser samples
+fn prefiltered_map(mips: &[CubeMap], size: u32, roughness: f32) -> CubeMap {
+    let base = &mips[0];
+    if roughness == 0.0 {
+        return CubeMap::new(size, |direction| mips.iter().find(|mip| mip.size <= size).unwrap_or(base).sample(direction));
+    }
+
+    let texel_solid_angle = 4.0 * PI / (6.0 * (base.size * base.size) as f32);
+    CubeMap::new(size, |normal| {
+        let mut color = uv::Vec3::zero();
+        let mut weight = 0.0;
+        for i in 0..SAMPLE_COUNT {
+            let half = importance_sample_ggx(hammersley(i, SAMPLE_COUNT), normal, roughness);
+            let light = half * 2.0 * normal.dot(half) - normal;
+            let n_dot_l = normal.dot(light);
+            if n_dot_l <= 0.0 {
+                continue;
+            }
+
+            // With the view along the normal the pdf reduces to D / 4
+            let pdf = distribution_ggx(normal.dot(half).max(0.0), roughness) / 4.0;
+            let sample_solid_angle = 1.0 / (SAMPLE_COUNT as f32 * pdf + 0.0001);
+            let level = (0.5 * (sample_solid_angle / texel_solid_angle).log2() + 1.0).max(0.0);
+            let mip = &mips[(level.round() as usize).min(mips.len() - 1)];
+
+            color += mip.sample(light) * n_dot_l;
+            weight += n_dot_l;
+        }
+        color / weight.max(0.0001)
+    })
+}
+
+/// Scale and bias of the Fresnel term integrated over the GGX lobe, the second part of the split sum approximation
+fn integrate_brdf(n_dot_v: f32, roughness: f32) -> (f32, f32) {
+    let view = uv::Vec3::new((1.0 - n_dot_v * n_dot_v).sqrt(), 0.0, n_dot_v);
+    let normal = uv::Vec3::unit_z();
+
+    let (mut scale, mut bias) = (0.0, 0.0);
+    for i in 0..BRDF_LUT_SAMPLE_COUNT {
+        let half = importance_sample_ggx(hammersley(i, BRDF_LUT_SAMPLE_COUNT), normal, roughness);
+        let light = half * 2.0 * view.dot(half) - view;
+
+        let n_dot_l = light.z;
+        if n_dot_l <= 0.0 {
+            continue;
+        }
+        let n_dot_h = half.z.max(0.0);
+        let v_dot_h = view.dot(half).max(0.0);
+
+        let visibility = geometry_smith(n_dot_v, n_dot_l, roughness) * v_dot_h / (n_dot_h * n_dot_v);
+        let fresnel = (1.0 - v_dot_h).powi(5);
+        scale += (1.0 - fresnel) * visibility;
+        bias += fresnel * visibility;
+    }
+    (scale / BRDF_LUT_SAMPLE_COUNT as f32, bias / BRDF_LUT_SAMPLE_COUNT as f32)
+}
+
+/// Low discrepancy point in the unit square
+fn hammersley(i: u32, count: u32) -> (f32, f32) {
+    (i as f32 / count as f32, i.reverse_bits() as f32 / 4_294_967_296.0)
+}
+
+/// Half vector around `normal` distributed like the GGX normal distribution
+fn importance_sample_ggx((u, v): (f32, f32), normal: uv::Vec3, roughness: f32) -> uv::Vec3 {
+    let a = roughness * roughness;
+    let phi = 2.0 * PI * u;
+    let cos_theta = ((1.0 - v) / (1.0 + (a * a - 1.0) * v)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+    let up = if normal.z.abs() < 0.999 { uv::Vec3::unit_z() } else { uv::Vec3::unit_x() };
+    let tangent = up.cross(normal).normalized();
+    let bitangent = normal.cross(tangent);
+    (tangent * (phi.cos() * sin_theta) + bitangent * (phi.sin() * sin_theta) + normal * cos_theta).normalized()
+}
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let a2 = roughness.powi(4);
+    let denominator = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * denominator * denominator)
+}
+
+/// Schlick-GGX masking and shadowing, with the `k` used for image based lighting
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = roughness * roughness / 2.0;
+    let schlick = |n_dot_x: f32| n_dot_x / (n_dot_x * (1.0 - k) + k);
+    schlick(n_dot_v) * schlick(n_dot_l)
+}
+
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let encoded = if value <= 0.003_130_8 { value * 12.92 } else { 1.055 * value.powf(1.0 / 2.4) - 0.055 };
+    to_unorm(encoded)
+}
+
+fn to_unorm(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
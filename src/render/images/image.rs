@@ -0,0 +1,355 @@
+use std::{ops::Range, sync::Arc};
+
+use ash::{version::DeviceV1_0, vk};
+
+use crate::render::{buffers::Buffer, device::Device, VulkanObject};
+
+pub struct Image {
+    device: Arc<Device>,
+    image: vk::Image,
+    image_memory: vk::DeviceMemory,
+    image_view: vk::ImageView,
+    /// Single layer views of array images, which render passes can draw into one at a time
+    layer_views: Vec<vk::ImageView>,
+    extent: vk::Extent2D,
+    format: vk::Format,
+    mip_levels: u32,
+    layers: u32,
+    aspect: vk::ImageAspectFlags,
+}
+
+impl Image {
+    pub fn new(device: Arc<Device>, extent: vk::Extent2D, format: vk::Format, usage: vk::ImageUsageFlags, aspect: vk::ImageAspectFlags) -> Self {
+        Self::with_mip_levels(device, extent, format, 1, usage, aspect)
+    }
+
+    pub fn with_mip_levels(device: Arc<Device>, extent: vk::Extent2D, format: vk::Format, mip_levels: u32, usage: vk::ImageUsageFlags, aspect: vk::ImageAspectFlags) -> Self {
+        Self::create(device, extent, format, mip_levels, 1, vk::ImageViewType::TYPE_2D, usage, aspect, vk::SampleCountFlags::TYPE_1)
+    }
+
+    /// Transient color attachment with `samples` samples per pixel, resolved into a single sampled image by the pass
+    pub fn multisampled(device: Arc<Device>, extent: vk::Extent2D, format: vk::Format, samples: vk::SampleCountFlags) -> Self {
+        Self::create(
+            device,
+            extent,
+            format,
+            1,
+            1,
+            vk::ImageViewType::TYPE_2D,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSIENT_ATTACHMENT,
+            vk::ImageAspectFlags::COLOR,
+            samples,
+        )
+    }
+
+    /// Image of `layers` layers viewed as an array, with a view of each layer in `layer_views`
+    pub fn array(device: Arc<Device>, extent: vk::Extent2D, format: vk::Format, layers: u32, usage: vk::ImageUsageFlags, aspect: vk::ImageAspectFlags) -> Self {
+        Self::create(device, extent, format, 1, layers, vk::ImageViewType::TYPE_2D_ARRAY, usage, aspect, vk::SampleCountFlags::TYPE_1)
+    }
+
+    /// Square color image with six layers viewed as a cube, faces in the +X, -X, +Y, -Y, +Z, -Z order
+    pub fn cube(device: Arc<Device>, size: u32, format: vk::Format, mip_levels: u32, usage: vk::ImageUsageFlags) -> Self {
+        Self::create(
+            device,
+            vk::Extent2D { width: size, height: size },
+            format,
+            mip_levels,
+            6,
+            vk::ImageViewType::CUBE,
+            usage,
+            vk::ImageAspectFlags::COLOR,
+            vk::SampleCountFlags::TYPE_1,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create(
+        device: Arc<Device>,
+        extent: vk::Extent2D,
+        format: vk::Format,
+        mip_levels: u32,
+        layers: u32,
+        view_type: vk::ImageViewType,
+        usage: vk::ImageUsageFlags,
+        aspect: vk::ImageAspectFlags,
+        samples: vk::SampleCountFlags,
+    ) -> Self {
+        let flags = if view_type == vk::ImageViewType::CUBE {
+            vk::ImageCreateFlags::CUBE_COMPATIBLE
+        } else {
+            vk::ImageCreateFlags::empty()
+        };
+
+        let image_info = vk::ImageCreateInfo::builder()
+            .flags(flags)
+            .image_type(vk::ImageType::TYPE_2D)
+            .extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .mip_levels(mip_levels)
+            .array_layers(layers)
+            .format(format)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .usage(usage)
+            .samples(samples)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .build();
+
+        let image = unsafe { device.vk().create_image(&image_info, None).unwrap() };
+
+        let mem_requirements = unsafe { device.vk().get_image_memory_requirements(image) };
+
+        let alloc_info = vk::MemoryAllocateInfo::builder()
+            .allocation_size(mem_requirements.size)
+            .memory_type_index(Buffer::find_memory_type(
+                mem_requirements.memory_type_bits,
+                vk::MemoryPropertyFlags::DEVICE_LOCAL,
+                device.physical_device().get_mem_properties(),
+            ))
+            .build();
+
+        let image_memory = unsafe { device.vk().allocate_memory(&alloc_info, None).unwrap() };
+
+        unsafe {
+            device.vk().bind_image_memory(image, image_memory, 0).unwrap();
+        }
+
+        let create_view = |view_type, base_layer, layer_count| {
+            let view_info = vk::ImageViewCreateInfo::builder()
+                .image(image)
+                .view_type(view_type)
+                .format(format)
+                .subresource_range(
+                    vk::ImageSubresourceRange::builder()
+                        .base_mip_level(0)
+                        .level_count(mip_levels)
+                        .base_array_layer(base_layer)
+                        .layer_count(layer_count)
+                        .aspect_mask(aspect)
+                        .build(),
+                )
+                .build();
+
+            unsafe { device.vk().create_image_view(&view_info, None).unwrap() }
+        };
+
+        let image_view = create_view(view_type, 0, layers);
+        let layer_views = if view_type == vk::ImageViewType::TYPE_2D_ARRAY {
+            (0..layers).map(|layer| create_view(vk::ImageViewType::TYPE_2D, layer, 1)).collect()
+        } else {
+            Vec::new()
+        };
+
+        Image {
+            device,
+            image,
+            image_memory,
+            image_view,
+            layer_views,
+            extent,
+            format,
+            mip_levels,
+            layers,
+            aspect,
+        }
+    }
+
+    /// Depth attachment with `samples` samples per pixel, including the stencil aspect for formats which have one
+    pub fn depth(device: Arc<Device>, extent: vk::Extent2D, format: vk::Format, samples: vk::SampleCountFlags) -> Self {
+        let aspect = match format {
+            vk::Format::D32_SFLOAT_S8_UINT | vk::Format::D24_UNORM_S8_UINT => vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL,
+            _ => vk::ImageAspectFlags::DEPTH,
+        };
+
+        Self::create(device, extent, format, 1, 1, vk::ImageViewType::TYPE_2D, vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT, aspect, samples)
+    }
+
+    /// Number of levels in a full mip chain down to 1x1
+    pub fn full_mip_levels(extent: vk::Extent2D) -> u32 {
+        32 - extent.width.max(extent.height).max(1).leading_zeros()
+    }
+
+    pub fn view(&self) -> &vk::ImageView {
+        &self.image_view
+    }
+
+    pub fn layer_views(&self) -> &[vk::ImageView] {
+        &self.layer_views
+    }
+
+    pub fn extent(&self) -> &vk::Extent2D {
+        &self.extent
+    }
+
+    pub fn format(&self) -> vk::Format {
+        self.format
+    }
+
+    pub fn mip_levels(&self) -> u32 {
+        self.mip_levels
+    }
+
+    /// Records a barrier moving every mip level and layer of the image from `old_layout` to `new_layout`
+    pub fn transition_layout(&self, command_buffer: vk::CommandBuffer, old_layout: vk::ImageLayout, new_layout: vk::ImageLayout) {
+        self.transition_levels(command_buffer, 0..self.mip_levels, old_layout, new_layout);
+    }
+
+    fn transition_levels(&self, command_buffer: vk::CommandBuffer, levels: Range<u32>, old_layout: vk::ImageLayout, new_layout: vk::ImageLayout) {
+        let (src_access, src_stage) = match old_layout {
+            vk::ImageLayout::UNDEFINED => (vk::AccessFlags::empty(), vk::PipelineStageFlags::TOP_OF_PIPE),
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL => (vk::AccessFlags::TRANSFER_WRITE, vk::PipelineStageFlags::TRANSFER),
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL => (vk::AccessFlags::TRANSFER_READ, vk::PipelineStageFlags::TRANSFER),
+            _ => panic!("Unsupported layout transition from {:?}", old_layout),
+        };
+        let (dst_access, dst_stage) = match new_layout {
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL => (vk::AccessFlags::TRANSFER_WRITE, vk::PipelineStageFlags::TRANSFER),
+            vk::ImageLayout::TRANSFER_SRC_OPTIMAL => (vk::AccessFlags::TRANSFER_READ, vk::PipelineStageFlags::TRANSFER),
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL => (vk::AccessFlags::SHADER_READ, vk::PipelineStageFlags::FRAGMENT_SHADER),
+            _ => panic!("Unsupported layout transition to {:?}", new_layout),
+        };
+
+        let barriers = [vk::ImageMemoryBarrier::builder()
+            .old_layout(old_layout)
+            .new_layout(new_layout)
+            .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+            .image(self.image)
+            .subresource_range(
+                vk::ImageSubresourceRange::builder()
+                    .aspect_mask(self.aspect)
+                    .base_mip_level(levels.start)
+                    .level_count(levels.end - levels.start)
+                    .base_array_layer(0)
+                    .layer_count(self.layers)
+                    .build(),
+            )
+            .src_access_mask(src_access)
+            .dst_access_mask(dst_access)
+            .build()];
+
+        unsafe {
+            self.device
+                .vk()
+                .cmd_pipeline_barrier(command_buffer, src_stage, dst_stage, vk::DependencyFlags::empty(), &[], &[], &barriers);
+        }
+    }
+
+    /// Records a copy of tightly packed texels from `buffer` into the image, which has to be in `TRANSFER_DST_OPTIMAL`
+    ///
+    /// `level_offsets` holds the buffer offset of each mip level to fill, starting at the base level. Each level holds
+    /// all layers of the image one after another.
+    pub fn copy_from_buffer(&self, command_buffer: vk::CommandBuffer, buffer: &Buffer, level_offsets: &[vk::DeviceSize]) {
+        let regions = level_offsets
+            .iter()
+            .enumerate()
+            .map(|(level, offset)| {
+                vk::BufferImageCopy::builder()
+                    .buffer_offset(*offset)
+                    .buffer_row_length(0)
+                    .buffer_image_height(0)
+                    .image_subresource(
+                        vk::ImageSubresourceLayers::builder()
+                            .aspect_mask(vk::ImageAspectFlags::COLOR)
+                            .mip_level(level as u32)
+                            .base_array_layer(0)
+                            .layer_count(self.layers)
+                            .build(),
+                    )
+                    .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+                    .image_extent(vk::Extent3D {
+                        width: mip_size(self.extent.width, level as u32),
+                        height: mip_size(self.extent.height, level as u32),
+                        depth: 1,
+                    })
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        unsafe {
+            self.device
+                .vk()
+                .cmd_copy_buffer_to_image(command_buffer, *buffer.vk(), self.image, vk::ImageLayout::TRANSFER_DST_OPTIMAL, &regions);
+        }
+    }
+
+    /// Fills every mip level by repeatedly blitting the previous one with linear filtering
+    ///
+    /// All levels have to be in `TRANSFER_DST_OPTIMAL` with the base level written, they all end in
+    /// `SHADER_READ_ONLY_OPTIMAL`. The format has to support linear blits, see `PhysicalDevice::supports_linear_blit`.
+    pub fn generate_mipmaps(&self, command_buffer: vk::CommandBuffer) {
+        for level in 1..self.mip_levels {
+            self.transition_levels(command_buffer, level - 1..level, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::TRANSFER_SRC_OPTIMAL);
+
+            let subresource = |mip_level| {
+                vk::ImageSubresourceLayers::builder()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .mip_level(mip_level)
+                    .base_array_layer(0)
+                    .layer_count(self.layers)
+                    .build()
+            };
+            let corner = |mip_level| vk::Offset3D {
+                x: mip_size(self.extent.width, mip_level) as i32,
+                y: mip_size(self.extent.height, mip_level) as i32,
+                z: 1,
+            };
+            let regions = [vk::ImageBlit::builder()
+                .src_subresource(subresource(level - 1))
+                .src_offsets([vk::Offset3D { x: 0, y: 0, z: 0 }, corner(level - 1)])
+                .dst_subresource(subresource(level))
+                .dst_offsets([vk::Offset3D { x: 0, y: 0, z: 0 }, corner(level)])
+                .build()];
+
+            unsafe {
+                self.device.vk().cmd_blit_image(
+                    command_buffer,
+                    self.image,
+                    vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                    self.image,
+                    vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    &regions,
+                    vk::Filter::LINEAR,
+                );
+            }
+
+            self.transition_levels(command_buffer, level - 1..level, vk::ImageLayout::TRANSFER_SRC_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        }
+
+        self.transition_levels(
+            command_buffer,
+            self.mip_levels - 1..self.mip_levels,
+            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        );
+    }
+}
+
+/// Size of a mip level along one axis
+pub fn mip_size(base: u32, level: u32) -> u32 {
+    (base >> level).max(1)
+}
+
+impl VulkanObject for Image {
+    type Object = vk::Image;
+
+    fn vk(&self) -> &Self::Object {
+        &self.image
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        trace!("Dropping Image");
+        unsafe {
+            for &layer_view in &self.layer_views {
+                self.device.vk().destroy_image_view(layer_view, None);
+            }
+            self.device.vk().destroy_image_view(self.image_view, None);
+            self.device.vk().destroy_image(self.image, None);
+            self.device.vk().free_memory(self.image_memory, None);
+        }
+    }
+}
@@ -0,0 +1,13 @@
+mod environment;
+mod image;
+mod render_texture;
+mod shadow_map;
+mod skybox;
+mod texture;
+
+pub use environment::{Environment, EnvironmentFactory};
+pub use image::Image;
+pub use render_texture::{RenderTexture, RenderTextureFactory};
+pub use shadow_map::ShadowMap;
+pub use skybox::{Background, Skybox, SkyboxFactory, StarfieldSettings};
+pub use texture::{Texture, TextureError, TextureFactory};
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use ash::vk;
+
+use super::{Image, Texture};
+use crate::render::{
+    commands::submit_single_time_commands,
+    device::Device,
+    renderpasses::{FrameBuffer, RenderPass},
+    VulkanObject,
+};
+
+/// Offscreen color and depth target which cameras render into and meshes sample through `texture`
+///
+/// The texture holds the scene's HDR colors as they are before post processing. A mesh sampling the texture must not
+/// be visible to the cameras rendering into it.
+pub struct RenderTexture {
+    texture: Arc<Texture>,
+    _depth_image: Image,
+    render_pass: Arc<RenderPass>,
+    frame_buffer: Arc<FrameBuffer>,
+}
+
+impl RenderTexture {
+    /// `format` and `depth_format` have to match the scene pass so its pipelines can draw into the texture
+    pub fn new(device: Arc<Device>, extent: vk::Extent2D, format: vk::Format, depth_format: vk::Format) -> Arc<RenderTexture> {
+        let image = Image::new(
+            device.clone(),
+            extent,
+            format,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            vk::ImageAspectFlags::COLOR,
+        );
+
+        // Meshes may sample the texture before anything was rendered into it
+        submit_single_time_commands(&device, |command_buffer| {
+            image.transition_layout(command_buffer, vk::ImageLayout::UNDEFINED, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        });
+
+        let depth_image = Image::depth(device.clone(), extent, depth_format, vk::SampleCountFlags::TYPE_1);
+
+        let render_pass = RenderPass::new(device.clone(), format, depth_format, vk::SampleCountFlags::TYPE_1);
+        let frame_buffer = FrameBuffer::with_views(device.clone(), &[*image.view()], extent, Some(&depth_image), &render_pass);
+
+        RenderTexture {
+            texture: Texture::from_image(device, image),
+            _depth_image: depth_image,
+            render_pass,
+            frame_buffer,
+        }
+        .into()
+    }
+
+    /// The rendered color image, set as the texture of the materials showing it
+    pub fn texture(&self) -> &Arc<Texture> {
+        &self.texture
+    }
+
+    pub fn extent(&self) -> &vk::Extent2D {
+        self.texture.extent()
+    }
+
+    pub fn render_pass(&self) -> &Arc<RenderPass> {
+        &self.render_pass
+    }
+
+    pub fn frame_buffer(&self) -> &vk::Framebuffer {
+        &self.frame_buffer.vk()[0]
+    }
+}
+
+impl Drop for RenderTexture {
+    fn drop(&mut self) {
+        trace!("Dropping Render Texture");
+    }
+}
+
+pub struct RenderTextureFactory {
+    device: Arc<Device>,
+    format: vk::Format,
+    depth_format: vk::Format,
+}
+
+impl RenderTextureFactory {
+    pub fn new(device: Arc<Device>, format: vk::Format, depth_format: vk::Format) -> RenderTextureFactory {
+        RenderTextureFactory { device, format, depth_format }
+    }
+
+    pub fn create_render_texture(&self, width: u32, height: u32) -> Arc<RenderTexture> {
+        RenderTexture::new(self.device.clone(), vk::Extent2D { width, height }, self.format, self.depth_format)
+    }
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use ash::{version::DeviceV1_0, vk};
+
+use super::Image;
+use crate::render::{
+    commands::submit_single_time_commands,
+    constants::{MAX_SHADOW_MAPS, SHADOW_MAP_SIZE},
+    device::Device,
+    renderpasses::{FrameBuffer, RenderPass},
+    VulkanObject,
+};
+
+const SHADOW_MAP_FORMAT: vk::Format = vk::Format::D16_UNORM;
+
+/// Depth array image the shadow passes render into, one layer per cascade or spot light, sampled by the lit shaders
+pub struct ShadowMap {
+    device: Arc<Device>,
+    image: Image,
+    sampler: vk::Sampler,
+    render_pass: Arc<RenderPass>,
+    frame_buffers: Arc<FrameBuffer>,
+}
+
+impl ShadowMap {
+    pub fn new(device: Arc<Device>) -> ShadowMap {
+        let extent = vk::Extent2D {
+            width: SHADOW_MAP_SIZE,
+            height: SHADOW_MAP_SIZE,
+        };
+        let image = Image::array(
+            device.clone(),
+            extent,
+            SHADOW_MAP_FORMAT,
+            MAX_SHADOW_MAPS as u32,
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            vk::ImageAspectFlags::DEPTH,
+        );
+
+        // Layers no light renders into in a frame are still sampled
+        submit_single_time_commands(&device, |command_buffer| {
+            image.transition_layout(command_buffer, vk::ImageLayout::UNDEFINED, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        });
+
+        // Depth is compared in the shader, nearest filtering keeps each tap a single texel
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::NEAREST)
+            .min_filter(vk::Filter::NEAREST)
+            .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+            .anisotropy_enable(false)
+            .max_anisotropy(1.0)
+            .border_color(vk::BorderColor::FLOAT_OPAQUE_WHITE)
+            .unnormalized_coordinates(false)
+            .compare_enable(false)
+            .compare_op(vk::CompareOp::ALWAYS)
+            .mipmap_mode(vk::SamplerMipmapMode::NEAREST)
+            .mip_lod_bias(0.0)
+            .min_lod(0.0)
+            .max_lod(0.0)
+            .build();
+
+        let sampler = unsafe { device.vk().create_sampler(&sampler_info, None).unwrap() };
+
+        let render_pass = RenderPass::shadow(device.clone(), SHADOW_MAP_FORMAT);
+        let frame_buffers = FrameBuffer::with_views(device.clone(), image.layer_views(), extent, None, &render_pass);
+
+        ShadowMap {
+            device,
+            image,
+            sampler,
+            render_pass,
+            frame_buffers,
+        }
+    }
+
+    /// All layers viewed as an array
+    pub fn view(&self) -> &vk::ImageView {
+        self.image.view()
+    }
+
+    pub fn sampler(&self) -> &vk::Sampler {
+        &self.sampler
+    }
+
+    pub fn extent(&self) -> &vk::Extent2D {
+        self.image.extent()
+    }
+
+    pub fn render_pass(&self) -> &Arc<RenderPass> {
+        &self.render_pass
+    }
+
+    /// Framebuffer drawing into a single layer
+    pub fn frame_buffer(&self, layer: usize) -> &vk::Framebuffer {
+        &self.frame_buffers.vk()[layer]
+    }
+}
+
+impl Drop for ShadowMap {
+    fn drop(&mut self) {
+        trace!("Dropping Shadow Map");
+        unsafe {
+            self.device.vk().destroy_sampler(self.sampler, None);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::{f32::consts::PI, path::Path, sync::Arc};
+
+use ash::vk;
+
+use super::{
+    environment::{decode_faces, face_coordinates, CubeMap},
+    Image, Texture, TextureError,
+};
+use crate::render::device::Device;
+
+const SKYBOX_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+/// Magnitude of the brightest generated stars, which are drawn at full brightness
+const BRIGHTEST_MAGNITUDE: f32 = -1.0;
+/// Growth of the star count per magnitude, real skies have about three times more stars at each fainter magnitude
+const MAGNITUDE_GROWTH: f32 = 0.45;
+/// Spectral classes O/B, A, F, G, K and M as linear tints with their share of the stars
+const STAR_CLASSES: [([f32; 3], f32); 6] = [
+    ([0.62, 0.72, 1.0], 0.05),
+    ([0.80, 0.85, 1.0], 0.10),
+    ([0.97, 0.96, 1.0], 0.15),
+    ([1.0, 0.93, 0.82], 0.20),
+    ([1.0, 0.78, 0.56], 0.25),
+    ([1.0, 0.62, 0.42], 0.25),
+];
+/// Size of the nebula clouds, higher values make more and smaller clouds
+const NEBULA_SCALE: f32 = 2.5;
+const NEBULA_OCTAVES: u32 = 4;
+
+/// Cubemap drawn behind all geometry, see `Background::Skybox`
+pub struct Skybox {
+    cube: Arc<Texture>,
+}
+
+impl Skybox {
+    pub fn cube(&self) -> &Arc<Texture> {
+        &self.cube
+    }
+}
+
+impl Drop for Skybox {
+    fn drop(&mut self) {
+        trace!("Dropping Skybox");
+    }
+}
+
+/// What the scene pass shows where no geometry was drawn
+#[derive(Clone)]
+pub enum Background {
+    /// Clears every view to a linear color
+    Color([f32; 3]),
+    /// Surrounds the camera with a cubemap at infinite depth, its colors scaled by `intensity`
+    Skybox { skybox: Arc<Skybox>, intensity: f32 },
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Color([0.0, 0.1, 0.2])
+    }
+}
+
+/// Parameters of a generated starfield, the same settings always generate the same sky
+#[derive(Clone, Debug)]
+pub struct StarfieldSettings {
+    pub seed: u64,
+    /// Width and height of each cube face
+    pub face_size: u32,
+    pub star_count: u32,
+    /// Apparent magnitude of the faintest stars, each magnitude is 2.5 times dimmer and more common than the last
+    pub faintest_magnitude: f32,
+    /// Brightness of the densest nebula clouds, 0 leaves space black
+    pub nebula_intensity: f32,
+    /// Linear colors the nebula clouds blend between
+    pub nebula_colors: [[f32; 3]; 2],
+}
+
+impl Default for StarfieldSettings {
+    fn default() -> Self {
+        StarfieldSettings {
+            seed: 0,
+            face_size: 256,
+            star_count: 4000,
+            faintest_magnitude: 6.0,
+            nebula_intensity: 0.06,
+            nebula_colors: [[0.35, 0.15, 0.6], [0.1, 0.4, 0.55]],
+        }
+    }
+}
+
+impl StarfieldSettings {
+    /// RGBA8 sRGB faces of the starfield in the order `SkyboxFactory::create_skybox` takes them
+    ///
+    /// The sky is generated on the CPU, which takes a moment for large faces.
+    pub fn generate(&self) -> Vec<u8> {
+        let size = self.face_size;
+        let mut random = SplitMix64(self.seed);
+
+        let nebula_offset = uv::Vec3::new(random.next_f32(), random.next_f32(), random.next_f32()) * 100.0;
+        let tint_offset = uv::Vec3::new(random.next_f32(), random.next_f32(), random.next_f32()) * 100.0;
+        let [first, second] = self.nebula_colors;
+        let (first, second) = (uv::Vec3::from(first), uv::Vec3::from(second));
+        let mut sky = CubeMap::new(size, |direction| {
+            let density = fbm(self.seed, direction * NEBULA_SCALE + nebula_offset, NEBULA_OCTAVES);
+            let cloud = smoothstep(0.45, 0.8, density);
+            let tint = smoothstep(0.3, 0.7, fbm(self.seed ^ 1, direction * NEBULA_SCALE * 0.5 + tint_offset, 2));
+            (first + (second - first) * tint) * cloud * self.nebula_intensity
+        });
+
+        let magnitude_range = self.faintest_magnitude - BRIGHTEST_MAGNITUDE;
+        let growth = 10.0_f32.powf(MAGNITUDE_GROWTH * magnitude_range) - 1.0;
+        for _ in 0..self.star_count {
+            // Uniform on the sphere: cosine of the polar angle and the azimuth are both uniform
+            let z = random.next_f32() * 2.0 - 1.0;
+            let azimuth = random.next_f32() * 2.0 * PI;
+            let radius = (1.0 - z * z).sqrt();
+            let direction = uv::Vec3::new(radius * azimuth.cos(), radius * azimuth.sin(), z);
+
+            // Inverts the cumulative count of stars up to a magnitude, which grows exponentially
+            let magnitude = BRIGHTEST_MAGNITUDE + (1.0 + random.next_f32() * growth).log10() / MAGNITUDE_GROWTH;
+            let brightness = 10.0_f32.powf(-0.4 * (magnitude - BRIGHTEST_MAGNITUDE));
+            let color = uv::Vec3::from(star_class(random.next_f32())) * brightness;
+
+            splat_star(&mut sky, direction, color, brightness);
+        }
+
+        sky.to_srgb()
+    }
+}
+
+pub struct SkyboxFactory {
+    device: Arc<Device>,
+}
+
+impl SkyboxFactory {
+    pub fn new(device: Arc<Device>) -> SkyboxFactory {
+        SkyboxFactory { device }
+    }
+
+    /// Uploads square RGBA8 sRGB faces in the +X, -X, +Y, -Y, +Z, -Z order
+    pub fn create_skybox(&self, face_size: u32, faces: [&[u8]; 6]) -> Arc<Skybox> {
+        let mut texels = Vec::with_capacity((6 * face_size * face_size * 4) as usize);
+        for face in faces.iter() {
+            assert_eq!(face.len(), (face_size * face_size * 4) as usize, "Skybox faces must be {0}x{0} RGBA8", face_size);
+            texels.extend_from_slice(face);
+        }
+        self.create(face_size, texels)
+    }
+
+    /// Decodes six PNG or JPEG faces, see `create_skybox`
+    pub fn load<P: AsRef<Path>>(&self, faces: [P; 6]) -> Result<Arc<Skybox>, TextureError> {
+        let decoded = decode_faces(&faces)?;
+        let raw = decoded.iter().map(|image| image.as_raw().as_slice()).collect::<Vec<_>>();
+        Ok(self.create_skybox(decoded[0].width(), [raw[0], raw[1], raw[2], raw[3], raw[4], raw[5]]))
+    }
+
+    /// Generates stars scattered uniformly over the sky above faint nebula clouds, see `StarfieldSettings::generate`
+    pub fn create_starfield(&self, settings: &StarfieldSettings) -> Arc<Skybox> {
+        self.create(settings.face_size, settings.generate())
+    }
+
+    fn create(&self, size: u32, texels: Vec<u8>) -> Arc<Skybox> {
+        let image = Image::cube(self.device.clone(), size, SKYBOX_FORMAT, 1, vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED);
+        Skybox {
+            cube: Texture::from_levels(self.device.clone(), image, &[texels]),
+        }
+        .into()
+    }
+}
+
+/// Tint of the spectral class picked by `choice`, from 0 to 1
+fn star_class(choice: f32) -> [f32; 3] {
+    let mut remaining = choice;
+    for (tint, share) in STAR_CLASSES.iter() {
+        if remaining < *share {
+            return *tint;
+        }
+        remaining -= share;
+    }
+    STAR_CLASSES[STAR_CLASSES.len() - 1].0
+}
+
+/// Spreads a star over the texels around it, brighter stars cover more of them
+///
+/// Stars next to a face's edge are cut off there rather than spread onto the neighbouring face.
+fn splat_star(sky: &mut CubeMap, direction: uv::Vec3, color: uv::Vec3, brightness: f32) {
+    let (face, s, t) = face_coordinates(direction);
+    let size = sky.size() as f32;
+    let (x, y) = (s * size - 0.5, t * size - 0.5);
+    let sigma = 0.4 + 0.6 * brightness.sqrt();
+    let reach = (sigma * 2.0).ceil() as i64;
+
+    // The peak keeps the star's brightness, bright stars saturate their center and glow into their neighbours
+    let (center_x, center_y) = (x.round() as i64, y.round() as i64);
+    for texel_y in center_y - reach..=center_y + reach {
+        for texel_x in center_x - reach..=center_x + reach {
+            let distance_squared = (texel_x as f32 - x).powi(2) + (texel_y as f32 - y).powi(2);
+            sky.add(face, texel_x, texel_y, color * (-distance_squared / (2.0 * sigma * sigma)).exp());
+        }
+    }
+}
+
+/// Fractal sum of value noise octaves, each twice as fine and half as strong, from 0 to 1
+fn fbm(seed: u64, point: uv::Vec3, octaves: u32) -> f32 {
+    let (mut sum, mut amplitude, mut total, mut frequency) = (0.0, 1.0, 0.0, 1.0);
+    for octave in 0..octaves {
+        sum += value_noise(seed.wrapping_add(octave as u64), point * frequency) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+/// Random values at the integer lattice, smoothly interpolated between
+fn value_noise(seed: u64, point: uv::Vec3) -> f32 {
+    let cell = uv::Vec3::new(point.x.floor(), point.y.floor(), point.z.floor());
+    let fraction = point - cell;
+    let fade = |value: f32| value * value * (3.0 - 2.0 * value);
+    let (u, v, w) = (fade(fraction.x), fade(fraction.y), fade(fraction.z));
+
+    let corner = |dx: i64, dy: i64, dz: i64| {
+        let hash = (cell.x as i64 + dx)
+            .wrapping_mul(0x8da6_b343)
+            .wrapping_add((cell.y as i64 + dy).wrapping_mul(0xd816_3841))
+            .wrapping_add((cell.z as i64 + dz).wrapping_mul(0xcb1a_b31f));
+        SplitMix64(seed ^ hash as u64).next_f32()
+    };
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), u);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), u);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), u);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), u);
+    lerp(lerp(x00, x10, v), lerp(x01, x11, v), w)
+}
+
+fn smoothstep(edge0: f32, edge1: f32, value: f32) -> f32 {
+    let t = ((value - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Small seeded generator, so the same settings generate the same starfield on every platform
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform from 0 to 1, excluding 1
+    fn next_f32(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ash::{version::DeviceV1_0, vk};
+
+use image::{imageops, imageops::FilterType, RgbaImage};
+
+use super::{image::mip_size, Image};
+use crate::render::{buffers::Buffer, commands::submit_single_time_commands, device::Device, VulkanObject};
+
+const TEXTURE_FORMAT: vk::Format = vk::Format::R8G8B8A8_SRGB;
+/// Format of textures holding data rather than colors, such as normal or metallic-roughness maps
+const LINEAR_TEXTURE_FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+const MAX_ANISOTROPY: f32 = 16.0;
+/// Entries along each color axis of a color grading lookup table, has to match the composite post shader
+const LUT_SIZE: u32 = 16;
+
+#[derive(Debug)]
+pub struct TextureError {
+    pub path: PathBuf,
+    pub source: image::ImageError,
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.source)
+    }
+}
+
+impl Error for TextureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Sampled RGBA8 image, bound through the descriptor set of the materials using it
+pub struct Texture {
+    device: Arc<Device>,
+    image: Image,
+    sampler: vk::Sampler,
+}
+
+impl Texture {
+    fn new(device: Arc<Device>, extent: vk::Extent2D, pixels: &[u8], format: vk::Format, mipmaps: bool) -> Arc<Texture> {
+        let mip_levels = if mipmaps { Image::full_mip_levels(extent) } else { 1 };
+        let blit_mipmaps = mip_levels > 1 && device.physical_device().supports_linear_blit(format);
+
+        // Without linear blits the chain is downsampled on the CPU and every level uploaded
+        let levels = if mip_levels > 1 && !blit_mipmaps {
+            debug!("{:?} doesn't support linear blits, generating mipmaps on the CPU", format);
+            cpu_mip_chain(extent, pixels, mip_levels)
+        } else {
+            vec![pixels.to_vec()]
+        };
+        let (staging_buffer, level_offsets) = Self::staging_buffer(&device, &levels);
+
+        let mut usage = vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED;
+        if blit_mipmaps {
+            usage |= vk::ImageUsageFlags::TRANSFER_SRC;
+        }
+        let image = Image::with_mip_levels(device.clone(), extent, format, mip_levels, usage, vk::ImageAspectFlags::COLOR);
+
+        submit_single_time_commands(&device, |command_buffer| {
+            image.transition_layout(command_buffer, vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+            image.copy_from_buffer(command_buffer, &staging_buffer, &level_offsets);
+            if blit_mipmaps {
+                image.generate_mipmaps(command_buffer);
+            } else {
+                image.transition_layout(command_buffer, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+            }
+        });
+
+        Self::from_image(device, image)
+    }
+
+    /// Uploads precomputed mip levels into `image`, which needs `TRANSFER_DST` usage, and samples it without wrapping
+    ///
+    /// Each level holds the tightly packed texels of all layers of the image one after another.
+    pub fn from_levels(device: Arc<Device>, image: Image, levels: &[Vec<u8>]) -> Arc<Texture> {
+        assert_eq!(levels.len() as u32, image.mip_levels(), "Every mip level of the image needs its texels");
+        let (staging_buffer, level_offsets) = Self::staging_buffer(&device, levels);
+
+        submit_single_time_commands(&device, |command_buffer| {
+            image.transition_layout(command_buffer, vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL);
+            image.copy_from_buffer(command_buffer, &staging_buffer, &level_offsets);
+            image.transition_layout(command_buffer, vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        });
+
+        Self::with_address_mode(device, image, vk::SamplerAddressMode::CLAMP_TO_EDGE)
+    }
+
+    /// Host visible buffer holding every level one after another, with the offset of each level
+    fn staging_buffer(device: &Arc<Device>, levels: &[Vec<u8>]) -> (Buffer, Vec<vk::DeviceSize>) {
+        let mut level_offsets = Vec::with_capacity(levels.len());
+        let mut staging_data = Vec::with_capacity(levels.iter().map(Vec::len).sum());
+        for level in levels {
+            level_offsets.push(staging_data.len() as vk::DeviceSize);
+            staging_data.extend_from_slice(level);
+        }
+
+        let staging_buffer = Buffer::new(
+            staging_data.len() as vk::DeviceSize,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            device.clone(),
+        );
+        staging_buffer.map_memory::<u8, _>(&staging_data);
+
+        (staging_buffer, level_offsets)
+    }
+
+    /// Samples an image which is in `SHADER_READ_ONLY_OPTIMAL` whenever the texture is drawn with
+    pub fn from_image(device: Arc<Device>, image: Image) -> Arc<Texture> {
+        Self::with_address_mode(device, image, vk::SamplerAddressMode::REPEAT)
+    }
+
+    /// Samples an image like `from_image` without wrapping at its edges, for fullscreen passes reading their neighbours
+    pub fn from_image_clamped(device: Arc<Device>, image: Image) -> Arc<Texture> {
+        Self::with_address_mode(device, image, vk::SamplerAddressMode::CLAMP_TO_EDGE)
+    }
+
+    fn with_address_mode(device: Arc<Device>, image: Image, address_mode: vk::SamplerAddressMode) -> Arc<Texture> {
+        let mip_levels = image.mip_levels();
+        let anisotropy = device.features().sampler_anisotropy == vk::TRUE;
+        let sampler_info = vk::SamplerCreateInfo::builder()
+            .mag_filter(vk::Filter::LINEAR)
+            .min_filter(vk::Filter::LINEAR)
+            .address_mode_u(address_mode)
+            .address_mode_v(address_mode)
+            .address_mode_w(address_mode)
+            .anisotropy_enable(anisotropy)
+            .max_anisotropy(
+                if anisotropy {
+                    device.physical_device().get_properties().limits.max_sampler_anisotropy.min(MAX_ANISOTROPY)
+                } else {
+                    1.0
+                },
+            )
+            .border_color(vk::BorderColor::INT_OPAQUE_BLACK)
+            .unnormalized_coordinates(false)
+            .compare_enable(false)
+            .compare_op(vk::CompareOp::ALWAYS)
+            .mipmap_mode(vk::SamplerMipmapMode::LINEAR)
+            .mip_lod_bias(0.0)
+            .min_lod(0.0)
+            .max_lod(mip_levels as f32)
+            .build();
+
+        let sampler = unsafe { device.vk().create_sampler(&sampler_info, None).unwrap() };
+
+        Texture { device, image, sampler }.into()
+    }
+
+    pub fn view(&self) -> &vk::ImageView {
+        self.image.view()
+    }
+
+    pub fn sampler(&self) -> &vk::Sampler {
+        &self.sampler
+    }
+
+    pub fn extent(&self) -> &vk::Extent2D {
+        self.image.extent()
+    }
+
+    pub fn mip_levels(&self) -> u32 {
+        self.image.mip_levels()
+    }
+}
+
+impl VulkanObject for Texture {
+    type Object = vk::Image;
+
+    fn vk(&self) -> &Self::Object {
+        self.image.vk()
+    }
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        trace!("Dropping Texture");
+        unsafe {
+            self.device.vk().destroy_sampler(self.sampler, None);
+        }
+    }
+}
+
+pub struct TextureFactory {
+    device: Arc<Device>,
+}
+
+impl TextureFactory {
+    pub fn new(device: Arc<Device>) -> TextureFactory {
+        TextureFactory { device }
+    }
+
+    /// Uploads tightly packed RGBA8 sRGB texels, with a full mip chain when `mipmaps` is set
+    pub fn create_texture(&self, width: u32, height: u32, pixels: &[u8], mipmaps: bool) -> Arc<Texture> {
+        self.create(width, height, pixels, TEXTURE_FORMAT, mipmaps)
+    }
+
+    /// Uploads texels which aren't colors and are sampled as they are, such as normal, metallic-roughness or occlusion maps
+    pub fn create_linear_texture(&self, width: u32, height: u32, pixels: &[u8], mipmaps: bool) -> Arc<Texture> {
+        self.create(width, height, pixels, LINEAR_TEXTURE_FORMAT, mipmaps)
+    }
+
+    fn create(&self, width: u32, height: u32, pixels: &[u8], format: vk::Format, mipmaps: bool) -> Arc<Texture> {
+        assert_eq!(pixels.len(), (width * height * 4) as usize, "Texture pixels must be {}x{} RGBA8", width, height);
+        Texture::new(self.device.clone(), vk::Extent2D { width, height }, pixels, format, mipmaps)
+    }
+
+    /// Color grading lookup table leaving colors unchanged, a strip of 16 slices of 16x16 texels
+    ///
+    /// Red increases along each slice, green down it and blue from one slice to the next, all gamma encoded. Tables
+    /// are made by grading a PNG of this strip and loading it with `load_linear`.
+    pub fn create_identity_lut(&self) -> Arc<Texture> {
+        let mut pixels = Vec::with_capacity((LUT_SIZE * LUT_SIZE * LUT_SIZE * 4) as usize);
+        let entry = |index: u32| (index * 255 / (LUT_SIZE - 1)) as u8;
+        for green in 0..LUT_SIZE {
+            for blue in 0..LUT_SIZE {
+                for red in 0..LUT_SIZE {
+                    pixels.extend_from_slice(&[entry(red), entry(green), entry(blue), 255]);
+                }
+            }
+        }
+
+        self.create_linear_texture(LUT_SIZE * LUT_SIZE, LUT_SIZE, &pixels, false)
+    }
+
+    /// Decodes a PNG or JPEG file and uploads it
+    pub fn load<P: AsRef<Path>>(&self, path: P, mipmaps: bool) -> Result<Arc<Texture>, TextureError> {
+        let decoded = decode(path.as_ref())?;
+        Ok(self.create_texture(decoded.width(), decoded.height(), &decoded.into_raw(), mipmaps))
+    }
+
+    /// Decodes a PNG or JPEG file and uploads it as a linear texture, see `create_linear_texture`
+    pub fn load_linear<P: AsRef<Path>>(&self, path: P, mipmaps: bool) -> Result<Arc<Texture>, TextureError> {
+        let decoded = decode(path.as_ref())?;
+        Ok(self.create_linear_texture(decoded.width(), decoded.height(), &decoded.into_raw(), mipmaps))
+    }
+}
+
+/// Decodes a PNG or JPEG file into RGBA8 texels
+pub fn decode(path: &Path) -> Result<RgbaImage, TextureError> {
+    image::open(path)
+        .map(|decoded| decoded.into_rgba8())
+        .map_err(|source| TextureError { path: path.to_path_buf(), source })
+}
+
+/// Downsamples each level from the previous one, starting with `pixels` as the base level
+fn cpu_mip_chain(extent: vk::Extent2D, pixels: &[u8], mip_levels: u32) -> Vec<Vec<u8>> {
+    let mut level = RgbaImage::from_raw(extent.width, extent.height, pixels.to_vec()).expect("Texture pixels don't match the extent");
+    let mut levels = Vec::with_capacity(mip_levels as usize);
+    for mip_level in 1..mip_levels {
+        let next = imageops::resize(&level, mip_size(extent.width, mip_level), mip_size(extent.height, mip_level), FilterType::Triangle);
+        levels.push(level.into_raw());
+        level = next;
+    }
+    levels.push(level.into_raw());
+    levels
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use ash::vk;
+
+use crate::render::{
+    buffers::Buffer,
+    device::Device,
+    images::Texture,
+    pipelines::{DescriptorLayout, DescriptorPoolAlloc},
+    VulkanObject,
+};
+
+/// Names of the compiled vertex and fragment shaders in `assets/gen/shaders`, without their `.vert.spv` / `.frag.spv` suffix
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ShaderPair {
+    pub vertex: String,
+    pub fragment: String,
+}
+
+impl ShaderPair {
+    /// Pair of shaders sharing the same name
+    pub fn named(name: &str) -> ShaderPair {
+        ShaderPair {
+            vertex: name.to_string(),
+            fragment: name.to_string(),
+        }
+    }
+
+    pub fn vertex_path(&self) -> String {
+        format!("assets/gen/shaders/{}.vert.spv", self.vertex)
+    }
+
+    pub fn fragment_path(&self) -> String {
+        format!("assets/gen/shaders/{}.frag.spv", self.fragment)
+    }
+}
+
+impl Default for ShaderPair {
+    fn default() -> Self {
+        ShaderPair::named("shader")
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BlendMode {
+    Opaque,
+    /// Blends by the fragment's alpha, drawn after the opaque batches
+    Alpha,
+    Additive,
+}
+
+/// Fixed function state baked into a material's pipeline
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RenderState {
+    pub blend: BlendMode,
+    pub cull_mode: vk::CullModeFlags,
+    pub depth_test: bool,
+    pub depth_write: bool,
+    /// Anything but `FILL` needs the `fill_mode_non_solid` device feature and falls back to `FILL` without it
+    pub polygon_mode: vk::PolygonMode,
+    /// How vertices are assembled into primitives, lines are drawn one pixel wide
+    pub topology: vk::PrimitiveTopology,
+}
+
+impl Default for RenderState {
+    fn default() -> Self {
+        RenderState {
+            blend: BlendMode::Opaque,
+            cull_mode: vk::CullModeFlags::BACK,
+            depth_test: true,
+            depth_write: true,
+            polygon_mode: vk::PolygonMode::FILL,
+            topology: vk::PrimitiveTopology::TRIANGLE_LIST,
+        }
+    }
+}
+
+/// Uniform block of a material, read by the fragment shader at set 1 binding 1
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct MaterialParams {
+    /// Multiplies the texture and vertex colors, alpha is only used by blended materials
+    pub color: [f32; 4],
+    /// Color of the highlights of the lit shaders
+    pub specular: [f32; 3],
+    /// Blinn-Phong exponent, higher values give smaller and sharper highlights
+    pub shininess: f32,
+    /// Light given off by the PBR shaders, multiplies the emissive texture
+    pub emissive: [f32; 3],
+    /// Multiplies the blue channel of the metallic-roughness texture
+    pub metallic: f32,
+    /// Multiplies the green channel of the metallic-roughness texture
+    pub roughness: f32,
+    /// Scales the x and y of the normal texture
+    pub normal_scale: f32,
+    /// How much of the occlusion texture's red channel darkens the environment's light, from 0 to 1
+    pub occlusion_strength: f32,
+}
+
+impl Default for MaterialParams {
+    fn default() -> Self {
+        MaterialParams {
+            color: [1.0, 1.0, 1.0, 1.0],
+            specular: [0.5, 0.5, 0.5],
+            shininess: 32.0,
+            emissive: [0.0, 0.0, 0.0],
+            metallic: 0.0,
+            roughness: 0.5,
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+        }
+    }
+}
+
+/// Everything a material is created from
+///
+/// Textures are sampled with the mesh's uvs. The maps other than `texture` follow glTF's metallic-roughness
+/// workflow, are only read by the PBR shaders and should be created as linear textures, except for the emissive one.
+#[derive(Clone, Default)]
+pub struct MaterialData {
+    pub shaders: ShaderPair,
+    pub state: RenderState,
+    pub params: MaterialParams,
+    /// Base color, a plain white texture when `None`
+    pub texture: Option<Arc<Texture>>,
+    /// Roughness in the green channel and metallic in the blue one, white when `None`
+    pub metallic_roughness_texture: Option<Arc<Texture>>,
+    /// Tangent space normals, needs meshes with tangents and leaves the normals unchanged when `None`
+    pub normal_texture: Option<Arc<Texture>>,
+    /// Ambient occlusion in the red channel, white when `None`
+    pub occlusion_texture: Option<Arc<Texture>>,
+    /// sRGB texture multiplied by `MaterialParams::emissive`, white when `None`
+    pub emissive_texture: Option<Arc<Texture>>,
+}
+
+/// Descriptor set shared by every mesh drawn with the same shaders, state, parameters and texture
+///
+/// The pipeline comes from the `PipelineCache` when drawing, so it matches the sample count of the pass drawn into.
+pub struct Material {
+    data: MaterialData,
+    _params_buffer: Buffer,
+    descriptor_set: Arc<DescriptorPoolAlloc>,
+}
+
+impl Material {
+    fn new(device: Arc<Device>, layout: &Arc<DescriptorLayout>, default_textures: &DefaultTextures, data: MaterialData) -> Arc<Material> {
+        let params_buffer = Buffer::new(
+            std::mem::size_of::<MaterialParams>() as vk::DeviceSize,
+            vk::BufferUsageFlags::UNIFORM_BUFFER,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            device.clone(),
+        );
+        params_buffer.map_memory::<f32, _>(&[data.params]);
+
+        let descriptor_set = device.descriptor_pool().alloc(std::slice::from_ref(layout));
+        let white = &default_textures.white;
+        descriptor_set.update_texture(0, data.texture.as_deref().unwrap_or(white));
+        descriptor_set.update_buffers(1, &[&params_buffer], std::mem::size_of::<MaterialParams>() as vk::DeviceSize);
+        descriptor_set.update_texture(2, data.metallic_roughness_texture.as_deref().unwrap_or(white));
+        descriptor_set.update_texture(3, data.normal_texture.as_deref().unwrap_or(&default_textures.flat_normal));
+        descriptor_set.update_texture(4, data.occlusion_texture.as_deref().unwrap_or(white));
+        descriptor_set.update_texture(5, data.emissive_texture.as_deref().unwrap_or(white));
+
+        Material {
+            data,
+            _params_buffer: params_buffer,
+            descriptor_set,
+        }
+        .into()
+    }
+
+    pub fn shaders(&self) -> &ShaderPair {
+        &self.data.shaders
+    }
+
+    pub fn state(&self) -> &RenderState {
+        &self.data.state
+    }
+
+    pub fn params(&self) -> &MaterialParams {
+        &self.data.params
+    }
+
+    pub fn texture(&self) -> Option<&Arc<Texture>> {
+        self.data.texture.as_ref()
+    }
+
+    /// Set laid out by `DescriptorLayout::material`
+    pub fn descriptor_set(&self) -> &vk::DescriptorSet {
+        &self.descriptor_set.vk()[0]
+    }
+}
+
+impl Drop for Material {
+    fn drop(&mut self) {
+        trace!("Dropping Material");
+    }
+}
+
+/// Textures bound in place of the ones a material doesn't set
+#[derive(Clone)]
+pub struct DefaultTextures {
+    pub white: Arc<Texture>,
+    /// Linear texture of normals pointing straight out of the surface
+    pub flat_normal: Arc<Texture>,
+}
+
+pub struct MaterialFactory {
+    device: Arc<Device>,
+    layout: Arc<DescriptorLayout>,
+    default_textures: DefaultTextures,
+}
+
+impl MaterialFactory {
+    pub fn new(device: Arc<Device>, layout: Arc<DescriptorLayout>, default_textures: DefaultTextures) -> MaterialFactory {
+        MaterialFactory { device, layout, default_textures }
+    }
+
+    /// Materials with the same shaders and render state share one pipeline per sample count
+    pub fn create_material(&self, data: MaterialData) -> Arc<Material> {
+        Material::new(self.device.clone(), &self.layout, &self.default_textures, data)
+    }
+}
@@ -0,0 +1,3 @@
+mod material;
+
+pub use material::{BlendMode, DefaultTextures, Material, MaterialData, MaterialFactory, MaterialParams, RenderState, ShaderPair};
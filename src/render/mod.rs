@@ -2,96 +2,260 @@ mod buffers;
 mod commands;
 mod constants;
 pub mod device;
+mod grid;
+pub mod images;
+pub mod materials;
 pub mod models;
 mod pipelines;
+mod post;
 mod renderpasses;
+mod shadow;
 mod sync;
 mod utilities;
+mod view;
 
 use ash::{version::DeviceV1_0, vk};
 use imgui_rs_vulkan_renderer::RendererVkContext;
+use raw_window_handle::HasRawWindowHandle;
 
-use std::{sync::Arc, time::Instant};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
 
-use buffers::{UniformBufferObject, UniformTestObject};
-use commands::CommandBuffer;
+use buffers::{Buffer, InstanceBuffer, LightBuffer, LineBuffer, UniformBufferObject, UniformTestObject};
+use commands::{submit_single_time_commands, CommandBuffer};
 use constants::*;
-use device::{
-    window::{HINSTANCE, HWND},
-    DebugMessenger, Device, Instance, PhysicalDevice, Surface, Window,
-};
-use pipelines::{DescriptorLayout, DescriptorPoolAlloc, Pipeline};
-use renderpasses::{FrameBuffer, RenderPass, SwapChain};
+use device::{DebugMessenger, Device, DeviceError, Instance, PhysicalDevice, Surface, Window};
+use grid::Grid;
+use images::{Background, Environment, EnvironmentFactory, Image, RenderTexture, RenderTextureFactory, ShadowMap, SkyboxFactory, Texture, TextureFactory};
+use materials::{BlendMode, DefaultTextures, Material, MaterialData, MaterialFactory, MaterialParams, RenderState, ShaderPair};
+use pipelines::{DescriptorLayout, DescriptorPoolAlloc, Pipeline, PipelineCache};
+use post::PostProcess;
+use renderpasses::{FrameBuffer, RenderPass, RenderTarget, SwapChain};
 use sync::SyncObjects;
 
-use models::MeshFactory;
+use models::{InstanceBatch, InstanceData, Mesh, MeshFactory, Vertex, VertexAttributes};
+
+pub use buffers::LightData;
+pub use constants::SHADOW_MAP_SIZE;
+pub use grid::{GridPlane, GridSettings};
+pub use post::PostSettings;
+pub use shadow::{ShadowCaster, SHADOW_CASCADES};
+pub use view::{Frustum, RenderStats, RenderView, ViewMode, Viewport};
 
 pub struct GraphicContext {
     _instance: Arc<Instance>,
     _debug_messenger: Option<Arc<DebugMessenger>>,
-    surface: Arc<Surface>,
+    surface: Option<Arc<Surface>>,
     device: Arc<Device>,
-    swapchain: Arc<SwapChain>,
+    target: RenderTarget,
     render_pass: Arc<RenderPass>,
-    pipeline: Arc<Pipeline>,
-    frame_buffers: Arc<FrameBuffer>,
+    overlay_render_pass: Arc<RenderPass>,
+    pipeline_cache: Arc<Mutex<PipelineCache>>,
+    /// Samples per pixel of the scene pass
+    samples: vk::SampleCountFlags,
+    /// Multisampled color attachment resolved into the HDR image, `None` with a single sample
+    color_image: Option<Image>,
+    depth_image: Image,
+    frame_buffer: Arc<FrameBuffer>,
+    overlay_frame_buffers: Arc<FrameBuffer>,
     command_buffers: Arc<CommandBuffer>,
     pub sync_objects: SyncObjects,
-    window: Window,
-    start_time: Instant,
+    window: Option<Window>,
     uniform_buffers: Vec<UniformBufferObject>,
+    instance_buffers: Vec<InstanceBuffer>,
+    light_buffers: Vec<LightBuffer>,
     descriptor_layout: Arc<DescriptorLayout>,
+    material_layout: Arc<DescriptorLayout>,
+    /// Bound in place of the textures materials don't set
+    default_textures: DefaultTextures,
+    default_material: Arc<Material>,
+    brdf_lut: Arc<Texture>,
+    /// Lights the PBR materials when no other environment is set
+    default_environment: Arc<Environment>,
+    environment: Arc<Environment>,
     descriptor_set: Arc<DescriptorPoolAlloc>,
+    shadow_map: ShadowMap,
+    shadow_pipeline: Arc<Pipeline>,
+    shadow_layout: Arc<DescriptorLayout>,
+    /// One light view and projection per shadow map layer of every swapchain image
+    shadow_buffers: Vec<UniformBufferObject>,
+    shadow_sets: Arc<DescriptorPoolAlloc>,
+    /// Shadow map layers rendered in the current frame
+    shadow_layers: usize,
+    post_layout: Arc<DescriptorLayout>,
+    post_process: PostProcess,
+    post_settings: PostSettings,
+    /// Color grading table used when the post settings don't set one
+    default_lut: Arc<Texture>,
+    background: Background,
+    /// Draws the skybox of the background, `None` when views are cleared to a color
+    background_material: Option<Arc<Material>>,
+    /// Unit cube around the camera the skybox is drawn on
+    skybox_mesh: Mesh,
+    grid: Grid,
+    grid_settings: GridSettings,
+    /// Debug lines of each swapchain image, drawn over the scene of every view
+    line_buffers: Vec<LineBuffer>,
+    line_material: Arc<Material>,
+    view_mode: ViewMode,
+    /// Addresses of the meshes and pipelines whose batches were skipped, so each pair is only reported once
+    skipped_meshes: Mutex<HashSet<(usize, usize)>>,
 }
 
 impl GraphicContext {
-    pub fn new(hwnd: HWND, hinstance: HINSTANCE) -> GraphicContext {
+    pub fn new<W: HasRawWindowHandle>(window_handle: &W, window_size: (u32, u32)) -> GraphicContext {
         let validation_enabled = validation_enabled();
 
-        let instance = Instance::new(validation_enabled);
+        let window = Window::new(window_handle.raw_window_handle(), window_size);
+
+        let instance = Instance::new(validation_enabled, Some(window.handle())).unwrap();
         let debug_messenger = if validation_enabled { Some(DebugMessenger::new(&instance)) } else { None };
 
-        let window = Window::new(hwnd);
-        let surface = Surface::new(hwnd, hinstance, instance.clone());
+        let surface = Surface::new(&window, instance.clone());
 
-        let physical_device = PhysicalDevice::new(instance.clone(), &surface);
+        let physical_device = PhysicalDevice::new(instance.clone(), Some(&surface)).unwrap();
         let device = Device::new(physical_device, validation_enabled);
 
-        let swapchain = SwapChain::new(device.clone(), surface.clone(), &window, None);
+        let target = RenderTarget::Swapchain(SwapChain::new(device.clone(), surface.clone(), &window, None));
+
+        Self::from_target(instance, debug_messenger, Some(surface), device, target, Some(window))
+    }
+
+    /// Creates a context without a window that renders into an offscreen image which can be read back with `read_frame`
+    ///
+    /// Fails only when the machine has no Vulkan device to render with, any other failure panics.
+    pub fn new_headless(extent: (u32, u32)) -> Result<GraphicContext, DeviceError> {
+        let validation_enabled = validation_enabled();
+
+        let instance = Instance::new(validation_enabled, None)?;
+        let debug_messenger = if validation_enabled { Some(DebugMessenger::new(&instance)) } else { None };
+
+        let physical_device = PhysicalDevice::new(instance.clone(), None)?;
+        let device = Device::new(physical_device, validation_enabled);
 
-        let render_pass = RenderPass::new(device.clone(), swapchain.surface_format().format);
+        let image = Image::new(
+            device.clone(),
+            vk::Extent2D { width: extent.0, height: extent.1 },
+            HEADLESS_FORMAT,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::TRANSFER_SRC,
+            vk::ImageAspectFlags::COLOR,
+        );
+
+        Ok(Self::from_target(instance, debug_messenger, None, device, RenderTarget::Offscreen(image), None))
+    }
+
+    fn from_target(
+        instance: Arc<Instance>,
+        debug_messenger: Option<Arc<DebugMessenger>>,
+        surface: Option<Arc<Surface>>,
+        device: Arc<Device>,
+        target: RenderTarget,
+        window: Option<Window>,
+    ) -> GraphicContext {
+        let samples = vk::SampleCountFlags::TYPE_1;
+        let (color_image, depth_image) = Self::create_scene_images(&device, &target, samples);
+        let render_pass = RenderPass::new(device.clone(), HDR_FORMAT, depth_image.format(), samples);
+        let overlay_render_pass = RenderPass::overlay(device.clone(), target.format(), target.final_layout());
         let descriptor_layout = DescriptorLayout::new(device.clone());
-        let pipeline = Pipeline::new(device.clone(), &render_pass, &descriptor_layout);
-        let framebuffer = FrameBuffer::new(device.clone(), &swapchain, &render_pass);
-        let command_buffers = CommandBuffer::new(device.clone(), framebuffer.vk().len() as u32);
-        let sync_objects = SyncObjects::new(device.clone(), MAX_FRAMES_IN_FLIGHT, swapchain.images().len());
-        let start_time = Instant::now();
+        let material_layout = DescriptorLayout::material(device.clone());
+        let pipeline_cache = Arc::new(Mutex::new(PipelineCache::new(device.clone(), vec![descriptor_layout.clone(), material_layout.clone()])));
+        let texture_factory = TextureFactory::new(device.clone());
+        let default_textures = DefaultTextures {
+            white: texture_factory.create_texture(1, 1, &[255; 4], false),
+            flat_normal: texture_factory.create_linear_texture(1, 1, &[128, 128, 255, 255], false),
+        };
+        let material_factory = MaterialFactory::new(device.clone(), material_layout.clone(), default_textures.clone());
+        let default_material = material_factory.create_material(MaterialData::default());
+        // Lines keep their vertex colors and are hidden by the scene without hiding each other
+        let line_material = material_factory.create_material(MaterialData {
+            state: RenderState {
+                cull_mode: vk::CullModeFlags::NONE,
+                depth_write: false,
+                topology: vk::PrimitiveTopology::LINE_LIST,
+                ..RenderState::default()
+            },
+            ..MaterialData::default()
+        });
+        let environment_factory = EnvironmentFactory::new(device.clone());
+        let brdf_lut = environment_factory.create_brdf_lut();
+        let default_environment = environment_factory.create_uniform([1.0, 1.0, 1.0]);
+        let default_lut = texture_factory.create_identity_lut();
+        let post_layout = DescriptorLayout::post(device.clone());
+        let post_process = PostProcess::new(device.clone(), &target, &post_layout, default_lut.clone());
+        let skybox_mesh = Self::create_skybox_mesh(&device);
+        let grid = Grid::new(device.clone(), &descriptor_layout, target.image_count());
+        let framebuffer = Self::create_scene_frame_buffer(&device, &post_process, color_image.as_ref(), &depth_image, &render_pass);
+        let overlay_framebuffer = FrameBuffer::new(device.clone(), &target, &overlay_render_pass);
+        let command_buffers = CommandBuffer::new(device.clone(), target.image_count() as u32);
+        let sync_objects = SyncObjects::new(device.clone(), MAX_FRAMES_IN_FLIGHT, target.image_count());
 
         let mut u_buffers = Vec::new();
-        for _ in 0..swapchain.images().len() {
+        for _ in 0..target.image_count() * MAX_VIEWS {
             u_buffers.push(UniformBufferObject::new(&device));
         }
+        let instance_buffers = (0..target.image_count()).map(|_| InstanceBuffer::new(&device)).collect();
+        let light_buffers = (0..target.image_count()).map(|_| LightBuffer::new(&device)).collect::<Vec<_>>();
+        let line_buffers = (0..target.image_count()).map(|_| LineBuffer::new(&device)).collect();
 
-        let layouts = (0..swapchain.images().len()).map(|_| descriptor_layout.clone()).collect::<Vec<_>>();
-        let descriptor_set = device.descriptor_pool().alloc(&layouts);
-        descriptor_set.update(&u_buffers);
+        let shadow_map = ShadowMap::new(device.clone());
+        let shadow_layout = DescriptorLayout::shadow(device.clone());
+        let shadow_pipeline = Pipeline::depth_only(device.clone(), shadow_map.render_pass(), std::slice::from_ref(&shadow_layout), "assets/gen/shaders/shadow.vert.spv");
+        let shadow_buffers = (0..target.image_count() * MAX_SHADOW_MAPS).map(|_| UniformBufferObject::new(&device)).collect::<Vec<_>>();
+        let shadow_sets = Self::create_shadow_sets(&device, &shadow_layout, &shadow_buffers);
+
+        let descriptor_set = Self::create_descriptor_set(&device, &descriptor_layout, &u_buffers, &light_buffers);
+        Self::update_environment(&descriptor_set, &default_environment, &brdf_lut);
+        descriptor_set.update_image(5, *shadow_map.view(), *shadow_map.sampler());
 
         GraphicContext {
             _instance: instance,
             _debug_messenger: debug_messenger,
             surface,
             device,
-            swapchain,
+            target,
             render_pass,
-            pipeline,
-            frame_buffers: framebuffer,
+            overlay_render_pass,
+            pipeline_cache,
+            samples,
+            color_image,
+            depth_image,
+            frame_buffer: framebuffer,
+            overlay_frame_buffers: overlay_framebuffer,
             command_buffers,
             sync_objects,
             window,
-            start_time,
             uniform_buffers: u_buffers,
+            instance_buffers,
+            light_buffers,
             descriptor_layout,
+            material_layout,
+            default_textures,
+            default_material,
+            brdf_lut,
+            environment: default_environment.clone(),
+            default_environment,
             descriptor_set,
+            shadow_map,
+            shadow_pipeline,
+            shadow_layout,
+            shadow_buffers,
+            shadow_sets,
+            shadow_layers: 0,
+            post_layout,
+            post_process,
+            post_settings: PostSettings::default(),
+            default_lut,
+            background: Background::default(),
+            background_material: None,
+            skybox_mesh,
+            grid,
+            grid_settings: GridSettings::default(),
+            line_buffers,
+            line_material,
+            view_mode: ViewMode::default(),
+            skipped_meshes: Mutex::new(HashSet::new()),
         }
     }
 
@@ -99,12 +263,208 @@ impl GraphicContext {
         &self.device
     }
 
-    pub fn get_window(&self) -> &Window {
-        &self.window
+    pub fn get_window(&self) -> Option<&Window> {
+        self.window.as_ref()
+    }
+
+    pub fn extent(&self) -> &vk::Extent2D {
+        self.target.extent()
+    }
+
+    /// Whether the target encodes what is written to it as sRGB, so the overlay has to draw in linear colors
+    pub fn is_srgb_target(&self) -> bool {
+        matches!(self.target.format(), vk::Format::R8G8B8A8_SRGB | vk::Format::B8G8R8A8_SRGB)
+    }
+
+    /// Render pass the ui is recorded in, after `begin_overlay`
+    pub fn get_overlay_render_pass(&self) -> &vk::RenderPass {
+        self.overlay_render_pass.vk()
+    }
+
+    /// Multisampled color image, only needed with more than one sample, and depth image the scene pass draws into
+    fn create_scene_images(device: &Arc<Device>, target: &RenderTarget, samples: vk::SampleCountFlags) -> (Option<Image>, Image) {
+        let color_image = if samples == vk::SampleCountFlags::TYPE_1 {
+            None
+        } else {
+            Some(Image::multisampled(device.clone(), *target.extent(), HDR_FORMAT, samples))
+        };
+        let depth_image = Image::depth(device.clone(), *target.extent(), device.physical_device().find_depth_format(), samples);
+
+        (color_image, depth_image)
+    }
+
+    /// Framebuffer drawing the scene into the post process' HDR image, through `color_image` when multisampling
+    fn create_scene_frame_buffer(device: &Arc<Device>, post_process: &PostProcess, color_image: Option<&Image>, depth_image: &Image, render_pass: &Arc<RenderPass>) -> Arc<FrameBuffer> {
+        match color_image {
+            Some(color_image) => FrameBuffer::resolving(device.clone(), color_image, depth_image, *post_process.hdr_view(), render_pass),
+            None => FrameBuffer::with_views(device.clone(), &[*post_process.hdr_view()], *depth_image.extent(), Some(depth_image), render_pass),
+        }
+    }
+
+    /// Rebuilds the scene pass and the images and framebuffer it draws into for the current target and sample count
+    fn recreate_scene_pass(&mut self) {
+        let (color_image, depth_image) = Self::create_scene_images(&self.device, &self.target, self.samples);
+        self.render_pass = RenderPass::new(self.device.clone(), HDR_FORMAT, depth_image.format(), self.samples);
+        self.frame_buffer = Self::create_scene_frame_buffer(&self.device, &self.post_process, color_image.as_ref(), &depth_image, &self.render_pass);
+        self.color_image = color_image;
+        self.depth_image = depth_image;
+    }
+
+    /// Draws the scene with `samples` samples per pixel, lowered to the highest count the device supports
+    ///
+    /// Waits for the device to be idle when the sample count changes.
+    pub fn set_msaa_samples(&mut self, samples: u32) {
+        let supported = self.device.physical_device().supported_samples(samples);
+        if supported == self.samples {
+            return;
+        }
+        if supported.as_raw() < samples {
+            info!("{} samples aren't supported by the device, using {}", samples, supported.as_raw());
+        }
+
+        self.wait_device();
+        self.samples = supported;
+        self.recreate_scene_pass();
+    }
+
+    /// One set per view of every swapchain image, each pointing at its own camera uniform buffer and at the lights of its image
+    fn create_descriptor_set(device: &Arc<Device>, descriptor_layout: &Arc<DescriptorLayout>, uniform_buffers: &[UniformBufferObject], light_buffers: &[LightBuffer]) -> Arc<DescriptorPoolAlloc> {
+        let layouts = uniform_buffers.iter().map(|_| descriptor_layout.clone()).collect::<Vec<_>>();
+        let descriptor_set = device.descriptor_pool().alloc(&layouts);
+        descriptor_set.update(uniform_buffers);
+
+        let light_buffers = light_buffers.iter().flat_map(|light_buffer| (0..MAX_VIEWS).map(move |_| light_buffer.buffer())).collect::<Vec<_>>();
+        descriptor_set.update_buffers(1, &light_buffers, LightBuffer::size());
+        descriptor_set
+    }
+
+    fn create_shadow_sets(device: &Arc<Device>, shadow_layout: &Arc<DescriptorLayout>, shadow_buffers: &[UniformBufferObject]) -> Arc<DescriptorPoolAlloc> {
+        let layouts = shadow_buffers.iter().map(|_| shadow_layout.clone()).collect::<Vec<_>>();
+        let shadow_sets = device.descriptor_pool().alloc(&layouts);
+        shadow_sets.update(shadow_buffers);
+        shadow_sets
+    }
+
+    fn update_environment(descriptor_set: &DescriptorPoolAlloc, environment: &Environment, brdf_lut: &Texture) {
+        descriptor_set.update_texture(2, environment.irradiance());
+        descriptor_set.update_texture(3, environment.prefiltered());
+        descriptor_set.update_texture(4, brdf_lut);
+    }
+
+    /// Lights the PBR materials with `environment`, or with a uniform white one when `None`
+    ///
+    /// Waits for the device to be idle when the environment changes.
+    pub fn set_environment(&mut self, environment: Option<&Arc<Environment>>) {
+        let environment = environment.unwrap_or(&self.default_environment);
+        if Arc::ptr_eq(environment, &self.environment) {
+            return;
+        }
+
+        self.wait_device();
+        self.environment = environment.clone();
+        Self::update_environment(&self.descriptor_set, &self.environment, &self.brdf_lut);
+    }
+
+    /// Runs the frame through the post passes enabled in `settings`, grading it with the identity table when it
+    /// doesn't set one
+    ///
+    /// Waits for the device to be idle when the color grading table changes.
+    pub fn set_post_settings(&mut self, settings: &PostSettings) {
+        let lut = settings.lut.as_ref().unwrap_or(&self.default_lut);
+        if !Arc::ptr_eq(lut, self.post_process.lut()) {
+            self.wait_device();
+            self.post_process.set_lut(lut.clone());
+        }
+        self.post_settings = settings.clone();
+    }
+
+    /// Shows `background` wherever the views don't draw any geometry
+    ///
+    /// Waits for the device to be idle when the skybox or its intensity changes.
+    pub fn set_background(&mut self, background: &Background) {
+        let changed = match (background, &self.background) {
+            (Background::Color(_), Background::Color(_)) => false,
+            (
+                Background::Skybox { skybox, intensity },
+                Background::Skybox {
+                    skybox: current,
+                    intensity: current_intensity,
+                },
+            ) => !Arc::ptr_eq(skybox, current) || intensity != current_intensity,
+            _ => true,
+        };
+        if changed {
+            self.wait_device();
+            self.background_material = match background {
+                Background::Color(_) => None,
+                Background::Skybox { skybox, intensity } => Some(self.create_material_factory().create_material(MaterialData {
+                    shaders: ShaderPair::named("skybox"),
+                    state: RenderState {
+                        cull_mode: vk::CullModeFlags::NONE,
+                        depth_test: false,
+                        depth_write: false,
+                        ..RenderState::default()
+                    },
+                    params: MaterialParams {
+                        color: [*intensity, *intensity, *intensity, 1.0],
+                        ..MaterialParams::default()
+                    },
+                    texture: Some(skybox.cube().clone()),
+                    ..MaterialData::default()
+                })),
+            };
+        }
+        self.background = background.clone();
+    }
+
+    /// Draws the editor grid over every view when `settings` enables it
+    pub fn set_grid_settings(&mut self, settings: &GridSettings) {
+        self.grid_settings = settings.clone();
+    }
+
+    /// Shades the batches of the following frames as `view_mode` shows them
+    pub fn set_view_mode(&mut self, view_mode: ViewMode) {
+        self.view_mode = view_mode;
+    }
+
+    /// Whether the device draws `ViewMode::Wireframe` with lines, it fills the triangles otherwise
+    pub fn supports_wireframe(&self) -> bool {
+        self.device.features().fill_mode_non_solid == vk::TRUE
+    }
+
+    /// Draws lines between each pair of `vertices` over the views of the frame drawn into `image_index`
+    ///
+    /// Has to be called before `draw_views`, the lines are depth tested against the scene.
+    pub fn set_debug_lines(&mut self, image_index: usize, vertices: &[Vertex]) {
+        self.line_buffers[image_index].update(vertices);
     }
 
-    pub fn get_render_pass(&self) -> &vk::RenderPass {
-        self.render_pass.vk()
+    fn create_skybox_mesh(device: &Arc<Device>) -> Mesh {
+        let vertices = (0..8)
+            .map(|corner| Vertex {
+                pos: [
+                    if corner & 1 == 0 { -1.0 } else { 1.0 },
+                    if corner & 2 == 0 { -1.0 } else { 1.0 },
+                    if corner & 4 == 0 { -1.0 } else { 1.0 },
+                ],
+                ..Vertex::default()
+            })
+            .collect::<Vec<_>>();
+        let indices = [0, 2, 1, 1, 2, 3, 4, 5, 6, 5, 7, 6, 0, 1, 4, 1, 5, 4, 2, 6, 3, 3, 6, 7, 0, 4, 2, 2, 4, 6, 1, 3, 5, 3, 7, 5];
+        Mesh::new(&vertices, Some(&indices), VertexAttributes::POSITION | VertexAttributes::COLOR, device)
+    }
+
+    /// Clear value of the color attachment, black behind a skybox
+    fn clear_color(&self) -> vk::ClearValue {
+        let color = match self.background {
+            Background::Color(color) => color,
+            Background::Skybox { .. } => [0.0, 0.0, 0.0],
+        };
+        vk::ClearValue {
+            color: vk::ClearColorValue {
+                float32: [color[0], color[1], color[2], 1.0],
+            },
+        }
     }
 
     pub fn wait_device(&self) {
@@ -112,10 +472,15 @@ impl GraphicContext {
     }
 
     pub fn acquire_next_image(&self) -> Result<usize, &'static str> {
+        let swapchain = match &self.target {
+            RenderTarget::Swapchain(swapchain) => swapchain,
+            RenderTarget::Offscreen(_) => return Ok(0),
+        };
+
         let image_result = unsafe {
-            self.swapchain
+            swapchain
                 .get_loader()
-                .acquire_next_image(*self.swapchain.vk(), std::u64::MAX, *self.sync_objects.get_image_semaphore(), vk::Fence::null())
+                .acquire_next_image(*swapchain.vk(), std::u64::MAX, *self.sync_objects.get_image_semaphore(), vk::Fence::null())
         };
 
         match image_result {
@@ -126,43 +491,363 @@ impl GraphicContext {
     }
 
     pub fn recreate_swapchain(&mut self) {
+        let (surface, window, old_swapchain) = match (&self.surface, &self.window, &self.target) {
+            (Some(surface), Some(window), RenderTarget::Swapchain(swapchain)) => (surface, window, swapchain),
+            _ => return,
+        };
+
         trace!("Recreating wapchain");
         self.wait_device();
 
-        self.swapchain = SwapChain::new(self.device.clone(), self.surface.clone(), &self.window, Some(&self.swapchain));
-        self.render_pass = RenderPass::new(self.device.clone(), self.swapchain.surface_format().format);
-        self.frame_buffers = FrameBuffer::new(self.device.clone(), &self.swapchain, &self.render_pass);
+        self.target = RenderTarget::Swapchain(SwapChain::new(self.device.clone(), surface.clone(), window, Some(old_swapchain)));
+        self.post_process = PostProcess::new(self.device.clone(), &self.target, &self.post_layout, self.post_process.lut().clone());
+        self.recreate_scene_pass();
+        self.overlay_render_pass = RenderPass::overlay(self.device.clone(), self.target.format(), self.target.final_layout());
+        self.overlay_frame_buffers = FrameBuffer::new(self.device.clone(), &self.target, &self.overlay_render_pass);
         self.uniform_buffers = Vec::new();
-        for _ in 0..self.swapchain.images().len() {
+        for _ in 0..self.target.image_count() * MAX_VIEWS {
             self.uniform_buffers.push(UniformBufferObject::new(&self.device));
         }
+        self.instance_buffers = (0..self.target.image_count()).map(|_| InstanceBuffer::new(&self.device)).collect();
+        self.light_buffers = (0..self.target.image_count()).map(|_| LightBuffer::new(&self.device)).collect();
+        self.grid = Grid::new(self.device.clone(), &self.descriptor_layout, self.target.image_count());
+        self.line_buffers = (0..self.target.image_count()).map(|_| LineBuffer::new(&self.device)).collect();
+
+        self.shadow_buffers = (0..self.target.image_count() * MAX_SHADOW_MAPS).map(|_| UniformBufferObject::new(&self.device)).collect();
+        self.shadow_sets = Self::create_shadow_sets(&self.device, &self.shadow_layout, &self.shadow_buffers);
 
-        let layouts = (0..self.swapchain.images().len()).map(|_| self.descriptor_layout.clone()).collect::<Vec<_>>();
-        self.descriptor_set = self.device.descriptor_pool().alloc(&layouts);
-        self.descriptor_set.update(&self.uniform_buffers);
-        self.command_buffers = CommandBuffer::new(self.device.clone(), self.frame_buffers.vk().len() as u32);
+        self.descriptor_set = Self::create_descriptor_set(&self.device, &self.descriptor_layout, &self.uniform_buffers, &self.light_buffers);
+        Self::update_environment(&self.descriptor_set, &self.environment, &self.brdf_lut);
+        self.descriptor_set.update_image(5, *self.shadow_map.view(), *self.shadow_map.sampler());
+        self.command_buffers = CommandBuffer::new(self.device.clone(), self.target.image_count() as u32);
     }
 
     pub fn begin_command_buffer(&self, image_index: usize) {
-        let clear_color = vk::ClearValue {
-            color: vk::ClearColorValue {
-                float32: [0.0f32, 0.1f32, 0.2f32, 1.0f32],
-            },
+        self.command_buffers.begin(image_index);
+    }
+
+    /// Ends the scene pass, records the post passes taking the scene to the frame, and starts the overlay pass drawn on
+    /// top of them
+    pub fn begin_overlay(&self, image_index: usize) {
+        let render_pass_info = vk::RenderPassBeginInfo::builder()
+            .render_pass(*self.overlay_render_pass.vk())
+            .framebuffer(self.overlay_frame_buffers.vk()[image_index])
+            .render_area(self.target.scissor())
+            .build();
+
+        self.command_buffers.end_render_pass(image_index);
+        if self.view_mode == ViewMode::Shaded {
+            self.post_process.record(&self.command_buffers, image_index, &self.post_settings);
+        } else {
+            // Debug views are shown with their colors as they are
+            let settings = PostSettings {
+                tonemapping: false,
+                ..PostSettings::default()
+            };
+            self.post_process.record(&self.command_buffers, image_index, &settings);
+        }
+        self.command_buffers.begin_render_pass(image_index, &render_pass_info);
+    }
+
+    /// Sets the ambient color and the lights shaded by the lit materials of the frame drawn into `image_index`, and the
+    /// lights throwing shadows in it
+    ///
+    /// Has to be called before `draw_views`, lights past `MAX_LIGHTS` are dropped. Casters get shadow map layers in
+    /// order, those which don't fit into the remaining layers are drawn without shadows.
+    pub fn update_lights(&mut self, image_index: usize, ambient: [f32; 3], lights: &[LightData], casters: &[ShadowCaster]) {
+        if lights.len() > MAX_LIGHTS {
+            warn!("Only shading the first {} of {} lights", MAX_LIGHTS, lights.len());
+        }
+
+        let shaded_count = lights.len().min(MAX_LIGHTS);
+        let mut lights = lights.to_vec();
+        let mut shadow_matrices = Vec::new();
+        for caster in casters.iter().filter(|caster| caster.light < shaded_count) {
+            if shadow_matrices.len() + caster.matrices.len() > MAX_SHADOW_MAPS {
+                warn!("Out of shadow map layers, light {} is drawn without shadows", caster.light);
+                continue;
+            }
+            lights[caster.light].set_shadow(shadow_matrices.len(), caster.matrices.len());
+            shadow_matrices.extend_from_slice(&caster.matrices);
+        }
+
+        self.light_buffers[image_index].update(ambient, &lights, &shadow_matrices);
+        for (layer, matrix) in shadow_matrices.iter().enumerate() {
+            self.shadow_buffers[image_index * MAX_SHADOW_MAPS + layer].update2::<f32, _>(&[UniformTestObject {
+                view: *matrix,
+                proj: uv::Mat4::identity(),
+                camera_pos: uv::Vec4::zero(),
+            }]);
+        }
+        self.shadow_layers = shadow_matrices.len();
+    }
+
+    /// Records the shadow passes of the lights set by `update_lights` and the views drawn into render textures, then starts
+    /// the scene pass and records the views drawn into the frame
+    ///
+    /// Has to be called once per frame between `begin_command_buffer` and `begin_overlay`. Within one target later views
+    /// are drawn over earlier ones. The opaque batches of `shadow_batches` are drawn into every shadow map layer. The
+    /// instances of all views and shadows are uploaded together and each batch becomes one instanced draw.
+    /// Returns the instances the views drawn into the frame recorded, views without any pixels or past `MAX_VIEWS` are left out
+    pub fn draw_views(&mut self, image_index: usize, views: &[RenderView], shadow_batches: &[InstanceBatch]) -> RenderStats {
+        let extent = *self.target.extent();
+        let target_extent = |view: &RenderView| view.target.as_ref().map_or(extent, |target| *target.extent());
+        let mut views = views
+            .iter()
+            .filter(|view| {
+                let rect = view.viewport.rect(&target_extent(view));
+                rect.extent.width > 0 && rect.extent.height > 0
+            })
+            .collect::<Vec<_>>();
+        if views.len() > MAX_VIEWS {
+            warn!("Only drawing the first {} of {} views", MAX_VIEWS, views.len());
+            views.truncate(MAX_VIEWS);
+        }
+
+        // The first instance is the untransformed one the skybox and the debug lines are drawn with
+        let identity_instance = InstanceData {
+            model: uv::Mat4::identity(),
+            color: [1.0, 1.0, 1.0],
         };
+        let instances = std::iter::once(identity_instance)
+            .chain(
+                views
+                    .iter()
+                    .flat_map(|view| view.batches.iter())
+                    .chain(shadow_batches.iter())
+                    .flat_map(|batch| batch.instances.iter().copied()),
+            )
+            .collect::<Vec<InstanceData>>();
+        self.instance_buffers[image_index].update(&instances);
+        self.grid.update(image_index, &self.grid_settings);
+
+        let mut first_instances = Vec::with_capacity(views.len());
+        let mut first_instance = 1;
+        for view in &views {
+            first_instances.push(first_instance);
+            first_instance += view.batches.iter().map(|batch| batch.instances.len() as u32).sum::<u32>();
+        }
+
+        let mut stats = RenderStats {
+            drawn: 0,
+            culled: views.iter().map(|view| view.culled).sum(),
+        };
+
+        let command_buffer = self.command_buffers.get(image_index);
+        unsafe {
+            self.device
+                .vk()
+                .cmd_bind_vertex_buffers(*command_buffer, InstanceData::BINDING, &[*self.instance_buffers[image_index].vk()], &[0]);
+        }
+
+        for layer in 0..self.shadow_layers {
+            self.record_shadow(image_index, layer, shadow_batches, first_instance);
+        }
+
+        // Render textures are finished before the scene pass starts so the frame can sample them
+        let mut render_textures = Vec::<&Arc<RenderTexture>>::new();
+        for target in views.iter().filter_map(|view| view.target.as_ref()) {
+            if !render_textures.iter().any(|known| Arc::ptr_eq(known, target)) {
+                render_textures.push(target);
+            }
+        }
+        for render_texture in render_textures {
+            self.begin_scene_pass(image_index, *render_texture.render_pass().vk(), *render_texture.frame_buffer(), *render_texture.extent());
+            for (view_index, view) in views.iter().enumerate() {
+                if view.target.iter().any(|target| Arc::ptr_eq(target, render_texture)) {
+                    stats.drawn += self.record_view(image_index, view_index, view, first_instances[view_index], render_texture.render_pass(), render_texture.extent());
+                }
+            }
+            self.command_buffers.end_render_pass(image_index);
+        }
+
+        self.begin_scene_pass(image_index, *self.render_pass.vk(), self.frame_buffer.vk()[0], extent);
+        for (view_index, view) in views.iter().enumerate() {
+            if view.target.is_none() {
+                stats.drawn += self.record_view(image_index, view_index, view, first_instances[view_index], &self.render_pass, &extent);
+            }
+        }
+        stats
+    }
+
+    /// Draws the depth of the opaque batches from a light into one shadow map layer, in a pass of its own
+    fn record_shadow(&self, image_index: usize, layer: usize, batches: &[InstanceBatch], first_instance: u32) {
+        let extent = *self.shadow_map.extent();
+        let clear_values = [CLEAR_DEPTH];
+        let render_area = vk::Rect2D::builder().offset(vk::Offset2D { x: 0, y: 0 }).extent(extent).build();
 
         let render_pass_info = vk::RenderPassBeginInfo::builder()
-            .render_pass(*self.render_pass.vk())
-            .framebuffer(self.frame_buffers.vk()[image_index])
-            .render_area(vk::Rect2D::builder().offset(vk::Offset2D { x: 0, y: 0 }).extent(*self.swapchain.extent()).build())
-            .clear_values(&[clear_color])
+            .render_pass(*self.shadow_map.render_pass().vk())
+            .framebuffer(*self.shadow_map.frame_buffer(layer))
+            .render_area(render_area)
+            .clear_values(&clear_values)
             .build();
 
-        self.command_buffers.begin(image_index, &render_pass_info);
-        self.command_buffers.bind_pipeline(image_index, self.pipeline.vk());
-        self.command_buffers.set_scissor(image_index, self.swapchain.scissor());
-        self.command_buffers.set_viewport(image_index, self.swapchain.viewport());
+        self.command_buffers.begin_render_pass(image_index, &render_pass_info);
+        self.command_buffers.set_scissor(image_index, render_area);
+        self.command_buffers.set_viewport(
+            image_index,
+            vk::Viewport::builder().width(extent.width as f32).height(extent.height as f32).min_depth(0f32).max_depth(1f32).build(),
+        );
+
+        let set_index = image_index * MAX_SHADOW_MAPS + layer;
+        self.command_buffers.bind_pipeline(image_index, self.shadow_pipeline.vk());
         self.command_buffers
-            .bind_descriptor_sets(image_index, self.pipeline.get_layout(), &self.descriptor_set.vk()[image_index..=image_index]);
+            .bind_descriptor_sets(image_index, self.shadow_pipeline.get_layout(), 0, &self.shadow_sets.vk()[set_index..=set_index]);
+
+        let command_buffer = self.command_buffers.get(image_index);
+        let mut first_instance = first_instance;
+        for batch in batches {
+            let instance_count = batch.instances.len() as u32;
+            if batch.material.state().blend == BlendMode::Opaque {
+                batch.mesh.render(&self.device, command_buffer, first_instance, instance_count);
+            }
+            first_instance += instance_count;
+        }
+
+        self.command_buffers.end_render_pass(image_index);
+    }
+
+    fn begin_scene_pass(&self, image_index: usize, render_pass: vk::RenderPass, frame_buffer: vk::Framebuffer, extent: vk::Extent2D) {
+        let clear_values = [self.clear_color(), CLEAR_DEPTH];
+
+        let render_pass_info = vk::RenderPassBeginInfo::builder()
+            .render_pass(render_pass)
+            .framebuffer(frame_buffer)
+            .render_area(vk::Rect2D::builder().offset(vk::Offset2D { x: 0, y: 0 }).extent(extent).build())
+            .clear_values(&clear_values)
+            .build();
+
+        self.command_buffers.begin_render_pass(image_index, &render_pass_info);
+    }
+
+    /// Draws the background, the batches, the grid and the debug lines of a view into its rectangle of the current pass, using the uniform buffer
+    /// of `view_index`
+    ///
+    /// `render_pass` is the current pass, the materials are drawn with the pipelines matching its samples.
+    /// Returns the instances drawn, batches the pipeline can't draw are skipped
+    fn record_view(&self, image_index: usize, view_index: usize, view: &RenderView, first_instance: u32, render_pass: &Arc<RenderPass>, extent: &vk::Extent2D) -> usize {
+        let set_index = image_index * MAX_VIEWS + view_index;
+        let camera_pos = view.view.inversed().cols[3];
+        self.uniform_buffers[set_index].update2::<f32, _>(&[UniformTestObject {
+            view: view.view,
+            proj: view.proj,
+            camera_pos,
+        }]);
+
+        let rect = view.viewport.rect(extent);
+        self.command_buffers.set_scissor(image_index, rect);
+        self.command_buffers.set_viewport(
+            image_index,
+            vk::Viewport::builder()
+                .x(rect.offset.x as f32)
+                .y(rect.offset.y as f32)
+                .width(rect.extent.width as f32)
+                .height(rect.extent.height as f32)
+                .min_depth(0f32)
+                .max_depth(1f32)
+                .build(),
+        );
+        self.clear_rect(image_index, rect);
+
+        // Batches come sorted by shaders, render state and material so each pipeline and material is only bound when it changes
+        let command_buffer = self.command_buffers.get(image_index);
+        let mut pipeline_cache = self.pipeline_cache.lock().unwrap();
+        let mut bound_pipeline: Option<Arc<Pipeline>> = None;
+        let mut bound_material: Option<&Arc<Material>> = None;
+
+        if let Some(material) = &self.background_material {
+            let pipeline = pipeline_cache.get(material.shaders(), material.state(), render_pass);
+            self.command_buffers.bind_pipeline(image_index, pipeline.vk());
+            self.command_buffers
+                .bind_descriptor_sets(image_index, pipeline.get_layout(), 0, &self.descriptor_set.vk()[set_index..=set_index]);
+            self.command_buffers.bind_descriptor_sets(image_index, pipeline.get_layout(), 1, &[*material.descriptor_set()]);
+            self.skybox_mesh.render(&self.device, command_buffer, 0, 1);
+            bound_pipeline = Some(pipeline);
+            bound_material = Some(material);
+        }
+
+        let mut first_instance = first_instance;
+        let mut drawn = 0;
+        for batch in &view.batches {
+            let instance_count = batch.instances.len() as u32;
+            let pipeline = match self.view_mode.replace(batch.material.shaders(), batch.material.state()) {
+                Some((shaders, state)) => pipeline_cache.get(&shaders, &state, render_pass),
+                None => pipeline_cache.get(batch.material.shaders(), batch.material.state(), render_pass),
+            };
+            if !pipeline.accepts(batch.mesh.attributes()) {
+                let pair = (Arc::as_ptr(&batch.mesh) as usize, Arc::as_ptr(&pipeline) as usize);
+                if self.skipped_meshes.lock().unwrap().insert(pair) {
+                    let missing = pipeline.required_attributes().without(batch.mesh.attributes());
+                    warn!("Skipping mesh missing the vertex attributes needed by its pipeline: {}", missing);
+                }
+                first_instance += instance_count;
+                continue;
+            }
+
+            if !matches!(&bound_pipeline, Some(bound) if Arc::ptr_eq(bound, &pipeline)) {
+                self.command_buffers.bind_pipeline(image_index, pipeline.vk());
+                self.command_buffers
+                    .bind_descriptor_sets(image_index, pipeline.get_layout(), 0, &self.descriptor_set.vk()[set_index..=set_index]);
+                bound_pipeline = Some(pipeline.clone());
+                bound_material = None;
+            }
+            if !matches!(bound_material, Some(bound) if Arc::ptr_eq(bound, &batch.material)) {
+                self.command_buffers.bind_descriptor_sets(image_index, pipeline.get_layout(), 1, &[*batch.material.descriptor_set()]);
+                bound_material = Some(&batch.material);
+            }
+
+            batch.mesh.render(&self.device, command_buffer, first_instance, instance_count);
+            first_instance += instance_count;
+            drawn += batch.instances.len();
+        }
+
+        if self.grid_settings.enabled {
+            let pipeline = self.grid.pipeline(render_pass);
+            self.command_buffers.bind_pipeline(image_index, pipeline.vk());
+            self.command_buffers
+                .bind_descriptor_sets(image_index, pipeline.get_layout(), 0, &self.descriptor_set.vk()[set_index..=set_index]);
+            self.command_buffers.bind_descriptor_sets(image_index, pipeline.get_layout(), 1, &[self.grid.set(image_index)]);
+            unsafe { self.device.vk().cmd_draw(*command_buffer, 3, 1, 0, 0) };
+        }
+
+        let line_buffer = &self.line_buffers[image_index];
+        if line_buffer.vertex_count() > 0 {
+            let pipeline = pipeline_cache.get(self.line_material.shaders(), self.line_material.state(), render_pass);
+            self.command_buffers.bind_pipeline(image_index, pipeline.vk());
+            self.command_buffers
+                .bind_descriptor_sets(image_index, pipeline.get_layout(), 0, &self.descriptor_set.vk()[set_index..=set_index]);
+            self.command_buffers
+                .bind_descriptor_sets(image_index, pipeline.get_layout(), 1, &[*self.line_material.descriptor_set()]);
+            unsafe {
+                self.device.vk().cmd_bind_vertex_buffers(*command_buffer, 0, &[*line_buffer.vk()], &[0]);
+                self.device.vk().cmd_draw(*command_buffer, line_buffer.vertex_count(), 1, 0, 0);
+            }
+        }
+        drawn
+    }
+
+    /// Resets the color and depth of a view's rectangle so it isn't mixed with views drawn before it
+    fn clear_rect(&self, image_index: usize, rect: vk::Rect2D) {
+        let attachments = [
+            vk::ClearAttachment {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                color_attachment: 0,
+                clear_value: self.clear_color(),
+            },
+            vk::ClearAttachment {
+                aspect_mask: vk::ImageAspectFlags::DEPTH,
+                color_attachment: 0,
+                clear_value: CLEAR_DEPTH,
+            },
+        ];
+        let rects = [vk::ClearRect {
+            rect,
+            base_array_layer: 0,
+            layer_count: 1,
+        }];
+
+        self.command_buffers.clear_attachments(image_index, &attachments, &rects);
     }
 
     pub fn get_command_buffer(&self, image_index: usize) -> &vk::CommandBuffer {
@@ -170,16 +855,26 @@ impl GraphicContext {
     }
 
     pub fn end_command_buffer(&self, image_index: usize) {
+        self.command_buffers.end_render_pass(image_index);
         self.command_buffers.end(image_index);
     }
 
     pub fn submit_queue(&self, image_index: usize) {
-        let submit_info = vk::SubmitInfo::builder()
-            .wait_semaphores(&[*self.sync_objects.get_image_semaphore()])
-            .wait_dst_stage_mask(&[vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT])
-            .command_buffers(&[*self.command_buffers.get(image_index)])
-            .signal_semaphores(&[*self.sync_objects.get_render_semaphore()])
-            .build();
+        let wait_semaphores = [*self.sync_objects.get_image_semaphore()];
+        let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+        let command_buffers = [*self.command_buffers.get(image_index)];
+        let signal_semaphores = [*self.sync_objects.get_render_semaphore()];
+
+        // Offscreen images are never acquired or presented so there is nothing to wait on or signal
+        let submit_info = match self.target {
+            RenderTarget::Swapchain(_) => vk::SubmitInfo::builder()
+                .wait_semaphores(&wait_semaphores)
+                .wait_dst_stage_mask(&wait_stages)
+                .command_buffers(&command_buffers)
+                .signal_semaphores(&signal_semaphores)
+                .build(),
+            RenderTarget::Offscreen(_) => vk::SubmitInfo::builder().command_buffers(&command_buffers).build(),
+        };
 
         unsafe {
             self.device.vk().reset_fences(&[self.sync_objects.get_flight_fence()]).unwrap();
@@ -191,18 +886,23 @@ impl GraphicContext {
     }
 
     pub fn present_queue(&self, image_index: u32) -> Result<(), &'static str> {
+        let (swapchain, window) = match (&self.target, &self.window) {
+            (RenderTarget::Swapchain(swapchain), Some(window)) => (swapchain, window),
+            _ => return Ok(()),
+        };
+
         let present_info = vk::PresentInfoKHR::builder()
             .wait_semaphores(&[*self.sync_objects.get_render_semaphore()])
-            .swapchains(&[*self.swapchain.vk()])
+            .swapchains(&[*swapchain.vk()])
             .image_indices(&[image_index])
             .build();
 
         unsafe {
-            let result = self.swapchain.get_loader().queue_present(*self.device.present_queue(), &present_info);
+            let result = swapchain.get_loader().queue_present(*self.device.present_queue(), &present_info);
             match result {
                 Ok(true) | Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => return Err("Out of date swapchain"),
                 Ok(false) => {
-                    if self.window.has_window_resized() {
+                    if window.has_window_resized() {
                         return Err("Wrong size swapchain");
                     }
                 }
@@ -213,28 +913,82 @@ impl GraphicContext {
         Ok(())
     }
 
-    pub fn update_uniforms(&self, image_index: usize, camera_pos: &uv::Vec3, camera_dir: &uv::Vec3, camera_up: &uv::Vec3) {
-        let time = Instant::now().duration_since(self.start_time).as_millis();
-
-        let aspect = self.swapchain.extent().width as f32 / self.swapchain.extent().height as f32;
+    /// Copies the last rendered offscreen image back to the host as tightly packed RGBA8 rows
+    pub fn read_frame(&self) -> Option<Vec<u8>> {
+        let image = match &self.target {
+            RenderTarget::Offscreen(image) => image,
+            RenderTarget::Swapchain(_) => return None,
+        };
 
-        // let model = glm::rotate(&glm::Mat4::identity(), (time as f32 * 0.180).to_radians(), &glm::Vec3::new(0.0, 0.0, 1.0));
-        let model = uv::Mat4::identity();
+        self.wait_device();
 
-        let view = uv::Mat4::look_at(*camera_pos, *camera_pos + *camera_dir, *camera_up);
+        let extent = *image.extent();
+        let buffer_size = (extent.width * extent.height * 4) as vk::DeviceSize;
+        let staging_buffer = Buffer::new(
+            buffer_size,
+            vk::BufferUsageFlags::TRANSFER_DST,
+            vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            self.device.clone(),
+        );
 
-        let mut proj = uv::projection::perspective_gl(45f32.to_radians(), aspect, 0.1, 10.0);
-        proj[1][1] *= -1.0;
+        let region = vk::BufferImageCopy::builder()
+            .buffer_offset(0)
+            .buffer_row_length(0)
+            .buffer_image_height(0)
+            .image_subresource(
+                vk::ImageSubresourceLayers::builder()
+                    .aspect_mask(vk::ImageAspectFlags::COLOR)
+                    .mip_level(0)
+                    .base_array_layer(0)
+                    .layer_count(1)
+                    .build(),
+            )
+            .image_offset(vk::Offset3D { x: 0, y: 0, z: 0 })
+            .image_extent(vk::Extent3D {
+                width: extent.width,
+                height: extent.height,
+                depth: 1,
+            })
+            .build();
 
-        let ubo = UniformTestObject { model, view, proj };
-        let ubos = [ubo];
+        submit_single_time_commands(&self.device, |command_buffer| unsafe {
+            self.device
+                .vk()
+                .cmd_copy_image_to_buffer(command_buffer, *image.vk(), vk::ImageLayout::TRANSFER_SRC_OPTIMAL, *staging_buffer.vk(), &[region]);
+        });
 
-        self.uniform_buffers[image_index].update2::<f32, _>(&ubos);
+        Some(staging_buffer.read_memory(buffer_size))
     }
 
     pub fn create_mesh_factory(&self) -> MeshFactory {
         MeshFactory::new(self.device.clone())
     }
+
+    pub fn create_texture_factory(&self) -> TextureFactory {
+        TextureFactory::new(self.device.clone())
+    }
+
+    pub fn create_material_factory(&self) -> MaterialFactory {
+        MaterialFactory::new(self.device.clone(), self.material_layout.clone(), self.default_textures.clone())
+    }
+
+    pub fn create_environment_factory(&self) -> EnvironmentFactory {
+        EnvironmentFactory::new(self.device.clone())
+    }
+
+    pub fn create_skybox_factory(&self) -> SkyboxFactory {
+        SkyboxFactory::new(self.device.clone())
+    }
+
+    /// Material of renderables which don't set one, the default shaders and render state without a texture
+    pub fn default_material(&self) -> &Arc<Material> {
+        &self.default_material
+    }
+
+    /// Render textures use the scene pass' formats so the scene pipelines can draw into them
+    pub fn create_render_texture_factory(&self) -> RenderTextureFactory {
+        RenderTextureFactory::new(self.device.clone(), HDR_FORMAT, self.depth_image.format())
+    }
 }
 
 impl RendererVkContext for GraphicContext {
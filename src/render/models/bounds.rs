@@ -0,0 +1,40 @@
+use super::Vertex;
+
+/// Volume around a mesh's vertices, both as a box and as a sphere around the box's center
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds {
+    pub min: uv::Vec3,
+    pub max: uv::Vec3,
+    pub center: uv::Vec3,
+    pub radius: f32,
+}
+
+impl Bounds {
+    /// Empty meshes get an empty volume at the origin
+    pub fn from_vertices(vertices: &[Vertex]) -> Bounds {
+        if vertices.is_empty() {
+            return Bounds {
+                min: uv::Vec3::zero(),
+                max: uv::Vec3::zero(),
+                center: uv::Vec3::zero(),
+                radius: 0.0,
+            };
+        }
+
+        let positions = vertices.iter().map(|vertex| uv::Vec3::from(vertex.pos));
+        let (min, max) = positions.clone().fold((uv::Vec3::broadcast(f32::MAX), uv::Vec3::broadcast(f32::MIN)), |(min, max), pos| {
+            (min.min_by_component(pos), max.max_by_component(pos))
+        });
+        let center = (min + max) * 0.5;
+        // Tighter than half the box's diagonal whenever the vertices don't reach the box's corners
+        let radius = positions.map(|pos| (pos - center).mag()).fold(0.0, f32::max);
+
+        Bounds { min, max, center, radius }
+    }
+
+    /// Sphere around the mesh once placed by `model`, grown by its largest scale so it still encloses the mesh
+    pub fn sphere(&self, model: &uv::Mat4) -> (uv::Vec3, f32) {
+        let scale = (0..3).map(|axis| model.cols[axis].xyz().mag()).fold(0.0, f32::max);
+        (model.transform_point3(self.center), self.radius * scale)
+    }
+}
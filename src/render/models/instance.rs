@@ -0,0 +1,95 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ash::vk;
+
+use memoffset::offset_of;
+
+use super::Mesh;
+use crate::render::materials::{BlendMode, Material};
+
+/// Per instance vertex input, read from binding 1 starting after the `Vertex` locations
+#[repr(C)]
+#[derive(Clone, Debug, Copy)]
+pub struct InstanceData {
+    pub model: uv::Mat4,
+    pub color: [f32; 3],
+}
+
+impl InstanceData {
+    pub const BINDING: u32 = 1;
+    const FIRST_LOCATION: u32 = 5;
+
+    pub fn get_binding_description() -> vk::VertexInputBindingDescription {
+        vk::VertexInputBindingDescription::builder()
+            .binding(Self::BINDING)
+            .stride(std::mem::size_of::<Self>() as u32)
+            .input_rate(vk::VertexInputRate::INSTANCE)
+            .build()
+    }
+
+    /// The model matrix takes one location per column
+    pub fn get_attribute_descriptions() -> Vec<vk::VertexInputAttributeDescription> {
+        let column_size = std::mem::size_of::<uv::Vec4>() as u32;
+        let mut descriptions = (0..4)
+            .map(|column| {
+                vk::VertexInputAttributeDescription::builder()
+                    .binding(Self::BINDING)
+                    .location(Self::FIRST_LOCATION + column)
+                    .format(vk::Format::R32G32B32A32_SFLOAT)
+                    .offset(offset_of!(Self, model) as u32 + column * column_size)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        descriptions.push(
+            vk::VertexInputAttributeDescription::builder()
+                .binding(Self::BINDING)
+                .location(Self::FIRST_LOCATION + 4)
+                .format(vk::Format::R32G32B32_SFLOAT)
+                .offset(offset_of!(Self, color) as u32)
+                .build(),
+        );
+        descriptions
+    }
+}
+
+/// Every instance of one mesh and material drawn this frame, recorded as a single instanced draw
+pub struct InstanceBatch {
+    pub mesh: Arc<Mesh>,
+    pub material: Arc<Material>,
+    pub instances: Vec<InstanceData>,
+}
+
+impl InstanceBatch {
+    /// Groups instances by the mesh and material they share
+    ///
+    /// Batches are sorted so those sharing shaders and render state, and so a pipeline, and then a material are
+    /// consecutive, blended materials last.
+    pub fn group<'a, I: IntoIterator<Item = (&'a Arc<Mesh>, &'a Arc<Material>, InstanceData)>>(instances: I) -> Vec<InstanceBatch> {
+        let mut batches = Vec::<InstanceBatch>::new();
+        let mut batch_indices = HashMap::<(*const Mesh, *const Material), usize>::new();
+
+        for (mesh, material, instance) in instances {
+            let index = *batch_indices.entry((Arc::as_ptr(mesh), Arc::as_ptr(material))).or_insert_with(|| {
+                batches.push(InstanceBatch {
+                    mesh: mesh.clone(),
+                    material: material.clone(),
+                    instances: Vec::new(),
+                });
+                batches.len() - 1
+            });
+            batches[index].instances.push(instance);
+        }
+
+        let sort_key = |batch: &InstanceBatch| {
+            (
+                batch.material.state().blend != BlendMode::Opaque,
+                batch.material.shaders().clone(),
+                *batch.material.state(),
+                Arc::as_ptr(&batch.material) as usize,
+            )
+        };
+        batches.sort_by_cached_key(sort_key);
+        batches
+    }
+}
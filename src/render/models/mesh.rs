@@ -1,6 +1,6 @@
-use std::sync::Arc;
+use std::{path::Path, sync::Arc};
 
-use super::Vertex;
+use super::{Bounds, ObjError, ObjModel, Vertex, VertexAttributes};
 use crate::render::{
     buffers::{IndexBuffer, VertexBuffer},
     device::Device,
@@ -9,33 +9,69 @@ use crate::render::{
 
 use ash::{version::DeviceV1_0, vk};
 
+/// Vertex data of a mesh which hasn't been uploaded to the GPU yet
+#[derive(Debug)]
+pub struct MeshData {
+    pub vertices: Vec<Vertex>,
+    pub indices: Option<Vec<u16>>,
+    pub attributes: VertexAttributes,
+}
+
+impl From<ObjModel> for MeshData {
+    fn from(model: ObjModel) -> Self {
+        MeshData {
+            vertices: model.vertices,
+            indices: Some(model.indices),
+            attributes: model.attributes,
+        }
+    }
+}
+
 pub struct Mesh {
     vertex_buffer: VertexBuffer,
     index_buffer: Option<IndexBuffer>,
+    attributes: VertexAttributes,
+    bounds: Bounds,
 }
 
 impl Mesh {
-    pub fn new(vertices: &[Vertex], indices: Option<&[u16]>, device: &Arc<Device>) -> Mesh {
+    pub fn new(vertices: &[Vertex], indices: Option<&[u16]>, attributes: VertexAttributes, device: &Arc<Device>) -> Mesh {
         let vertex_buffer = VertexBuffer::new(vertices, device);
         let index_buffer = match indices {
             Some(indices) => Some(IndexBuffer::new(indices, device)),
             None => None,
         };
 
-        Mesh { vertex_buffer, index_buffer }
+        Mesh {
+            vertex_buffer,
+            index_buffer,
+            attributes,
+            bounds: Bounds::from_vertices(vertices),
+        }
     }
 
-    pub fn render(&self, device: &Arc<Device>, command_buffer: &vk::CommandBuffer) {
+    /// Attributes whose values were provided, the rest of each vertex holds defaults
+    pub fn attributes(&self) -> VertexAttributes {
+        self.attributes
+    }
+
+    /// Volume around the vertices in the mesh's local space, computed when it was created
+    pub fn bounds(&self) -> &Bounds {
+        &self.bounds
+    }
+
+    /// Draws `instance_count` instances, whose data has to be bound at `InstanceData::BINDING` starting from `first_instance`
+    pub fn render(&self, device: &Arc<Device>, command_buffer: &vk::CommandBuffer, first_instance: u32, instance_count: u32) {
         let vertex_buffers = [*self.vertex_buffer.vk()];
         let offsets = [0];
         unsafe {
             if let Some(index_buffer) = &self.index_buffer {
                 device.vk().cmd_bind_vertex_buffers(*command_buffer, 0, &vertex_buffers, &offsets);
                 device.vk().cmd_bind_index_buffer(*command_buffer, *index_buffer.vk(), 0, vk::IndexType::UINT16);
-                device.vk().cmd_draw_indexed(*command_buffer, index_buffer.index_count(), 1, 0, 0, 0);
+                device.vk().cmd_draw_indexed(*command_buffer, index_buffer.index_count(), instance_count, 0, 0, first_instance);
             } else {
                 device.vk().cmd_bind_vertex_buffers(*command_buffer, 0, &vertex_buffers, &offsets);
-                device.vk().cmd_draw(*command_buffer, self.vertex_buffer.vertex_count(), 1, 0, 0);
+                device.vk().cmd_draw(*command_buffer, self.vertex_buffer.vertex_count(), instance_count, 0, first_instance);
             }
         }
     }
@@ -56,7 +92,15 @@ impl MeshFactory {
         MeshFactory { device }
     }
 
-    pub fn create_mesh(&self, vertices: &[Vertex], indices: Option<&[u16]>) -> Mesh {
-        Mesh::new(vertices, indices, &self.device)
+    pub fn create_mesh(&self, vertices: &[Vertex], indices: Option<&[u16]>, attributes: VertexAttributes) -> Mesh {
+        Mesh::new(vertices, indices, attributes, &self.device)
+    }
+
+    pub fn upload(&self, data: &MeshData) -> Mesh {
+        self.create_mesh(&data.vertices, data.indices.as_deref(), data.attributes)
+    }
+
+    pub fn load_obj<P: AsRef<Path>>(&self, path: P) -> Result<Mesh, ObjError> {
+        Ok(self.upload(&ObjModel::load(path)?.into()))
     }
 }
@@ -1,5 +1,13 @@
+mod bounds;
+mod instance;
 mod mesh;
+mod obj;
+mod scene;
 mod vertex;
 
-pub use mesh::{Mesh, MeshFactory};
-pub use vertex::Vertex;
+pub use bounds::Bounds;
+pub use instance::{InstanceBatch, InstanceData};
+pub use mesh::{Mesh, MeshData, MeshFactory};
+pub use obj::{ObjError, ObjModel};
+pub use scene::{SceneData, SceneError, SceneNode};
+pub use vertex::{Vertex, VertexAttributes};
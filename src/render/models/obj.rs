@@ -0,0 +1,332 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::{Vertex, VertexAttributes};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io { path: PathBuf, source: io::Error },
+    Parse { path: PathBuf, line: usize, message: String },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ObjError::Parse { path, line, message } => write!(f, "{}:{}: {}", path.display(), line, message),
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObjError::Io { source, .. } => Some(source),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+/// Triangulated contents of a Wavefront `.obj` file with its vertices de-duplicated
+#[derive(Debug)]
+pub struct ObjModel {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u16>,
+    pub attributes: VertexAttributes,
+}
+
+#[derive(Copy, Clone)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+struct Face {
+    corners: Vec<Corner>,
+    /// Smoothing group, 0 when smoothing is off and the face gets a flat normal
+    smoothing_group: u32,
+    color: Option<[f32; 3]>,
+    line: usize,
+}
+
+struct Parser<'a> {
+    path: &'a Path,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: String) -> Result<T, ObjError> {
+        Err(ObjError::Parse {
+            path: self.path.to_path_buf(),
+            line: self.line,
+            message,
+        })
+    }
+
+    fn float<'s>(&self, keyword: &str, args: &mut impl Iterator<Item = &'s str>) -> Result<f32, ObjError> {
+        match args.next().map(str::parse::<f32>) {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(err)) => self.error(format!("invalid number in `{}`: {}", keyword, err)),
+            None => self.error(format!("`{}` is missing a number", keyword)),
+        }
+    }
+
+    fn vec2<'s>(&self, keyword: &str, args: &mut impl Iterator<Item = &'s str>) -> Result<[f32; 2], ObjError> {
+        Ok([self.float(keyword, args)?, self.float(keyword, args)?])
+    }
+
+    fn vec3<'s>(&self, keyword: &str, args: &mut impl Iterator<Item = &'s str>) -> Result<[f32; 3], ObjError> {
+        Ok([self.float(keyword, args)?, self.float(keyword, args)?, self.float(keyword, args)?])
+    }
+
+    /// Resolves a 1-based (or negative, relative to the end) OBJ index into `0..count`
+    fn index(&self, token: &str, count: usize) -> Result<usize, ObjError> {
+        let index = match token.parse::<i64>() {
+            Ok(index) => index,
+            Err(err) => return self.error(format!("invalid index `{}`: {}", token, err)),
+        };
+        let resolved = if index < 0 { count as i64 + index } else { index - 1 };
+        if index == 0 || resolved < 0 || resolved >= count as i64 {
+            return self.error(format!("index {} is out of range, {} elements are defined", index, count));
+        }
+        Ok(resolved as usize)
+    }
+}
+
+impl ObjModel {
+    /// Loads an `.obj` file along with the `.mtl` libraries it references
+    ///
+    /// Faces are triangulated as fans and missing normals are generated, flat for faces outside a
+    /// smoothing group and averaged per position inside one. Material diffuse colors and the
+    /// `v x y z r g b` extension both end up in the vertex color.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<ObjModel, ObjError> {
+        let path = path.as_ref();
+        let source = read_file(path)?;
+        let mut parser = Parser { path, line: 0 };
+
+        let mut positions = Vec::<[f32; 3]>::new();
+        let mut colors = Vec::<Option<[f32; 3]>>::new();
+        let mut uvs = Vec::<[f32; 2]>::new();
+        let mut normals = Vec::<[f32; 3]>::new();
+        let mut faces = Vec::<Face>::new();
+        let mut materials = HashMap::<String, [f32; 3]>::new();
+
+        let mut smoothing_group = 0;
+        let mut material_color = None;
+
+        for (number, line) in source.lines().enumerate() {
+            parser.line = number + 1;
+            let mut args = line.split_whitespace();
+            let keyword = match args.next() {
+                Some(keyword) if !keyword.starts_with('#') => keyword,
+                _ => continue,
+            };
+
+            match keyword {
+                "v" => {
+                    positions.push(parser.vec3("v", &mut args)?);
+                    // Anything but an optional `w` weight after the position is an RGB vertex color
+                    let rest = args.collect::<Vec<_>>();
+                    colors.push(if rest.len() >= 3 { Some(parser.vec3("v", &mut rest.into_iter())?) } else { None });
+                }
+                "vt" => {
+                    let [u, v] = parser.vec2("vt", &mut args)?;
+                    // OBJ puts the texture origin at the bottom left, Vulkan at the top left
+                    uvs.push([u, 1.0 - v]);
+                }
+                "vn" => normals.push(parser.vec3("vn", &mut args)?),
+                "f" => {
+                    let mut corners = Vec::new();
+                    for token in args {
+                        let mut parts = token.split('/');
+                        let position = parser.index(parts.next().unwrap_or_default(), positions.len())?;
+                        let uv = match parts.next() {
+                            Some(part) if !part.is_empty() => Some(parser.index(part, uvs.len())?),
+                            _ => None,
+                        };
+                        let normal = match parts.next() {
+                            Some(part) if !part.is_empty() => Some(parser.index(part, normals.len())?),
+                            _ => None,
+                        };
+                        corners.push(Corner { position, uv, normal });
+                    }
+                    if corners.len() < 3 {
+                        return parser.error(format!("face has {} vertices, at least 3 are needed", corners.len()));
+                    }
+                    faces.push(Face {
+                        corners,
+                        smoothing_group,
+                        color: material_color,
+                        line: parser.line,
+                    });
+                }
+                "s" => {
+                    smoothing_group = match args.next() {
+                        Some("off") => 0,
+                        Some("on") => 1,
+                        Some(group) => match group.parse::<u32>() {
+                            Ok(group) => group,
+                            Err(err) => return parser.error(format!("invalid smoothing group `{}`: {}", group, err)),
+                        },
+                        None => return parser.error("`s` expects a smoothing group".to_string()),
+                    };
+                }
+                "mtllib" => {
+                    for library in args {
+                        let library_path = path.parent().unwrap_or_else(|| Path::new("")).join(library);
+                        materials.extend(load_materials(&library_path)?);
+                    }
+                }
+                "usemtl" => {
+                    let name = args.collect::<Vec<_>>().join(" ");
+                    material_color = materials.get(&name).copied();
+                    if material_color.is_none() {
+                        warn!("{}:{}: material `{}` is not defined, using the default color", path.display(), parser.line, name);
+                    }
+                }
+                // Groups, objects, lines and free-form geometry don't affect the triangle mesh
+                _ => (),
+            }
+        }
+
+        let has_uvs = faces.iter().flat_map(|face| face.corners.iter()).any(|corner| corner.uv.is_some());
+
+        // Area weighted sums of the face normals around each position of a smoothing group
+        let mut smooth_normals = HashMap::<(u32, usize), [f32; 3]>::new();
+        let face_normals = faces.iter().map(|face| face_normal(face, &positions)).collect::<Vec<_>>();
+        for (face, normal) in faces.iter().zip(&face_normals) {
+            if face.smoothing_group != 0 {
+                for corner in &face.corners {
+                    let sum = smooth_normals.entry((face.smoothing_group, corner.position)).or_insert([0.0; 3]);
+                    for axis in 0..3 {
+                        sum[axis] += normal[axis];
+                    }
+                }
+            }
+        }
+
+        let mut model = ObjModel {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            attributes: VertexAttributes::POSITION | VertexAttributes::NORMAL | VertexAttributes::COLOR,
+        };
+        if has_uvs {
+            model.attributes = model.attributes | VertexAttributes::UV;
+        }
+
+        let mut unique_vertices = HashMap::<[u32; 15], u16>::new();
+        for (face, face_normal) in faces.iter().zip(&face_normals) {
+            for corner_index in 1..face.corners.len() - 1 {
+                for corner in [face.corners[0], face.corners[corner_index], face.corners[corner_index + 1]].iter() {
+                    let normal = match corner.normal {
+                        Some(normal) => normals[normal],
+                        None if face.smoothing_group != 0 => smooth_normals[&(face.smoothing_group, corner.position)],
+                        None => *face_normal,
+                    };
+                    let vertex = Vertex {
+                        pos: positions[corner.position],
+                        normal: normalize(normal),
+                        uv: corner.uv.map_or([0.0, 0.0], |uv| uvs[uv]),
+                        color: colors[corner.position].or(face.color).unwrap_or([1.0, 1.0, 1.0]),
+                        ..Vertex::default()
+                    };
+
+                    let next_index = model.vertices.len();
+                    let index = match unique_vertices.get(&vertex_key(&vertex)) {
+                        Some(index) => *index,
+                        None if next_index > u16::MAX as usize => {
+                            parser.line = face.line;
+                            return parser.error(format!("model has more than {} unique vertices", u16::MAX as usize + 1));
+                        }
+                        None => {
+                            unique_vertices.insert(vertex_key(&vertex), next_index as u16);
+                            model.vertices.push(vertex);
+                            next_index as u16
+                        }
+                    };
+                    model.indices.push(index);
+                }
+            }
+        }
+
+        Ok(model)
+    }
+}
+
+/// Reads the diffuse color of every material in an `.mtl` library
+fn load_materials(path: &Path) -> Result<HashMap<String, [f32; 3]>, ObjError> {
+    let source = read_file(path)?;
+    let mut parser = Parser { path, line: 0 };
+
+    let mut materials = HashMap::new();
+    let mut current = None;
+
+    for (number, line) in source.lines().enumerate() {
+        parser.line = number + 1;
+        let mut args = line.split_whitespace();
+        match args.next() {
+            Some("newmtl") => {
+                let name = args.collect::<Vec<_>>().join(" ");
+                if name.is_empty() {
+                    return parser.error("`newmtl` expects a material name".to_string());
+                }
+                materials.insert(name.clone(), [1.0, 1.0, 1.0]);
+                current = Some(name);
+            }
+            Some("Kd") => {
+                let color = parser.vec3("Kd", &mut args)?;
+                match &current {
+                    Some(name) => {
+                        materials.insert(name.clone(), color);
+                    }
+                    None => return parser.error("`Kd` appears before any `newmtl`".to_string()),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(materials)
+}
+
+fn read_file(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|source| ObjError::Io { path: path.to_path_buf(), source })
+}
+
+/// Unnormalized so that larger faces weigh more when summed into smooth normals
+fn face_normal(face: &Face, positions: &[[f32; 3]]) -> [f32; 3] {
+    // Newell's method also handles non planar polygons
+    let mut normal = [0.0; 3];
+    for (i, corner) in face.corners.iter().enumerate() {
+        let current = positions[corner.position];
+        let next = positions[face.corners[(i + 1) % face.corners.len()].position];
+        normal[0] += (current[1] - next[1]) * (current[2] + next[2]);
+        normal[1] += (current[2] - next[2]) * (current[0] + next[0]);
+        normal[2] += (current[0] - next[0]) * (current[1] + next[1]);
+    }
+    normal.iter_mut().for_each(|axis| *axis *= 0.5);
+    normal
+}
+
+fn normalize(v: [f32; 3]) -> [f32; 3] {
+    let length = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+    if length > f32::EPSILON {
+        [v[0] / length, v[1] / length, v[2] / length]
+    } else {
+        Vertex::default().normal
+    }
+}
+
+fn vertex_key(vertex: &Vertex) -> [u32; 15] {
+    let mut key = [0; 15];
+    let fields = vertex.pos.iter().chain(&vertex.normal).chain(&vertex.uv).chain(&vertex.tangent).chain(&vertex.color);
+    for (bits, value) in key.iter_mut().zip(fields) {
+        *bits = value.to_bits();
+    }
+    key
+}
@@ -0,0 +1,151 @@
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use gltf::mesh::Mode;
+
+use super::{MeshData, Vertex, VertexAttributes};
+
+#[derive(Debug)]
+pub enum SceneError {
+    Import { path: PathBuf, source: gltf::Error },
+    Mesh { path: PathBuf, mesh: usize, message: String },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Import { path, source } => write!(f, "{}: {}", path.display(), source),
+            SceneError::Mesh { path, mesh, message } => write!(f, "{}: mesh {}: {}", path.display(), mesh, message),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Import { source, .. } => Some(source),
+            SceneError::Mesh { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SceneNode {
+    pub name: Option<String>,
+    /// Index of the parent in `SceneData::nodes`, which always comes before its children
+    pub parent: Option<usize>,
+    pub translation: uv::Vec3,
+    pub rotation: uv::Rotor3,
+    pub scale: uv::Vec3,
+    /// Index into `SceneData::meshes`
+    pub mesh: Option<usize>,
+}
+
+/// Node hierarchy and mesh data of a glTF scene, ready to be uploaded
+#[derive(Debug)]
+pub struct SceneData {
+    /// Triangle primitives of every mesh in the document
+    pub meshes: Vec<Vec<MeshData>>,
+    pub nodes: Vec<SceneNode>,
+}
+
+impl SceneData {
+    /// Loads the default scene (or the first one) of a `.gltf` or `.glb` file
+    ///
+    /// Primitives other than triangle lists are skipped. Vertex colors are multiplied by the base
+    /// color of the primitive's material, which alone is used when the primitive has none.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SceneData, SceneError> {
+        let path = path.as_ref();
+        let import_error = |source| SceneError::Import { path: path.to_path_buf(), source };
+
+        let (document, buffers, _images) = gltf::import(path).map_err(import_error)?;
+
+        let mut meshes = Vec::new();
+        for mesh in document.meshes() {
+            let mut primitives = Vec::new();
+            for primitive in mesh.primitives() {
+                if primitive.mode() != Mode::Triangles {
+                    warn!("{}: skipping {:?} primitive of mesh {}, only triangles are supported", path.display(), primitive.mode(), mesh.index());
+                    continue;
+                }
+
+                let mesh_error = |message: &str| SceneError::Mesh {
+                    path: path.to_path_buf(),
+                    mesh: mesh.index(),
+                    message: message.to_string(),
+                };
+
+                let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &*data.0));
+                let mut attributes = VertexAttributes::POSITION | VertexAttributes::COLOR;
+                let mut vertices = match reader.read_positions() {
+                    Some(positions) => positions.map(|pos| Vertex { pos, ..Vertex::default() }).collect::<Vec<_>>(),
+                    None => return Err(mesh_error("primitive has no positions")),
+                };
+
+                if let Some(normals) = reader.read_normals() {
+                    attributes = attributes | VertexAttributes::NORMAL;
+                    vertices.iter_mut().zip(normals).for_each(|(vertex, normal)| vertex.normal = normal);
+                }
+                if let Some(uvs) = reader.read_tex_coords(0) {
+                    attributes = attributes | VertexAttributes::UV;
+                    vertices.iter_mut().zip(uvs.into_f32()).for_each(|(vertex, uv)| vertex.uv = uv);
+                }
+                if let Some(tangents) = reader.read_tangents() {
+                    attributes = attributes | VertexAttributes::TANGENT;
+                    vertices.iter_mut().zip(tangents).for_each(|(vertex, tangent)| vertex.tangent = tangent);
+                }
+                if let Some(colors) = reader.read_colors(0) {
+                    vertices.iter_mut().zip(colors.into_rgb_f32()).for_each(|(vertex, color)| vertex.color = color);
+                }
+
+                let [r, g, b, _] = primitive.material().pbr_metallic_roughness().base_color_factor();
+                for vertex in &mut vertices {
+                    vertex.color = [vertex.color[0] * r, vertex.color[1] * g, vertex.color[2] * b];
+                }
+
+                let indices = match reader.read_indices() {
+                    Some(indices) => Some(
+                        indices
+                            .into_u32()
+                            .map(u16::try_from)
+                            .collect::<Result<Vec<_>, _>>()
+                            .map_err(|_| mesh_error("indices don't fit into 16 bits"))?,
+                    ),
+                    None => None,
+                };
+
+                primitives.push(MeshData { vertices, indices, attributes });
+            }
+            meshes.push(primitives);
+        }
+
+        let mut nodes = Vec::new();
+        if let Some(scene) = document.default_scene().or_else(|| document.scenes().next()) {
+            let mut stack = scene.nodes().map(|node| (node, None)).collect::<Vec<_>>();
+            stack.reverse();
+            while let Some((node, parent)) = stack.pop() {
+                let (translation, [x, y, z, w], scale) = node.transform().decomposed();
+                nodes.push(SceneNode {
+                    name: node.name().map(str::to_string),
+                    parent,
+                    translation: translation.into(),
+                    // A quaternion's imaginary parts map onto the bivector planes orthogonal to their axes
+                    rotation: uv::Rotor3::new(w, uv::Bivec3::new(-z, y, -x)),
+                    scale: scale.into(),
+                    mesh: node.mesh().map(|mesh| mesh.index()),
+                });
+
+                let index = nodes.len() - 1;
+                let first_child = stack.len();
+                stack.extend(node.children().map(|child| (child, Some(index))));
+                stack[first_child..].reverse();
+            }
+        }
+
+        Ok(SceneData { meshes, nodes })
+    }
+}
@@ -2,13 +2,86 @@ use ash::vk;
 
 use memoffset::offset_of;
 
+use std::{fmt, ops::BitOr};
+
 #[repr(C)]
 #[derive(Clone, Debug, Copy)]
 pub struct Vertex {
-    pub pos: [f32; 2],
+    pub pos: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+    /// Tangent direction with the bitangent handedness in `w`
+    pub tangent: [f32; 4],
     pub color: [f32; 3],
 }
 
+impl Default for Vertex {
+    fn default() -> Self {
+        Vertex {
+            pos: [0.0, 0.0, 0.0],
+            normal: [0.0, 0.0, 1.0],
+            uv: [0.0, 0.0],
+            tangent: [1.0, 0.0, 0.0, 1.0],
+            color: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+/// Set of vertex attributes a mesh provides or a pipeline consumes
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexAttributes(u32);
+
+impl VertexAttributes {
+    pub const POSITION: VertexAttributes = VertexAttributes(1);
+    pub const NORMAL: VertexAttributes = VertexAttributes(1 << 1);
+    pub const UV: VertexAttributes = VertexAttributes(1 << 2);
+    pub const TANGENT: VertexAttributes = VertexAttributes(1 << 3);
+    pub const COLOR: VertexAttributes = VertexAttributes(1 << 4);
+
+    pub fn none() -> VertexAttributes {
+        VertexAttributes(0)
+    }
+
+    pub fn all() -> VertexAttributes {
+        Self::POSITION | Self::NORMAL | Self::UV | Self::TANGENT | Self::COLOR
+    }
+
+    pub fn contains(self, other: VertexAttributes) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Attributes of `self` which aren't in `other`
+    pub fn without(self, other: VertexAttributes) -> VertexAttributes {
+        VertexAttributes(self.0 & !other.0)
+    }
+}
+
+impl fmt::Display for VertexAttributes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (VertexAttributes::POSITION, "position"),
+            (VertexAttributes::NORMAL, "normal"),
+            (VertexAttributes::UV, "uv"),
+            (VertexAttributes::TANGENT, "tangent"),
+            (VertexAttributes::COLOR, "color"),
+        ];
+        let present = names.iter().filter(|(attribute, _)| self.contains(*attribute)).map(|(_, name)| *name).collect::<Vec<_>>();
+        if present.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", present.join(", "))
+        }
+    }
+}
+
+impl BitOr for VertexAttributes {
+    type Output = VertexAttributes;
+
+    fn bitor(self, rhs: VertexAttributes) -> VertexAttributes {
+        VertexAttributes(self.0 | rhs.0)
+    }
+}
+
 impl Vertex {
     pub fn get_binding_description() -> vk::VertexInputBindingDescription {
         vk::VertexInputBindingDescription::builder()
@@ -18,20 +91,26 @@ impl Vertex {
             .build()
     }
 
-    pub fn get_attribute_descriptions() -> [vk::VertexInputAttributeDescription; 2] {
-        [
-            vk::VertexInputAttributeDescription::builder()
-                .binding(0)
-                .location(0)
-                .format(vk::Format::R32G32_SFLOAT)
-                .offset(offset_of!(Self, pos) as u32)
-                .build(),
-            vk::VertexInputAttributeDescription::builder()
-                .binding(0)
-                .location(1)
-                .format(vk::Format::R32G32B32_SFLOAT)
-                .offset(offset_of!(Self, color) as u32)
-                .build(),
-        ]
+    /// Descriptions for the requested attributes, each attribute keeps a fixed shader location whether or not the others are present
+    pub fn get_attribute_descriptions(attributes: VertexAttributes) -> Vec<vk::VertexInputAttributeDescription> {
+        let all = [
+            (VertexAttributes::POSITION, 0, vk::Format::R32G32B32_SFLOAT, offset_of!(Self, pos)),
+            (VertexAttributes::NORMAL, 1, vk::Format::R32G32B32_SFLOAT, offset_of!(Self, normal)),
+            (VertexAttributes::UV, 2, vk::Format::R32G32_SFLOAT, offset_of!(Self, uv)),
+            (VertexAttributes::TANGENT, 3, vk::Format::R32G32B32A32_SFLOAT, offset_of!(Self, tangent)),
+            (VertexAttributes::COLOR, 4, vk::Format::R32G32B32_SFLOAT, offset_of!(Self, color)),
+        ];
+
+        all.iter()
+            .filter(|(attribute, ..)| attributes.contains(*attribute))
+            .map(|&(_, location, format, offset)| {
+                vk::VertexInputAttributeDescription::builder()
+                    .binding(0)
+                    .location(location)
+                    .format(format)
+                    .offset(offset as u32)
+                    .build()
+            })
+            .collect()
     }
 }
@@ -7,10 +7,35 @@ use ash::{version::DeviceV1_0, vk};
 pub struct DescriptorLayout {
     device: Arc<Device>,
     descriptor_layout: vk::DescriptorSetLayout,
+    descriptor_count: u32,
 }
 
 impl DescriptorLayout {
+    /// Per view layout holding the camera uniform buffer at binding 0, the lights at binding 1, the environment's
+    /// irradiance map, prefiltered map and BRDF lookup table at bindings 2 to 4, and the shadow map at binding 5
     pub fn new(device: Arc<Device>) -> Arc<DescriptorLayout> {
+        let ubo_layout_binding = vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::VERTEX | vk::ShaderStageFlags::FRAGMENT)
+            .build();
+
+        let lights_layout_binding = vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build();
+
+        let mut bindings = vec![ubo_layout_binding, lights_layout_binding];
+        bindings.extend((2..6).map(Self::fragment_sampler));
+
+        Self::create(device, &bindings)
+    }
+
+    /// Layout of a shadow pass, only the light's view and projection at binding 0
+    pub fn shadow(device: Arc<Device>) -> Arc<DescriptorLayout> {
         let ubo_layout_binding = vk::DescriptorSetLayoutBinding::builder()
             .binding(0)
             .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
@@ -18,13 +43,78 @@ impl DescriptorLayout {
             .stage_flags(vk::ShaderStageFlags::VERTEX)
             .build();
 
-        let ubo_layout_bindings = [ubo_layout_binding];
+        Self::create(device, &[ubo_layout_binding])
+    }
+
+    /// Layout of a post pass, its parameters at binding 0, the image it reads at binding 1, the bloom at binding 2 and
+    /// the color grading lookup table at binding 3
+    pub fn post(device: Arc<Device>) -> Arc<DescriptorLayout> {
+        let params_layout_binding = vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build();
+
+        let mut bindings = vec![params_layout_binding];
+        bindings.extend((1..4).map(Self::fragment_sampler));
+
+        Self::create(device, &bindings)
+    }
+
+    /// Layout of the editor grid's set, its parameters at binding 0
+    pub fn grid(device: Arc<Device>) -> Arc<DescriptorLayout> {
+        let params_layout_binding = vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build();
+
+        Self::create(device, &[params_layout_binding])
+    }
 
-        let descriptor_layout_info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(&ubo_layout_bindings).build();
+    /// Layout of a material set, the base color texture at binding 0, the parameters at binding 1, and the
+    /// metallic-roughness, normal, occlusion and emissive textures at bindings 2 to 5
+    pub fn material(device: Arc<Device>) -> Arc<DescriptorLayout> {
+        let params_layout_binding = vk::DescriptorSetLayoutBinding::builder()
+            .binding(1)
+            .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build();
+
+        let mut bindings = vec![Self::fragment_sampler(0), params_layout_binding];
+        bindings.extend((2..6).map(Self::fragment_sampler));
+
+        Self::create(device, &bindings)
+    }
+
+    fn fragment_sampler(binding: u32) -> vk::DescriptorSetLayoutBinding {
+        vk::DescriptorSetLayoutBinding::builder()
+            .binding(binding)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()
+    }
+
+    fn create(device: Arc<Device>, bindings: &[vk::DescriptorSetLayoutBinding]) -> Arc<DescriptorLayout> {
+        let descriptor_layout_info = vk::DescriptorSetLayoutCreateInfo::builder().bindings(bindings).build();
 
         let descriptor_layout = unsafe { device.vk().create_descriptor_set_layout(&descriptor_layout_info, None).unwrap() };
 
-        DescriptorLayout { device, descriptor_layout }.into()
+        DescriptorLayout {
+            device,
+            descriptor_layout,
+            descriptor_count: bindings.iter().map(|binding| binding.descriptor_count).sum(),
+        }
+        .into()
+    }
+
+    /// Descriptors of all types in one set of this layout
+    pub fn descriptor_count(&self) -> u32 {
+        self.descriptor_count
     }
 }
 
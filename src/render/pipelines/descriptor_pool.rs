@@ -1,8 +1,9 @@
 use std::sync::{Arc, Mutex, Weak};
 
 use crate::render::{
-    buffers::{UniformBufferObject, UniformTestObject},
+    buffers::{Buffer, UniformBufferObject, UniformTestObject},
     device::Device,
+    images::Texture,
     VulkanObject,
 };
 
@@ -20,7 +21,13 @@ struct Pool {
 impl Pool {
     pub fn new(device: Arc<Device>, descriptor_count: u32, set_count: u32) -> Pool {
         trace!("Creating Descriptor Pool");
-        let pool_sizes = [vk::DescriptorPoolSize::builder().descriptor_count(descriptor_count).build()];
+        let pool_sizes = [
+            vk::DescriptorPoolSize::builder().ty(vk::DescriptorType::UNIFORM_BUFFER).descriptor_count(descriptor_count).build(),
+            vk::DescriptorPoolSize::builder()
+                .ty(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                .descriptor_count(descriptor_count)
+                .build(),
+        ];
 
         let pool_info = vk::DescriptorPoolCreateInfo::builder().pool_sizes(&pool_sizes).max_sets(set_count);
 
@@ -78,20 +85,19 @@ impl DescriptorPool {
     pub fn alloc(self: &mut Arc<Self>, descriptor_layouts: &[Arc<DescriptorLayout>]) -> Arc<DescriptorPoolAlloc> {
         let mut pools = self.pools.lock().unwrap();
 
+        let set_count = descriptor_layouts.len() as u32;
+        let descriptor_count = descriptor_layouts.iter().map(|layout| layout.descriptor_count()).sum::<u32>();
+
         for pool_arc in pools.iter_mut() {
             if let Some(pool) = pool_arc.upgrade() {
                 let mut pool_mut = pool.lock().unwrap();
-                if pool_mut.remaining_sets_count == 0 {
-                    continue;
-                }
-
-                // TODO: Add a descriptor count here
-                if pool_mut.remaining_descriptor_count < descriptor_layouts.len() as u32 {
+                // Every type gets the whole descriptor count so counting all types together never overcommits one
+                if pool_mut.remaining_sets_count < set_count || pool_mut.remaining_descriptor_count < descriptor_count {
                     continue;
                 }
 
-                pool_mut.remaining_sets_count -= descriptor_layouts.len() as u32;
-                pool_mut.remaining_descriptor_count -= descriptor_layouts.len() as u32;
+                pool_mut.remaining_sets_count -= set_count;
+                pool_mut.remaining_descriptor_count -= descriptor_count;
 
                 return DescriptorPoolAlloc {
                     _pool_parent: self.clone(),
@@ -104,10 +110,10 @@ impl DescriptorPool {
 
         pools.retain(|x| x.upgrade().is_some());
 
-        let mut pool = Pool::new(self.device.clone(), 40, 40);
+        let mut pool = Pool::new(self.device.clone(), descriptor_count.max(40), set_count.max(40));
         let sets = pool.alloc(descriptor_layouts);
-        pool.remaining_sets_count -= descriptor_layouts.len() as u32;
-        pool.remaining_descriptor_count -= descriptor_layouts.len() as u32;
+        pool.remaining_sets_count -= set_count;
+        pool.remaining_descriptor_count -= descriptor_count;
 
         let pool_arc = Arc::new(Mutex::new(pool));
         pools.push(Arc::downgrade(&pool_arc));
@@ -156,6 +162,67 @@ impl DescriptorPoolAlloc {
     }
 }
 
+impl DescriptorPoolAlloc {
+    /// Points `binding` of every set at the texture's image and sampler
+    pub fn update_texture(&self, binding: u32, texture: &Texture) {
+        self.update_image(binding, *texture.view(), *texture.sampler());
+    }
+
+    /// Points `binding` of every set at an image view in `SHADER_READ_ONLY_OPTIMAL` sampled through `sampler`
+    pub fn update_image(&self, binding: u32, image_view: vk::ImageView, sampler: vk::Sampler) {
+        let image_infos = [vk::DescriptorImageInfo::builder()
+            .image_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .image_view(image_view)
+            .sampler(sampler)
+            .build()];
+
+        let descriptor_writes = self
+            .sets
+            .iter()
+            .map(|set| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_set(*set)
+                    .dst_binding(binding)
+                    .dst_array_element(0)
+                    .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+                    .image_info(&image_infos)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        let pool = self.pool.lock().unwrap();
+        unsafe { pool.device.vk().update_descriptor_sets(&descriptor_writes, &[]) };
+    }
+}
+
+impl DescriptorPoolAlloc {
+    /// Points `binding` of each set at the first `range` bytes of the uniform buffer with the same index
+    pub fn update_buffers(&self, binding: u32, buffers: &[&Buffer], range: vk::DeviceSize) {
+        let buffer_infos = buffers
+            .iter()
+            .map(|buffer| [vk::DescriptorBufferInfo::builder().buffer(*buffer.vk()).offset(0).range(range).build()])
+            .collect::<Vec<_>>();
+
+        let descriptor_writes = self
+            .sets
+            .iter()
+            .zip(buffer_infos.iter())
+            .map(|(set, buffer_info)| {
+                vk::WriteDescriptorSet::builder()
+                    .dst_set(*set)
+                    .dst_binding(binding)
+                    .dst_array_element(0)
+                    .descriptor_type(vk::DescriptorType::UNIFORM_BUFFER)
+                    .buffer_info(buffer_info)
+                    .build()
+            })
+            .collect::<Vec<_>>();
+
+        let pool = self.pool.lock().unwrap();
+        unsafe { pool.device.vk().update_descriptor_sets(&descriptor_writes, &[]) };
+    }
+}
+
 impl VulkanObject for DescriptorPoolAlloc {
     type Object = Vec<vk::DescriptorSet>;
 
@@ -2,9 +2,11 @@ mod descriptor_layout;
 mod descriptor_pool;
 // mod descriptor_set;
 mod pipeline;
+mod pipeline_cache;
 pub mod shader;
 
 pub use descriptor_layout::DescriptorLayout;
 pub use descriptor_pool::{DescriptorPool, DescriptorPoolAlloc};
 // pub use descriptor_set::DescriptorSet;
 pub use pipeline::Pipeline;
+pub use pipeline_cache::PipelineCache;
@@ -1,20 +1,41 @@
 use super::{shader, DescriptorLayout};
-use crate::render::{device::Device, models::Vertex, renderpasses::RenderPass, VulkanObject};
+use crate::render::{
+    device::Device,
+    materials::{BlendMode, RenderState, ShaderPair},
+    models::{InstanceData, Vertex, VertexAttributes},
+    renderpasses::RenderPass,
+    VulkanObject,
+};
 
 use ash::{version::DeviceV1_0, vk};
 
 use std::{ffi::CString, sync::Arc};
 
+/// Vertex shader of the fullscreen pipelines, generating a triangle covering the pass from the vertex index
+const FULLSCREEN_VERTEX_PATH: &str = "assets/gen/shaders/fullscreen.vert.spv";
+
 pub struct Pipeline {
     device: Arc<Device>,
     pipeline_layout: vk::PipelineLayout,
     pipeline: vk::Pipeline,
+    required_attributes: VertexAttributes,
 }
 
 impl Pipeline {
-    pub fn new(device: Arc<Device>, render_pass: &Arc<RenderPass>, descriptor_layout: &Arc<DescriptorLayout>) -> Arc<Pipeline> {
-        let vert_shader = shader::create_shader_module("assets/gen/shaders/shader.vert.spv", &device).unwrap();
-        let frag_shader = shader::create_shader_module("assets/gen/shaders/shader.frag.spv", &device).unwrap();
+    /// `required_attributes` and `optional_attributes` together have to match the inputs declared by the vertex shader
+    ///
+    /// Meshes without some of the optional attributes are still drawn, reading the defaults `Vertex` fills them with.
+    pub fn new(
+        device: Arc<Device>,
+        render_pass: &Arc<RenderPass>,
+        descriptor_layouts: &[Arc<DescriptorLayout>],
+        shaders: &ShaderPair,
+        state: &RenderState,
+        required_attributes: VertexAttributes,
+        optional_attributes: VertexAttributes,
+    ) -> Arc<Pipeline> {
+        let vert_shader = shader::create_shader_module(&shaders.vertex_path(), &device).unwrap();
+        let frag_shader = shader::create_shader_module(&shaders.fragment_path(), &device).unwrap();
 
         let entry_point_name = CString::new("main").unwrap();
 
@@ -32,8 +53,111 @@ impl Pipeline {
 
         let shader_stages = [vert_shader_stage_info, frag_shader_stage_info];
 
-        let vertex_binding_descriptions = [Vertex::get_binding_description()];
-        let vertex_attribute_descriptions = Vertex::get_attribute_descriptions();
+        let vertex_binding_descriptions = [Vertex::get_binding_description(), InstanceData::get_binding_description()];
+        let mut vertex_attribute_descriptions = Vertex::get_attribute_descriptions(required_attributes | optional_attributes);
+        vertex_attribute_descriptions.extend(InstanceData::get_attribute_descriptions());
+        let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::builder()
+            .vertex_attribute_descriptions(&vertex_attribute_descriptions)
+            .vertex_binding_descriptions(&vertex_binding_descriptions)
+            .build();
+
+        let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::builder().topology(state.topology).primitive_restart_enable(false).build();
+
+        let viewport_state = vk::PipelineViewportStateCreateInfo::builder().viewport_count(1).scissor_count(1).build();
+
+        let polygon_mode = if state.polygon_mode != vk::PolygonMode::FILL && device.features().fill_mode_non_solid != vk::TRUE {
+            warn!("{:?} polygons aren't supported by the device, filling them instead", state.polygon_mode);
+            vk::PolygonMode::FILL
+        } else {
+            state.polygon_mode
+        };
+
+        let rasterizer = vk::PipelineRasterizationStateCreateInfo::builder()
+            .depth_clamp_enable(false)
+            .rasterizer_discard_enable(false)
+            .polygon_mode(polygon_mode)
+            .line_width(1f32)
+            .cull_mode(state.cull_mode)
+            .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+            .depth_bias_enable(false)
+            .build();
+
+        let multisampling = vk::PipelineMultisampleStateCreateInfo::builder()
+            .sample_shading_enable(false)
+            .rasterization_samples(render_pass.samples())
+            .min_sample_shading(1f32)
+            .build();
+
+        let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::builder()
+            .depth_test_enable(state.depth_test)
+            .depth_write_enable(state.depth_write)
+            .depth_compare_op(vk::CompareOp::LESS)
+            .depth_bounds_test_enable(false)
+            .stencil_test_enable(false)
+            .build();
+
+        let color_blend_attachment = color_blend_attachment(state.blend);
+
+        let color_blend_attachments = [color_blend_attachment];
+        let color_blending = vk::PipelineColorBlendStateCreateInfo::builder().logic_op_enable(false).attachments(&color_blend_attachments).build();
+
+        let set_layouts = descriptor_layouts.iter().map(|layout| *layout.vk()).collect::<Vec<_>>();
+        let pipeline_layout_info = vk::PipelineLayoutCreateInfo::builder().set_layouts(&set_layouts).build();
+
+        let pipeline_layout = unsafe { device.vk().create_pipeline_layout(&pipeline_layout_info, None).unwrap() };
+
+        let dynamic_states = vec![vk::DynamicState::SCISSOR, vk::DynamicState::VIEWPORT];
+        let dynamic_states_info = vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(&dynamic_states).build();
+
+        let pipeline_create_info = vk::GraphicsPipelineCreateInfo::builder()
+            .stages(&shader_stages)
+            .vertex_input_state(&vertex_input_info)
+            .input_assembly_state(&input_assembly)
+            .viewport_state(&viewport_state)
+            .rasterization_state(&rasterizer)
+            .multisample_state(&multisampling)
+            .depth_stencil_state(&depth_stencil)
+            .color_blend_state(&color_blending)
+            .layout(pipeline_layout)
+            .render_pass(*render_pass.vk())
+            .dynamic_state(&dynamic_states_info)
+            .subpass(0)
+            .build();
+
+        let pipeline = unsafe { device.vk().create_graphics_pipelines(vk::PipelineCache::null(), &[pipeline_create_info], None).unwrap()[0] };
+
+        //TODO: Manage these in a shader struct to ensure resources are destroyed
+        unsafe {
+            device.vk().destroy_shader_module(vert_shader, None);
+            device.vk().destroy_shader_module(frag_shader, None);
+        }
+
+        Pipeline {
+            device,
+            pipeline_layout,
+            pipeline,
+            required_attributes,
+        }
+        .into()
+    }
+
+    /// Vertex shader only pipeline writing the depth of opaque meshes into a shadow pass
+    ///
+    /// Both faces are drawn and the depth is biased away from the light to keep surfaces from shadowing themselves.
+    pub fn depth_only(device: Arc<Device>, render_pass: &Arc<RenderPass>, descriptor_layouts: &[Arc<DescriptorLayout>], vertex_path: &str) -> Arc<Pipeline> {
+        let vert_shader = shader::create_shader_module(vertex_path, &device).unwrap();
+
+        let entry_point_name = CString::new("main").unwrap();
+
+        let shader_stages = [vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::VERTEX)
+            .module(vert_shader)
+            .name(&entry_point_name)
+            .build()];
+
+        let vertex_binding_descriptions = [Vertex::get_binding_description(), InstanceData::get_binding_description()];
+        let mut vertex_attribute_descriptions = Vertex::get_attribute_descriptions(VertexAttributes::POSITION);
+        vertex_attribute_descriptions.extend(InstanceData::get_attribute_descriptions());
         let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::builder()
             .vertex_attribute_descriptions(&vertex_attribute_descriptions)
             .vertex_binding_descriptions(&vertex_binding_descriptions)
@@ -51,25 +175,142 @@ impl Pipeline {
             .rasterizer_discard_enable(false)
             .polygon_mode(vk::PolygonMode::FILL)
             .line_width(1f32)
-            .cull_mode(vk::CullModeFlags::BACK)
+            .cull_mode(vk::CullModeFlags::NONE)
+            .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+            .depth_bias_enable(true)
+            .depth_bias_constant_factor(1.25)
+            .depth_bias_slope_factor(1.75)
+            .build();
+
+        let multisampling = vk::PipelineMultisampleStateCreateInfo::builder()
+            .sample_shading_enable(false)
+            .rasterization_samples(render_pass.samples())
+            .min_sample_shading(1f32)
+            .build();
+
+        let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::builder()
+            .depth_test_enable(true)
+            .depth_write_enable(true)
+            .depth_compare_op(vk::CompareOp::LESS)
+            .depth_bounds_test_enable(false)
+            .stencil_test_enable(false)
+            .build();
+
+        let color_blending = vk::PipelineColorBlendStateCreateInfo::builder().logic_op_enable(false).build();
+
+        let set_layouts = descriptor_layouts.iter().map(|layout| *layout.vk()).collect::<Vec<_>>();
+        let pipeline_layout_info = vk::PipelineLayoutCreateInfo::builder().set_layouts(&set_layouts).build();
+
+        let pipeline_layout = unsafe { device.vk().create_pipeline_layout(&pipeline_layout_info, None).unwrap() };
+
+        let dynamic_states = vec![vk::DynamicState::SCISSOR, vk::DynamicState::VIEWPORT];
+        let dynamic_states_info = vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(&dynamic_states).build();
+
+        let pipeline_create_info = vk::GraphicsPipelineCreateInfo::builder()
+            .stages(&shader_stages)
+            .vertex_input_state(&vertex_input_info)
+            .input_assembly_state(&input_assembly)
+            .viewport_state(&viewport_state)
+            .rasterization_state(&rasterizer)
+            .multisample_state(&multisampling)
+            .depth_stencil_state(&depth_stencil)
+            .color_blend_state(&color_blending)
+            .layout(pipeline_layout)
+            .render_pass(*render_pass.vk())
+            .dynamic_state(&dynamic_states_info)
+            .subpass(0)
+            .build();
+
+        let pipeline = unsafe { device.vk().create_graphics_pipelines(vk::PipelineCache::null(), &[pipeline_create_info], None).unwrap()[0] };
+
+        unsafe {
+            device.vk().destroy_shader_module(vert_shader, None);
+        }
+
+        Pipeline {
+            device,
+            pipeline_layout,
+            pipeline,
+            required_attributes: VertexAttributes::POSITION,
+        }
+        .into()
+    }
+
+    /// Pipeline of a post pass, drawing a single triangle covering the whole pass with the fragment shader at
+    /// `fragment_path` and no vertex input, depth or blending
+    pub fn fullscreen(device: Arc<Device>, render_pass: &Arc<RenderPass>, descriptor_layouts: &[Arc<DescriptorLayout>], fragment_path: &str) -> Arc<Pipeline> {
+        let state = RenderState {
+            cull_mode: vk::CullModeFlags::NONE,
+            depth_test: false,
+            depth_write: false,
+            ..RenderState::default()
+        };
+        Self::screen_space(device, render_pass, descriptor_layouts, FULLSCREEN_VERTEX_PATH, fragment_path, &state)
+    }
+
+    /// Pipeline without vertex input whose vertex shader places the vertices itself, drawn with `state`'s culling,
+    /// depth and blending
+    pub fn screen_space(
+        device: Arc<Device>,
+        render_pass: &Arc<RenderPass>,
+        descriptor_layouts: &[Arc<DescriptorLayout>],
+        vertex_path: &str,
+        fragment_path: &str,
+        state: &RenderState,
+    ) -> Arc<Pipeline> {
+        let vert_shader = shader::create_shader_module(vertex_path, &device).unwrap();
+        let frag_shader = shader::create_shader_module(fragment_path, &device).unwrap();
+
+        let entry_point_name = CString::new("main").unwrap();
+
+        let vert_shader_stage_info = vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::VERTEX)
+            .module(vert_shader)
+            .name(&entry_point_name)
+            .build();
+
+        let frag_shader_stage_info = vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::FRAGMENT)
+            .module(frag_shader)
+            .name(&entry_point_name)
+            .build();
+
+        let shader_stages = [vert_shader_stage_info, frag_shader_stage_info];
+
+        let vertex_input_info = vk::PipelineVertexInputStateCreateInfo::builder().build();
+
+        let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::builder().topology(state.topology).primitive_restart_enable(false).build();
+
+        let viewport_state = vk::PipelineViewportStateCreateInfo::builder().viewport_count(1).scissor_count(1).build();
+
+        let rasterizer = vk::PipelineRasterizationStateCreateInfo::builder()
+            .depth_clamp_enable(false)
+            .rasterizer_discard_enable(false)
+            .polygon_mode(vk::PolygonMode::FILL)
+            .line_width(1f32)
+            .cull_mode(state.cull_mode)
             .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
             .depth_bias_enable(false)
             .build();
 
         let multisampling = vk::PipelineMultisampleStateCreateInfo::builder()
             .sample_shading_enable(false)
-            .rasterization_samples(vk::SampleCountFlags::TYPE_1)
+            .rasterization_samples(render_pass.samples())
             .min_sample_shading(1f32)
             .build();
 
-        let color_blend_attachment = vk::PipelineColorBlendAttachmentState::builder()
-            .color_write_mask(vk::ColorComponentFlags::all())
-            .blend_enable(false)
+        let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::builder()
+            .depth_test_enable(state.depth_test)
+            .depth_write_enable(state.depth_write)
+            .depth_compare_op(vk::CompareOp::LESS)
+            .depth_bounds_test_enable(false)
+            .stencil_test_enable(false)
             .build();
 
-        let color_blending = vk::PipelineColorBlendStateCreateInfo::builder().logic_op_enable(false).attachments(&[color_blend_attachment]).build();
+        let color_blend_attachments = [color_blend_attachment(state.blend)];
+        let color_blending = vk::PipelineColorBlendStateCreateInfo::builder().logic_op_enable(false).attachments(&color_blend_attachments).build();
 
-        let set_layouts = [*descriptor_layout.vk()];
+        let set_layouts = descriptor_layouts.iter().map(|layout| *layout.vk()).collect::<Vec<_>>();
         let pipeline_layout_info = vk::PipelineLayoutCreateInfo::builder().set_layouts(&set_layouts).build();
 
         let pipeline_layout = unsafe { device.vk().create_pipeline_layout(&pipeline_layout_info, None).unwrap() };
@@ -84,6 +325,7 @@ impl Pipeline {
             .viewport_state(&viewport_state)
             .rasterization_state(&rasterizer)
             .multisample_state(&multisampling)
+            .depth_stencil_state(&depth_stencil)
             .color_blend_state(&color_blending)
             .layout(pipeline_layout)
             .render_pass(*render_pass.vk())
@@ -93,13 +335,27 @@ impl Pipeline {
 
         let pipeline = unsafe { device.vk().create_graphics_pipelines(vk::PipelineCache::null(), &[pipeline_create_info], None).unwrap()[0] };
 
-        //TODO: Manage these in a shader struct to ensure resources are destroyed
         unsafe {
             device.vk().destroy_shader_module(vert_shader, None);
             device.vk().destroy_shader_module(frag_shader, None);
         }
 
-        Pipeline { device, pipeline_layout, pipeline }.into()
+        Pipeline {
+            device,
+            pipeline_layout,
+            pipeline,
+            required_attributes: VertexAttributes::none(),
+        }
+        .into()
+    }
+
+    /// Whether a mesh carrying `attributes` provides every input this pipeline requires
+    pub fn accepts(&self, attributes: VertexAttributes) -> bool {
+        attributes.contains(self.required_attributes)
+    }
+
+    pub fn required_attributes(&self) -> VertexAttributes {
+        self.required_attributes
     }
 
     pub fn get_layout(&self) -> &vk::PipelineLayout {
@@ -124,3 +380,33 @@ impl Drop for Pipeline {
         }
     }
 }
+
+/// Blending of the color attachment for a material's blend mode
+fn color_blend_attachment(blend: BlendMode) -> vk::PipelineColorBlendAttachmentState {
+    match blend {
+        BlendMode::Opaque => vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::all())
+            .blend_enable(false)
+            .build(),
+        BlendMode::Alpha => vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::all())
+            .blend_enable(true)
+            .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+            .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+            .color_blend_op(vk::BlendOp::ADD)
+            .src_alpha_blend_factor(vk::BlendFactor::ONE)
+            .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+            .alpha_blend_op(vk::BlendOp::ADD)
+            .build(),
+        BlendMode::Additive => vk::PipelineColorBlendAttachmentState::builder()
+            .color_write_mask(vk::ColorComponentFlags::all())
+            .blend_enable(true)
+            .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+            .dst_color_blend_factor(vk::BlendFactor::ONE)
+            .color_blend_op(vk::BlendOp::ADD)
+            .src_alpha_blend_factor(vk::BlendFactor::ZERO)
+            .dst_alpha_blend_factor(vk::BlendFactor::ONE)
+            .alpha_blend_op(vk::BlendOp::ADD)
+            .build(),
+    }
+}
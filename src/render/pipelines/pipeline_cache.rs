@@ -0,0 +1,51 @@
+use std::{collections::HashMap, sync::Arc};
+
+use ash::vk;
+
+use super::{DescriptorLayout, Pipeline};
+use crate::render::{
+    device::Device,
+    materials::{RenderState, ShaderPair},
+    models::VertexAttributes,
+    renderpasses::RenderPass,
+};
+
+/// Builds each combination of shaders, render state and sample count once, every pipeline shares the descriptor layouts
+pub struct PipelineCache {
+    device: Arc<Device>,
+    descriptor_layouts: Vec<Arc<DescriptorLayout>>,
+    pipelines: HashMap<(ShaderPair, RenderState, vk::SampleCountFlags), Arc<Pipeline>>,
+}
+
+impl PipelineCache {
+    pub fn new(device: Arc<Device>, descriptor_layouts: Vec<Arc<DescriptorLayout>>) -> PipelineCache {
+        PipelineCache {
+            device,
+            descriptor_layouts,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Pipeline drawing into `render_pass` or any pass compatible with it
+    ///
+    /// Scene passes all share the frame's formats, so pipelines are only told apart by the samples of the pass.
+    pub fn get(&mut self, shaders: &ShaderPair, state: &RenderState, render_pass: &Arc<RenderPass>) -> Arc<Pipeline> {
+        let key = (shaders.clone(), *state, render_pass.samples());
+        if let Some(pipeline) = self.pipelines.get(&key) {
+            return pipeline.clone();
+        }
+
+        debug!("Creating pipeline for {:?} with {:?} and {:?} samples", shaders, state, render_pass.samples());
+        let pipeline = Pipeline::new(
+            self.device.clone(),
+            render_pass,
+            &self.descriptor_layouts,
+            shaders,
+            state,
+            VertexAttributes::POSITION | VertexAttributes::COLOR,
+            VertexAttributes::NORMAL | VertexAttributes::UV | VertexAttributes::TANGENT,
+        );
+        self.pipelines.insert(key, pipeline.clone());
+        pipeline
+    }
+}
@@ -2,11 +2,14 @@ use std::sync::Arc;
 
 use ash::{version::DeviceV1_0, vk};
 
-use crate::render::{device::Device, VulkanObject};
+use crate::{
+    assets,
+    render::{device::Device, VulkanObject},
+};
 
-// Returns a shader module from the source file
+// Returns a shader module from the source file, found relative to the asset root
 pub fn create_shader_module(file_name: &str, device: &Arc<Device>) -> Result<vk::ShaderModule, Box<dyn std::error::Error>> {
-    let mut file = std::fs::File::open(file_name)?;
+    let mut file = std::fs::File::open(assets::asset_path(file_name))?;
     let words = ash::util::read_spv(&mut file).unwrap();
 
     let create_info = vk::ShaderModuleCreateInfo::builder().code(&words).build();
@@ -0,0 +1,321 @@
+use std::sync::Arc;
+
+use ash::{version::DeviceV1_0, vk};
+
+use super::{
+    buffers::Buffer,
+    commands::{submit_single_time_commands, CommandBuffer},
+    constants::HDR_FORMAT,
+    device::Device,
+    images::{Image, Texture},
+    pipelines::{DescriptorLayout, DescriptorPoolAlloc, Pipeline},
+    renderpasses::{FrameBuffer, RenderPass, RenderTarget},
+    VulkanObject,
+};
+
+/// Bloom is blurred at this fraction of the frame's width and height
+const BLOOM_DOWNSAMPLE: u32 = 4;
+
+/// Post passes run on the frame and their parameters, toggled at runtime through `RenderSettings::post`
+#[derive(Clone)]
+pub struct PostSettings {
+    /// Scales the scene's light before it is tonemapped
+    pub exposure: f32,
+    /// Maps the scene's light into the displayable range with a filmic curve, it is clipped when off
+    pub tonemapping: bool,
+    pub bloom: bool,
+    /// Brightness above which light bleeds into its surroundings
+    pub bloom_threshold: f32,
+    pub bloom_intensity: f32,
+    pub vignette: bool,
+    /// Darkening of the frame's corners, from 0 to 1
+    pub vignette_strength: f32,
+    pub fxaa: bool,
+    pub color_grading: bool,
+    /// Color grading lookup table laid out like `TextureFactory::create_identity_lut`, that identity when `None`
+    pub lut: Option<Arc<Texture>>,
+}
+
+impl PostSettings {
+    const TONEMAPPING: u32 = 1;
+    const BLOOM: u32 = 1 << 1;
+    const VIGNETTE: u32 = 1 << 2;
+    const COLOR_GRADING: u32 = 1 << 3;
+
+    fn flags(&self) -> u32 {
+        [
+            (self.tonemapping, Self::TONEMAPPING),
+            (self.bloom, Self::BLOOM),
+            (self.vignette, Self::VIGNETTE),
+            (self.color_grading, Self::COLOR_GRADING),
+        ]
+        .iter()
+        .filter(|(enabled, _)| *enabled)
+        .fold(0, |flags, (_, flag)| flags | flag)
+    }
+}
+
+impl Default for PostSettings {
+    fn default() -> Self {
+        Self {
+            exposure: 1.0,
+            tonemapping: true,
+            bloom: false,
+            bloom_threshold: 1.0,
+            bloom_intensity: 0.5,
+            vignette: false,
+            vignette_strength: 0.5,
+            fxaa: false,
+            color_grading: false,
+            lut: None,
+        }
+    }
+}
+
+/// Uniform block shared by the post shaders, laid out to match std140
+#[repr(C)]
+#[derive(Copy, Clone)]
+struct PostParams {
+    /// Texels between the taps of a blur pass
+    direction: [f32; 2],
+    exposure: f32,
+    bloom_threshold: f32,
+    bloom_intensity: f32,
+    vignette_strength: f32,
+    flags: u32,
+    _padding: u32,
+}
+
+/// Fullscreen pass drawing with one fragment shader, with its own parameters and set per swapchain image
+struct PostPass {
+    pipeline: Arc<Pipeline>,
+    params_buffers: Vec<Buffer>,
+    sets: Arc<DescriptorPoolAlloc>,
+    direction: [f32; 2],
+}
+
+impl PostPass {
+    /// The pass reads `source` at binding 1, `bloom` at binding 2 and `lut` at binding 3
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        device: &Arc<Device>,
+        render_pass: &Arc<RenderPass>,
+        layout: &Arc<DescriptorLayout>,
+        image_count: usize,
+        fragment_path: &str,
+        direction: [f32; 2],
+        source: &Texture,
+        bloom: &Texture,
+        lut: &Texture,
+    ) -> PostPass {
+        let pipeline = Pipeline::fullscreen(device.clone(), render_pass, std::slice::from_ref(layout), fragment_path);
+        let params_size = std::mem::size_of::<PostParams>() as vk::DeviceSize;
+        let params_buffers = (0..image_count)
+            .map(|_| {
+                Buffer::new(
+                    params_size,
+                    vk::BufferUsageFlags::UNIFORM_BUFFER,
+                    vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+                    device.clone(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let layouts = params_buffers.iter().map(|_| layout.clone()).collect::<Vec<_>>();
+        let sets = device.descriptor_pool().alloc(&layouts);
+        sets.update_buffers(0, &params_buffers.iter().collect::<Vec<_>>(), params_size);
+        sets.update_texture(1, source);
+        sets.update_texture(2, bloom);
+        sets.update_texture(3, lut);
+
+        PostPass {
+            pipeline,
+            params_buffers,
+            sets,
+            direction,
+        }
+    }
+
+    fn update(&self, image_index: usize, settings: &PostSettings) {
+        self.params_buffers[image_index].map_memory::<f32, _>(&[PostParams {
+            direction: self.direction,
+            exposure: settings.exposure,
+            bloom_threshold: settings.bloom_threshold,
+            bloom_intensity: settings.bloom_intensity,
+            vignette_strength: settings.vignette_strength,
+            flags: settings.flags(),
+            _padding: 0,
+        }]);
+    }
+}
+
+/// Images and passes taking the HDR scene to the frame: bloom, then exposure, tonemapping, color grading and vignette
+/// in one composite pass, then FXAA
+pub struct PostProcess {
+    device: Arc<Device>,
+    /// Scene pass output, resolved into when multisampling
+    hdr_image: Arc<Texture>,
+    /// Bloom is blurred back and forth between these two
+    bloom_images: [Arc<Texture>; 2],
+    /// Composited frame FXAA reads, in the target's format
+    _ldr_image: Arc<Texture>,
+    hdr_pass: Arc<RenderPass>,
+    ldr_pass: Arc<RenderPass>,
+    /// Last pass, into the target's image which the overlay pass draws on
+    output_pass: Arc<RenderPass>,
+    bloom_frame_buffers: Arc<FrameBuffer>,
+    ldr_frame_buffer: Arc<FrameBuffer>,
+    output_frame_buffers: Arc<FrameBuffer>,
+    bright_pass: PostPass,
+    blur_passes: [PostPass; 2],
+    composite_pass: PostPass,
+    fxaa_pass: PostPass,
+    lut: Arc<Texture>,
+}
+
+impl PostProcess {
+    pub fn new(device: Arc<Device>, target: &RenderTarget, layout: &Arc<DescriptorLayout>, lut: Arc<Texture>) -> PostProcess {
+        let extent = *target.extent();
+        let bloom_extent = vk::Extent2D {
+            width: (extent.width / BLOOM_DOWNSAMPLE).max(1),
+            height: (extent.height / BLOOM_DOWNSAMPLE).max(1),
+        };
+
+        let hdr_image = Self::create_image(&device, extent, HDR_FORMAT);
+        let bloom_images = [Self::create_image(&device, bloom_extent, HDR_FORMAT), Self::create_image(&device, bloom_extent, HDR_FORMAT)];
+        let ldr_image = Self::create_image(&device, extent, target.format());
+
+        let hdr_pass = RenderPass::post(device.clone(), HDR_FORMAT, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        let ldr_pass = RenderPass::post(device.clone(), target.format(), vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        let output_pass = RenderPass::post(device.clone(), target.format(), vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL);
+
+        let bloom_views = bloom_images.iter().map(|image| *image.view()).collect::<Vec<_>>();
+        let bloom_frame_buffers = FrameBuffer::with_views(device.clone(), &bloom_views, bloom_extent, None, &hdr_pass);
+        let ldr_frame_buffer = FrameBuffer::with_views(device.clone(), &[*ldr_image.view()], extent, None, &ldr_pass);
+        let output_frame_buffers = FrameBuffer::new(device.clone(), target, &output_pass);
+
+        let image_count = target.image_count();
+        let pass = |render_pass: &Arc<RenderPass>, shader: &str, direction: [f32; 2], source: &Texture, bloom: &Texture| {
+            PostPass::new(&device, render_pass, layout, image_count, shader, direction, source, bloom, &lut)
+        };
+        let bright_pass = pass(&hdr_pass, "assets/gen/shaders/post_bright.frag.spv", [0.0, 0.0], &hdr_image, &bloom_images[1]);
+        let blur_passes = [
+            pass(&hdr_pass, "assets/gen/shaders/post_blur.frag.spv", [1.0, 0.0], &bloom_images[0], &bloom_images[1]),
+            pass(&hdr_pass, "assets/gen/shaders/post_blur.frag.spv", [0.0, 1.0], &bloom_images[1], &bloom_images[0]),
+        ];
+        let composite_pass = pass(&ldr_pass, "assets/gen/shaders/post_composite.frag.spv", [0.0, 0.0], &hdr_image, &bloom_images[0]);
+        let fxaa_pass = pass(&ldr_pass, "assets/gen/shaders/post_fxaa.frag.spv", [0.0, 0.0], &ldr_image, &bloom_images[0]);
+
+        PostProcess {
+            device,
+            hdr_image,
+            bloom_images,
+            _ldr_image: ldr_image,
+            hdr_pass,
+            ldr_pass,
+            output_pass,
+            bloom_frame_buffers,
+            ldr_frame_buffer,
+            output_frame_buffers,
+            bright_pass,
+            blur_passes,
+            composite_pass,
+            fxaa_pass,
+            lut,
+        }
+    }
+
+    /// Color attachment sampled by the post passes, transitioned so disabled passes can still bind it
+    fn create_image(device: &Arc<Device>, extent: vk::Extent2D, format: vk::Format) -> Arc<Texture> {
+        let image = Image::new(
+            device.clone(),
+            extent,
+            format,
+            vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+            vk::ImageAspectFlags::COLOR,
+        );
+
+        submit_single_time_commands(device, |command_buffer| {
+            image.transition_layout(command_buffer, vk::ImageLayout::UNDEFINED, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL);
+        });
+
+        Texture::from_image_clamped(device.clone(), image)
+    }
+
+    /// Image the scene pass draws or resolves into
+    pub fn hdr_view(&self) -> &vk::ImageView {
+        self.hdr_image.view()
+    }
+
+    pub fn lut(&self) -> &Arc<Texture> {
+        &self.lut
+    }
+
+    /// Grades colors with `lut`, the table must not be in use by a pending command buffer
+    pub fn set_lut(&mut self, lut: Arc<Texture>) {
+        self.composite_pass.sets.update_texture(3, &lut);
+        self.lut = lut;
+    }
+
+    /// Writes the parameters of the frame drawn into `image_index` and records the enabled passes, leaving the
+    /// target's image ready for the overlay pass
+    pub fn record(&self, command_buffers: &CommandBuffer, image_index: usize, settings: &PostSettings) {
+        let extent = *self.hdr_image.extent();
+        let output_frame_buffer = self.output_frame_buffers.vk()[image_index];
+
+        if settings.bloom {
+            let bloom_extent = *self.bloom_images[0].extent();
+            let [bloom, blurred] = [self.bloom_frame_buffers.vk()[0], self.bloom_frame_buffers.vk()[1]];
+            self.record_pass(command_buffers, image_index, &self.bright_pass, &self.hdr_pass, bloom, bloom_extent, settings);
+            self.record_pass(command_buffers, image_index, &self.blur_passes[0], &self.hdr_pass, blurred, bloom_extent, settings);
+            self.record_pass(command_buffers, image_index, &self.blur_passes[1], &self.hdr_pass, bloom, bloom_extent, settings);
+        }
+
+        if settings.fxaa {
+            let ldr_frame_buffer = self.ldr_frame_buffer.vk()[0];
+            self.record_pass(command_buffers, image_index, &self.composite_pass, &self.ldr_pass, ldr_frame_buffer, extent, settings);
+            self.record_pass(command_buffers, image_index, &self.fxaa_pass, &self.output_pass, output_frame_buffer, extent, settings);
+        } else {
+            self.record_pass(command_buffers, image_index, &self.composite_pass, &self.output_pass, output_frame_buffer, extent, settings);
+        }
+    }
+
+    /// Draws one fullscreen triangle with `pass` into `frame_buffer`, in a render pass of its own
+    #[allow(clippy::too_many_arguments)]
+    fn record_pass(
+        &self,
+        command_buffers: &CommandBuffer,
+        image_index: usize,
+        pass: &PostPass,
+        render_pass: &RenderPass,
+        frame_buffer: vk::Framebuffer,
+        extent: vk::Extent2D,
+        settings: &PostSettings,
+    ) {
+        pass.update(image_index, settings);
+
+        let render_area = vk::Rect2D::builder().offset(vk::Offset2D { x: 0, y: 0 }).extent(extent).build();
+        let render_pass_info = vk::RenderPassBeginInfo::builder()
+            .render_pass(*render_pass.vk())
+            .framebuffer(frame_buffer)
+            .render_area(render_area)
+            .build();
+
+        command_buffers.begin_render_pass(image_index, &render_pass_info);
+        command_buffers.set_scissor(image_index, render_area);
+        command_buffers.set_viewport(
+            image_index,
+            vk::Viewport::builder().width(extent.width as f32).height(extent.height as f32).min_depth(0f32).max_depth(1f32).build(),
+        );
+        command_buffers.bind_pipeline(image_index, pass.pipeline.vk());
+        command_buffers.bind_descriptor_sets(image_index, pass.pipeline.get_layout(), 0, &pass.sets.vk()[image_index..=image_index]);
+        unsafe { self.device.vk().cmd_draw(*command_buffers.get(image_index), 3, 1, 0, 0) };
+        command_buffers.end_render_pass(image_index);
+    }
+}
+
+impl Drop for PostProcess {
+    fn drop(&mut self) {
+        trace!("Dropping Post Process");
+    }
+}
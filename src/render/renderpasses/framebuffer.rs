@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
-use super::{RenderPass, SwapChain};
-use crate::render::{device::Device, VulkanObject};
+use super::{RenderPass, RenderTarget};
+use crate::render::{device::Device, images::Image, VulkanObject};
 
 use ash::{version::DeviceV1_0, vk};
 
@@ -12,17 +12,43 @@ pub struct FrameBuffer {
 }
 
 impl FrameBuffer {
-    pub fn new(device: Arc<Device>, swapchain: &Arc<SwapChain>, render_pass: &Arc<RenderPass>) -> Arc<Self> {
-        let mut framebuffers: Vec<vk::Framebuffer> = Vec::new();
+    /// One framebuffer per image of the target, drawn into without depth
+    pub fn new(device: Arc<Device>, target: &RenderTarget, render_pass: &Arc<RenderPass>) -> Arc<Self> {
+        Self::with_views(device, &target.image_views(), *target.extent(), None, render_pass)
+    }
+
+    /// Single framebuffer drawing into the multisampled `color_image` and `depth_image`, resolved into `resolve_view`
+    pub fn resolving(device: Arc<Device>, color_image: &Image, depth_image: &Image, resolve_view: vk::ImageView, render_pass: &Arc<RenderPass>) -> Arc<Self> {
+        let attachments = [vec![*color_image.view(), *depth_image.view(), resolve_view]];
+        Self::create(device, &attachments, *color_image.extent(), render_pass)
+    }
 
-        for &image_view in swapchain.image_views().iter() {
-            let attachments = [image_view];
+    /// One framebuffer per view, each followed by the shared depth image when there is one
+    ///
+    /// Depth only passes pass their depth views here without a separate depth image.
+    pub fn with_views(device: Arc<Device>, image_views: &[vk::ImageView], extent: vk::Extent2D, depth_image: Option<&Image>, render_pass: &Arc<RenderPass>) -> Arc<Self> {
+        let attachments = image_views
+            .iter()
+            .map(|&image_view| {
+                let mut attachments = vec![image_view];
+                attachments.extend(depth_image.map(|depth_image| *depth_image.view()));
+                attachments
+            })
+            .collect::<Vec<_>>();
+
+        Self::create(device, &attachments, extent, render_pass)
+    }
+
+    /// One framebuffer per list of attachments
+    fn create(device: Arc<Device>, attachments: &[Vec<vk::ImageView>], extent: vk::Extent2D, render_pass: &Arc<RenderPass>) -> Arc<Self> {
+        let mut framebuffers: Vec<vk::Framebuffer> = Vec::new();
 
+        for attachments in attachments.iter() {
             let framebuffer_info = vk::FramebufferCreateInfo::builder()
                 .render_pass(*render_pass.vk())
-                .attachments(&attachments)
-                .width(swapchain.extent().width)
-                .height(swapchain.extent().height)
+                .attachments(attachments)
+                .width(extent.width)
+                .height(extent.height)
                 .layers(1)
                 .build();
 
@@ -1,7 +1,9 @@
 mod framebuffer;
 mod renderpass;
 mod swapchain;
+mod target;
 
 pub use framebuffer::FrameBuffer;
 pub use renderpass::RenderPass;
 pub use swapchain::SwapChain;
+pub use target::RenderTarget;
@@ -7,46 +7,235 @@ use ash::{version::DeviceV1_0, vk};
 pub struct RenderPass {
     device: Arc<Device>,
     render_pass: vk::RenderPass,
+    samples: vk::SampleCountFlags,
 }
 
 impl RenderPass {
-    pub fn new(device: Arc<Device>, format: vk::Format) -> Arc<RenderPass> {
+    /// Scene pass clearing color and depth, the color attachment ends up ready to be sampled by the post passes
+    ///
+    /// With more than one sample the scene is drawn into multisampled color and depth attachments which are resolved
+    /// into a third attachment. Render textures use the same pass with a single sample so the scene pipelines can draw
+    /// into them. Earlier reads of the color by fragment shaders finish before it is cleared and its writes are visible
+    /// to fragment shaders of the passes recorded after it.
+    pub fn new(device: Arc<Device>, format: vk::Format, depth_format: vk::Format, samples: vk::SampleCountFlags) -> Arc<RenderPass> {
+        let multisampled = samples != vk::SampleCountFlags::TYPE_1;
         let color_attachment = vk::AttachmentDescription::builder()
             .format(format)
+            .samples(samples)
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(if multisampled { vk::AttachmentStoreOp::DONT_CARE } else { vk::AttachmentStoreOp::STORE })
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(
+                if multisampled {
+                    vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL
+                } else {
+                    vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL
+                },
+            )
+            .build();
+
+        let depth_attachment = vk::AttachmentDescription::builder()
+            .format(depth_format)
+            .samples(samples)
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+            .build();
+
+        let resolve_attachment = vk::AttachmentDescription::builder()
+            .format(format)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+            .build();
+
+        let color_attachment_refs = [vk::AttachmentReference::builder().attachment(0).layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL).build()];
+        let depth_attachment_ref = vk::AttachmentReference::builder().attachment(1).layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL).build();
+        let resolve_attachment_refs = [vk::AttachmentReference::builder().attachment(2).layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL).build()];
+
+        let mut sub_pass = vk::SubpassDescription::builder()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .color_attachments(&color_attachment_refs)
+            .depth_stencil_attachment(&depth_attachment_ref);
+        if multisampled {
+            sub_pass = sub_pass.resolve_attachments(&resolve_attachment_refs);
+        }
+        let sub_pass = sub_pass.build();
+
+        let dependencies = [
+            vk::SubpassDependency::builder()
+                .src_subpass(vk::SUBPASS_EXTERNAL)
+                .dst_subpass(0)
+                .src_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS)
+                .src_access_mask(vk::AccessFlags::SHADER_READ | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+                .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT | vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS)
+                .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+                .build(),
+            vk::SubpassDependency::builder()
+                .src_subpass(0)
+                .dst_subpass(vk::SUBPASS_EXTERNAL)
+                .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+                .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .build(),
+        ];
+
+        if multisampled {
+            Self::create(device, &[color_attachment, depth_attachment, resolve_attachment], sub_pass, &dependencies)
+        } else {
+            Self::create(device, &[color_attachment, depth_attachment], sub_pass, &dependencies)
+        }
+    }
+
+    /// Depth only pass into one layer of the shadow map, which ends up ready to be sampled
+    ///
+    /// Earlier reads of the layer by fragment shaders finish before it is cleared and its depth is visible to
+    /// fragment shaders of the passes recorded after it.
+    pub fn shadow(device: Arc<Device>, depth_format: vk::Format) -> Arc<RenderPass> {
+        let depth_attachment = vk::AttachmentDescription::builder()
+            .format(depth_format)
             .samples(vk::SampleCountFlags::TYPE_1)
             .load_op(vk::AttachmentLoadOp::CLEAR)
             .store_op(vk::AttachmentStoreOp::STORE)
             .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
             .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
             .initial_layout(vk::ImageLayout::UNDEFINED)
-            .final_layout(vk::ImageLayout::PRESENT_SRC_KHR)
+            .final_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
             .build();
 
-        let color_attachment_ref = vk::AttachmentReference::builder().attachment(0).layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL).build();
+        let depth_attachment_ref = vk::AttachmentReference::builder().attachment(0).layout(vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL).build();
 
         let sub_pass = vk::SubpassDescription::builder()
             .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
-            .color_attachments(&[color_attachment_ref])
+            .depth_stencil_attachment(&depth_attachment_ref)
+            .build();
+
+        let dependencies = [
+            vk::SubpassDependency::builder()
+                .src_subpass(vk::SUBPASS_EXTERNAL)
+                .dst_subpass(0)
+                .src_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+                .src_access_mask(vk::AccessFlags::SHADER_READ)
+                .dst_stage_mask(vk::PipelineStageFlags::EARLY_FRAGMENT_TESTS | vk::PipelineStageFlags::LATE_FRAGMENT_TESTS)
+                .dst_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_READ | vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+                .build(),
+            vk::SubpassDependency::builder()
+                .src_subpass(0)
+                .dst_subpass(vk::SUBPASS_EXTERNAL)
+                .src_stage_mask(vk::PipelineStageFlags::LATE_FRAGMENT_TESTS)
+                .src_access_mask(vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE)
+                .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .build(),
+        ];
+
+        Self::create(device, &[depth_attachment], sub_pass, &dependencies)
+    }
+
+    /// Color only pass of a fullscreen post pass, which overwrites every pixel so nothing is cleared or loaded
+    ///
+    /// Intermediate images end in `SHADER_READ_ONLY_OPTIMAL` for the next post pass, the last pass leaves the frame's
+    /// image as a color attachment for the overlay pass. Earlier reads of the image by fragment shaders finish before
+    /// it is written.
+    pub fn post(device: Arc<Device>, format: vk::Format, final_layout: vk::ImageLayout) -> Arc<RenderPass> {
+        let color_attachment = vk::AttachmentDescription::builder()
+            .format(format)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(final_layout)
+            .build();
+
+        let color_attachment_refs = [vk::AttachmentReference::builder().attachment(0).layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL).build()];
+
+        let sub_pass = vk::SubpassDescription::builder()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .color_attachments(&color_attachment_refs)
+            .build();
+
+        let dependencies = [
+            vk::SubpassDependency::builder()
+                .src_subpass(vk::SUBPASS_EXTERNAL)
+                .dst_subpass(0)
+                .src_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER | vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+                .src_access_mask(vk::AccessFlags::SHADER_READ)
+                .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+                .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                .build(),
+            vk::SubpassDependency::builder()
+                .src_subpass(0)
+                .dst_subpass(vk::SUBPASS_EXTERNAL)
+                .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
+                .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
+                .dst_stage_mask(vk::PipelineStageFlags::FRAGMENT_SHADER)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .build(),
+        ];
+
+        Self::create(device, &[color_attachment], sub_pass, &dependencies)
+    }
+
+    /// Color only pass drawn on top of the post passes' output, used for the ui which has no depth state
+    pub fn overlay(device: Arc<Device>, format: vk::Format, final_layout: vk::ImageLayout) -> Arc<RenderPass> {
+        let color_attachment = vk::AttachmentDescription::builder()
+            .format(format)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .load_op(vk::AttachmentLoadOp::LOAD)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+            .final_layout(final_layout)
+            .build();
+
+        let color_attachment_refs = [vk::AttachmentReference::builder().attachment(0).layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL).build()];
+
+        let sub_pass = vk::SubpassDescription::builder()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .color_attachments(&color_attachment_refs)
             .build();
 
         let dependency = vk::SubpassDependency::builder()
             .src_subpass(vk::SUBPASS_EXTERNAL)
             .dst_subpass(0)
             .src_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
-            .src_access_mask(vk::AccessFlags::default())
+            .src_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
             .dst_stage_mask(vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT)
             .dst_access_mask(vk::AccessFlags::COLOR_ATTACHMENT_READ | vk::AccessFlags::COLOR_ATTACHMENT_WRITE)
             .build();
 
-        let render_pass_info = vk::RenderPassCreateInfo::builder()
-            .attachments(&[color_attachment])
-            .subpasses(&[sub_pass])
-            .dependencies(&[dependency])
-            .build();
+        Self::create(device, &[color_attachment], sub_pass, &[dependency])
+    }
+
+    // The subpass points into the caller's attachment references so it has to be built there
+    fn create(device: Arc<Device>, attachments: &[vk::AttachmentDescription], sub_pass: vk::SubpassDescription, dependencies: &[vk::SubpassDependency]) -> Arc<RenderPass> {
+        let sub_passes = [sub_pass];
+        let render_pass_info = vk::RenderPassCreateInfo::builder().attachments(attachments).subpasses(&sub_passes).dependencies(dependencies).build();
 
         let render_pass = unsafe { device.vk().create_render_pass(&render_pass_info, None).unwrap() };
 
-        RenderPass { device, render_pass }.into()
+        // Every pass draws all its attachments with the same number of samples, apart from resolve attachments
+        let samples = attachments[0].samples;
+
+        RenderPass { device, render_pass, samples }.into()
+    }
+
+    /// Samples per pixel of the attachments drawn into, pipelines have to rasterize with as many
+    pub fn samples(&self) -> vk::SampleCountFlags {
+        self.samples
     }
 }
 
@@ -117,8 +117,10 @@ impl SwapChain {
     }
 
     pub fn choose_surface_format(available_formats: Vec<vk::SurfaceFormatKHR>) -> vk::SurfaceFormatKHR {
+        // The frame is shaded in linear space and encoded to sRGB when written, like headless frames
         for &available_format in available_formats.iter() {
-            if available_format.format == vk::Format::R8G8B8A8_UNORM && available_format.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR {
+            let srgb = available_format.format == vk::Format::R8G8B8A8_SRGB || available_format.format == vk::Format::B8G8R8A8_SRGB;
+            if srgb && available_format.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR {
                 return available_format;
             }
         }
@@ -148,21 +150,6 @@ impl SwapChain {
         }
     }
 
-    pub fn scissor(&self) -> vk::Rect2D {
-        vk::Rect2D::builder().offset(vk::Offset2D { x: 0, y: 0 }).extent(*self.extent()).build()
-    }
-
-    pub fn viewport(&self) -> vk::Viewport {
-        vk::Viewport::builder()
-            .x(0f32)
-            .y(0f32)
-            .width(self.extent().width as f32)
-            .height(self.extent().height as f32)
-            .min_depth(0f32)
-            .max_depth(1f32)
-            .build()
-    }
-
     pub fn extent(&self) -> &vk::Extent2D {
         &self.extent
     }
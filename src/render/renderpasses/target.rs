@@ -0,0 +1,54 @@
+use std::sync::Arc;
+
+use super::SwapChain;
+use crate::render::images::Image;
+
+use ash::vk;
+
+/// The images a frame is rendered into, either presented to a window or kept offscreen for readback
+pub enum RenderTarget {
+    Swapchain(Arc<SwapChain>),
+    Offscreen(Image),
+}
+
+impl RenderTarget {
+    pub fn extent(&self) -> &vk::Extent2D {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.extent(),
+            RenderTarget::Offscreen(image) => image.extent(),
+        }
+    }
+
+    pub fn format(&self) -> vk::Format {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.surface_format().format,
+            RenderTarget::Offscreen(image) => image.format(),
+        }
+    }
+
+    pub fn image_views(&self) -> Vec<vk::ImageView> {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.image_views().clone(),
+            RenderTarget::Offscreen(image) => vec![*image.view()],
+        }
+    }
+
+    pub fn image_count(&self) -> usize {
+        match self {
+            RenderTarget::Swapchain(swapchain) => swapchain.images().len(),
+            RenderTarget::Offscreen(_) => 1,
+        }
+    }
+
+    /// Layout the color attachment is left in at the end of the render pass
+    pub fn final_layout(&self) -> vk::ImageLayout {
+        match self {
+            RenderTarget::Swapchain(_) => vk::ImageLayout::PRESENT_SRC_KHR,
+            RenderTarget::Offscreen(_) => vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+        }
+    }
+
+    pub fn scissor(&self) -> vk::Rect2D {
+        vk::Rect2D::builder().offset(vk::Offset2D { x: 0, y: 0 }).extent(*self.extent()).build()
+    }
+}
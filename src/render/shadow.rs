@@ -0,0 +1,91 @@
+use super::constants::SHADOW_MAP_SIZE;
+
+/// Cascades covering the view of a shadow casting directional light, each one rendered into its own layer
+pub const SHADOW_CASCADES: usize = 3;
+/// Blend between uniform and logarithmic cascade splits, higher values give the near cascades more resolution
+const SPLIT_LAMBDA: f32 = 0.75;
+/// Distance behind each cascade from which casters outside of the view still throw shadows into it
+const CASTER_MARGIN: f32 = 50.0;
+
+/// Light throwing shadows, with the matrices of the shadow map layers rendered for it
+pub struct ShadowCaster {
+    /// Index of the light in the lights of the frame
+    pub light: usize,
+    /// World to shadow map clip space of each layer
+    pub matrices: Vec<uv::Mat4>,
+}
+
+impl ShadowCaster {
+    /// Cascades of a directional light shining along `direction`, fitted to the view up to `max_distance` from the camera
+    ///
+    /// Each cascade only moves in whole shadow map texels so its shadows don't shimmer as the camera moves.
+    pub fn directional(light: usize, direction: uv::Vec3, view: &uv::Mat4, proj: &uv::Mat4, max_distance: f32) -> ShadowCaster {
+        let direction = direction.normalized();
+        let inverse = (*proj * *view).inversed();
+        let corner = |x: f32, y: f32, z: f32| {
+            let corner = inverse * uv::Vec4::new(x, y, z, 1.0);
+            corner.truncated() / corner.w
+        };
+        let edges = [(-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)]
+            .iter()
+            .map(|&(x, y)| (corner(x, y, 0.0), corner(x, y, 1.0)))
+            .collect::<Vec<_>>();
+
+        // Depth along the view direction is linear along each edge of the frustum, perspective or not
+        let depth = |point: uv::Vec3| -view.transform_point3(point).z;
+        let near = depth(edges[0].0);
+        let far = depth(edges[0].1);
+        let shadow_far = far.min(near + max_distance);
+        let split = |cascade: usize| {
+            let fraction = cascade as f32 / SHADOW_CASCADES as f32;
+            let uniform = near + (shadow_far - near) * fraction;
+            if near > 0.0 {
+                let logarithmic = near * (shadow_far / near).powf(fraction);
+                logarithmic * SPLIT_LAMBDA + uniform * (1.0 - SPLIT_LAMBDA)
+            } else {
+                uniform
+            }
+        };
+        let at_depth = |edge: &(uv::Vec3, uv::Vec3), split_depth: f32| edge.0 + (edge.1 - edge.0) * ((split_depth - near) / (far - near));
+
+        let up = if direction.y.abs() > 0.99 { uv::Vec3::unit_x() } else { uv::Vec3::unit_y() };
+        let matrices = (0..SHADOW_CASCADES)
+            .map(|cascade| {
+                let (start, end) = (split(cascade), split(cascade + 1));
+                let corners = edges.iter().flat_map(|edge| vec![at_depth(edge, start), at_depth(edge, end)]).collect::<Vec<_>>();
+
+                // A bounding sphere keeps the cascade's size fixed as the camera turns
+                let center = corners.iter().fold(uv::Vec3::zero(), |sum, &corner| sum + corner) / corners.len() as f32;
+                let radius = corners.iter().map(|&corner| (corner - center).mag()).fold(0.0, f32::max);
+                let radius = (radius * 16.0).ceil() / 16.0;
+
+                let light_view = uv::Mat4::look_at(center, center + direction, up);
+                let light_proj = uv::projection::orthographic_vk(-radius, radius, -radius, radius, -radius - CASTER_MARGIN, radius);
+                let matrix = light_proj * light_view;
+
+                let half_size = SHADOW_MAP_SIZE as f32 / 2.0;
+                let origin = matrix.transform_point3(uv::Vec3::zero());
+                let snap = |coordinate: f32| (coordinate * half_size).round() / half_size - coordinate;
+                uv::Mat4::from_translation(uv::Vec3::new(snap(origin.x), snap(origin.y), 0.0)) * matrix
+            })
+            .collect();
+
+        ShadowCaster { light, matrices }
+    }
+
+    /// Single layer of a spot light at `position` shining along `direction`, covering its cone up to `range`
+    pub fn spot(light: usize, position: uv::Vec3, direction: uv::Vec3, outer_angle: f32, range: f32) -> ShadowCaster {
+        let direction = direction.normalized();
+        let up = if direction.y.abs() > 0.99 { uv::Vec3::unit_x() } else { uv::Vec3::unit_y() };
+        let fov = (outer_angle * 2.0).min(170f32.to_radians());
+        let near = (range * 0.01).max(0.05);
+
+        let light_view = uv::Mat4::look_at(position, position + direction, up);
+        let light_proj = uv::projection::perspective_vk(fov, 1.0, near, range.max(near * 2.0));
+
+        ShadowCaster {
+            light,
+            matrices: vec![light_proj * light_view],
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::Arc;
+
+use ash::vk;
+
+use super::{
+    images::RenderTexture,
+    materials::{BlendMode, RenderState, ShaderPair},
+    models::InstanceBatch,
+};
+
+/// Rectangle of the target covered by a view, as fractions of the target size with the origin at the top left
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        Viewport { x, y, width, height }
+    }
+
+    /// Width over height of the rectangle once mapped onto a target of `extent`
+    pub fn aspect(&self, extent: &vk::Extent2D) -> f32 {
+        (self.width * extent.width as f32) / (self.height * extent.height as f32)
+    }
+
+    /// Pixels covered in a target of `extent`, clamped to the target
+    pub fn rect(&self, extent: &vk::Extent2D) -> vk::Rect2D {
+        let to_pixels = |fraction: f32, size: u32| ((fraction.clamp(0.0, 1.0) * size as f32).round() as u32).min(size);
+        let (left, top) = (to_pixels(self.x, extent.width), to_pixels(self.y, extent.height));
+        let (right, bottom) = (to_pixels(self.x + self.width, extent.width), to_pixels(self.y + self.height, extent.height));
+
+        vk::Rect2D::builder()
+            .offset(vk::Offset2D { x: left as i32, y: top as i32 })
+            .extent(vk::Extent2D {
+                width: right.saturating_sub(left),
+                height: bottom.saturating_sub(top),
+            })
+            .build()
+    }
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport::new(0.0, 0.0, 1.0, 1.0)
+    }
+}
+
+/// One camera's draw into the frame or into a render texture, with the batches it shows
+pub struct RenderView {
+    /// Drawn into the frame when `None`
+    pub target: Option<Arc<RenderTexture>>,
+    pub viewport: Viewport,
+    pub view: uv::Mat4,
+    pub proj: uv::Mat4,
+    pub batches: Vec<InstanceBatch>,
+    /// Instances left out of `batches` because they are outside the view's frustum
+    pub culled: usize,
+}
+
+/// Instances drawn or culled outside the cameras' frustums in the last frame, summed over the views which were drawn
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RenderStats {
+    pub drawn: usize,
+    pub culled: usize,
+}
+
+/// Volume seen through a view projection, as six planes whose normals point inside
+#[derive(Copy, Clone, Debug)]
+pub struct Frustum {
+    /// Normal in `xyz` and distance from the origin in `w`, normalized
+    planes: [uv::Vec4; 6],
+}
+
+impl Frustum {
+    /// Planes of the clip volume of `view_proj`, using Vulkan's depth range from 0 to 1
+    pub fn new(view_proj: &uv::Mat4) -> Frustum {
+        let row = |index: usize| uv::Vec4::new(view_proj.cols[0][index], view_proj.cols[1][index], view_proj.cols[2][index], view_proj.cols[3][index]);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        let planes = [w + x, w - x, w + y, w - y, z, w - z];
+        Frustum {
+            planes: planes.map(|plane| plane / plane.xyz().mag()),
+        }
+    }
+
+    /// Whether any part of the sphere may be inside, spheres next to the frustum's corners are kept too
+    pub fn intersects_sphere(&self, center: uv::Vec3, radius: f32) -> bool {
+        self.planes.iter().all(|plane| plane.xyz().dot(center) + plane.w >= -radius)
+    }
+}
+
+/// How the batches of every view are shaded, the modes other than `Shaded` replace the materials to debug the scene
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ViewMode {
+    Shaded,
+    /// Edges of the triangles with their material's shading, filled when the device can't draw lines
+    Wireframe,
+    /// World space normals as colors
+    Normals,
+    /// Checkerboard laid out by the texture coordinates
+    UvChecker,
+    /// Distance from the camera, white up close
+    Depth,
+    /// Brighter where more surfaces are drawn over each other, hidden ones included
+    Overdraw,
+}
+
+impl Default for ViewMode {
+    fn default() -> Self {
+        ViewMode::Shaded
+    }
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 6] = [ViewMode::Shaded, ViewMode::Wireframe, ViewMode::Normals, ViewMode::UvChecker, ViewMode::Depth, ViewMode::Overdraw];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ViewMode::Shaded => "Shaded",
+            ViewMode::Wireframe => "Wireframe",
+            ViewMode::Normals => "Normals",
+            ViewMode::UvChecker => "UV checker",
+            ViewMode::Depth => "Depth",
+            ViewMode::Overdraw => "Overdraw",
+        }
+    }
+
+    /// Shaders and render state a material is drawn with in this mode, `None` when it keeps its own
+    pub fn replace(self, shaders: &ShaderPair, state: &RenderState) -> Option<(ShaderPair, RenderState)> {
+        let debug_shaders = |fragment: &str| ShaderPair {
+            vertex: "view_mode".to_string(),
+            fragment: fragment.to_string(),
+        };
+        // Debug shading is never blended, except for overdraw which adds up all layers
+        let opaque = RenderState {
+            blend: BlendMode::Opaque,
+            depth_write: true,
+            ..*state
+        };
+
+        match self {
+            ViewMode::Shaded => None,
+            ViewMode::Wireframe => Some((
+                shaders.clone(),
+                RenderState {
+                    cull_mode: vk::CullModeFlags::NONE,
+                    polygon_mode: vk::PolygonMode::LINE,
+                    ..*state
+                },
+            )),
+            ViewMode::Normals => Some((debug_shaders("view_normals"), opaque)),
+            ViewMode::UvChecker => Some((debug_shaders("view_uv_checker"), opaque)),
+            ViewMode::Depth => Some((debug_shaders("view_depth"), opaque)),
+            ViewMode::Overdraw => Some((
+                debug_shaders("view_overdraw"),
+                RenderState {
+                    blend: BlendMode::Additive,
+                    depth_test: false,
+                    depth_write: false,
+                    ..*state
+                },
+            )),
+        }
+    }
+}
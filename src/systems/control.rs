@@ -41,6 +41,12 @@ impl ControlSystem {
     }
 }
 
+impl Default for ControlSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ControlSystem {
     fn process_dir_key(&self, dir: Direction) -> f32 {
         let (forward, backward) = dir.keys();
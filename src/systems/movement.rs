@@ -17,6 +17,12 @@ impl MoveSystem {
     }
 }
 
+impl Default for MoveSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> System<'a> for MoveSystem {
     type SystemData = (WriteStorage<'a, Transform>, ReadStorage<'a, Movement>);
 
@@ -1,4 +1,6 @@
-use specs::{Join, Read, ReadStorage, System, Write};
+use std::{collections::HashSet, sync::Mutex};
+
+use specs::{Entities, Entity, Join, Read, ReadStorage, System, Write};
 
 use imgui::*;
 use imgui_winit_support::{HiDpiMode, WinitPlatform};
@@ -7,27 +9,44 @@ use winit::{
     window::Window,
 };
 
-use crate::{render::GraphicContext, ControlData, DeltaTime, MouseState, Player, Renderable, Transform, WinitEventData};
+use crate::{
+    debug_draw::{DebugDraw, DebugLabel},
+    render::{
+        models::{InstanceBatch, InstanceData, Vertex},
+        Frustum, GraphicContext, GridPlane, LightData, RenderStats, RenderView, ShadowCaster, ViewMode,
+    },
+    Camera, ControlData, DeltaTime, FrameCapture, Light, LightKind, MouseState, Parent, Player, RenderSettings, Renderable, Transform, WinitEventData,
+};
+
+/// Parents followed up from an entity before its `Parent` links are assumed to form a cycle
+const MAX_HIERARCHY_DEPTH: usize = 256;
 
 pub struct RenderSystem {
     graphic_context: GraphicContext,
     imgui: Context,
     platform: WinitPlatform,
     imgui_renderer: imgui_rs_vulkan_renderer::Renderer,
-    window: Window,
+    window: Option<Window>,
     window_focused: bool,
+    /// Entities whose parents form a cycle, which would be found again every frame so each is only reported once
+    reported_cycles: Mutex<HashSet<Entity>>,
 
     curr_image_index: usize,
-
-    camera_pos: uv::Vec3,
-    camera_dir: uv::Vec3,
-    camera_up: uv::Vec3,
 }
 
 impl RenderSystem {
     pub fn new(window: Window, graphic_context: GraphicContext) -> Self {
-        let (mut imgui, platform) = Self::configure_imgui(&window);
-        let imgui_renderer = imgui_rs_vulkan_renderer::Renderer::new(&graphic_context, 2, *graphic_context.get_render_pass(), &mut imgui).unwrap();
+        Self::with_window(Some(window), graphic_context)
+    }
+
+    /// Renders without a window, reading every frame back into the `FrameCapture` resource
+    pub fn headless(graphic_context: GraphicContext) -> Self {
+        Self::with_window(None, graphic_context)
+    }
+
+    fn with_window(window: Option<Window>, graphic_context: GraphicContext) -> Self {
+        let (mut imgui, platform) = Self::configure_imgui(window.as_ref(), &graphic_context);
+        let imgui_renderer = imgui_rs_vulkan_renderer::Renderer::new(&graphic_context, 2, *graphic_context.get_overlay_render_pass(), &mut imgui).unwrap();
 
         RenderSystem {
             graphic_context,
@@ -36,17 +55,21 @@ impl RenderSystem {
             imgui_renderer,
             window,
             window_focused: true,
+            reported_cycles: Mutex::new(HashSet::new()),
             curr_image_index: 0,
-            camera_pos: uv::Vec3::default(),
-            camera_dir: uv::Vec3::default(),
-            camera_up: uv::Vec3::default(),
         }
     }
 
-    fn configure_imgui(window: &Window) -> (Context, WinitPlatform) {
+    fn configure_imgui(window: Option<&Window>, graphic_context: &GraphicContext) -> (Context, WinitPlatform) {
         let mut imgui = Context::create();
         let mut platform = WinitPlatform::init(&mut imgui);
-        platform.attach_window(imgui.io_mut(), window, HiDpiMode::Default);
+        match window {
+            Some(window) => platform.attach_window(imgui.io_mut(), window, HiDpiMode::Default),
+            None => {
+                let extent = graphic_context.extent();
+                imgui.io_mut().display_size = [extent.width as f32, extent.height as f32];
+            }
+        }
 
         let hidpi_factor = platform.hidpi_factor();
         let font_size = (13.0 * hidpi_factor) as f32;
@@ -57,14 +80,24 @@ impl RenderSystem {
             }),
         }]);
         imgui.io_mut().font_global_scale = (1.0 / hidpi_factor) as f32;
+
+        // imgui's style is picked in sRGB, which an sRGB target would otherwise encode a second time
+        if graphic_context.is_srgb_target() {
+            for color in imgui.style_mut().colors.iter_mut() {
+                let [r, g, b] = srgb_to_linear([color[0], color[1], color[2]]);
+                *color = [r, g, b, color[3]];
+            }
+        }
         (imgui, platform)
     }
 
     fn begin_frame(&mut self) -> bool {
         self.graphic_context.sync_objects.wait_fence_current();
 
-        if !self.graphic_context.get_window().is_window_visible() {
-            return false;
+        if let Some(window) = self.graphic_context.get_window() {
+            if !window.is_window_visible() {
+                return false;
+            }
         }
 
         self.curr_image_index = match self.graphic_context.acquire_next_image() {
@@ -75,8 +108,6 @@ impl RenderSystem {
             Ok(i) => i,
         };
 
-        self.graphic_context.update_uniforms(self.curr_image_index, &self.camera_pos, &self.camera_dir, &self.camera_up);
-
         self.graphic_context.sync_objects.wait_fence_image(self.curr_image_index);
 
         self.graphic_context.begin_command_buffer(self.curr_image_index);
@@ -97,13 +128,17 @@ impl RenderSystem {
 
     fn update_imgui(&mut self, delta_time: &DeltaTime, event_storage: &WinitEventData) {
         self.imgui.io_mut().update_delta_time(delta_time.delta);
-        for event in &event_storage.events {
-            self.platform.handle_event(self.imgui.io_mut(), &self.window, event);
+        if let Some(window) = &self.window {
+            for event in &event_storage.events {
+                self.platform.handle_event(self.imgui.io_mut(), window, event);
+            }
         }
     }
 
-    fn draw_imgui(&mut self, delta_time: &DeltaTime, player_pos: &uv::Vec3, draw_mouse: bool) {
+    /// `labels` are placed at fractions of the frame's width and height
+    fn draw_imgui(&mut self, delta_time: &DeltaTime, player_pos: &uv::Vec3, draw_mouse: bool, settings: &mut RenderSettings, stats: &RenderStats, labels: &[([f32; 2], &DebugLabel)]) {
         let fps = self.imgui.io().framerate;
+        let wireframe_supported = self.graphic_context.supports_wireframe();
         let ui = self.imgui.frame();
 
         if draw_mouse {
@@ -112,12 +147,24 @@ impl RenderSystem {
             ui.set_mouse_cursor(None);
         }
 
+        ui.main_menu_bar(|| {
+            ui.menu(im_str!("View"), true, || {
+                for mode in ViewMode::ALL.iter() {
+                    let enabled = *mode != ViewMode::Wireframe || wireframe_supported;
+                    if MenuItem::new(&ImString::new(mode.name())).selected(settings.view_mode == *mode).enabled(enabled).build(&ui) {
+                        settings.view_mode = *mode;
+                    }
+                }
+            });
+        });
+
         imgui::Window::new(im_str!("Hello world")).build(&ui, || {
             ui.text(im_str!("Hello world!"));
             ui.separator();
             ui.text(format!("Running for: {:.3} seconds", delta_time.start_time.elapsed().as_secs_f32()));
             ui.text(format!("Player position: {:.2?}", player_pos));
             ui.text(format!("Average {:.3} ms/frame ({:.1} FPS)", 1000f32 / fps, fps));
+            ui.text(format!("Drawn: {}, culled: {}", stats.drawn, stats.culled));
             let mouse_pos = ui.io().mouse_pos;
             ui.text(format!("Mouse Position: ({:.1},{:.1})", mouse_pos[0], mouse_pos[1]));
         });
@@ -128,7 +175,45 @@ impl RenderSystem {
             ui.text(im_str!("Mouse Mode Toggle: TAB"));
         });
 
-        self.platform.prepare_render(&ui, &self.window);
+        // Changes apply from the next frame, the post passes of this one are already recorded
+        imgui::Window::new(im_str!("Post Processing")).build(&ui, || {
+            Slider::new(im_str!("Exposure")).range(0.0..=4.0).build(&ui, &mut settings.post.exposure);
+            ui.checkbox(im_str!("Tonemapping"), &mut settings.post.tonemapping);
+            ui.checkbox(im_str!("Bloom"), &mut settings.post.bloom);
+            Slider::new(im_str!("Bloom threshold")).range(0.0..=4.0).build(&ui, &mut settings.post.bloom_threshold);
+            Slider::new(im_str!("Bloom intensity")).range(0.0..=2.0).build(&ui, &mut settings.post.bloom_intensity);
+            ui.checkbox(im_str!("Vignette"), &mut settings.post.vignette);
+            Slider::new(im_str!("Vignette strength")).range(0.0..=1.0).build(&ui, &mut settings.post.vignette_strength);
+            ui.checkbox(im_str!("Color grading"), &mut settings.post.color_grading);
+            ui.checkbox(im_str!("FXAA"), &mut settings.post.fxaa);
+        });
+
+        imgui::Window::new(im_str!("Grid")).build(&ui, || {
+            ui.checkbox(im_str!("Show grid"), &mut settings.grid.enabled);
+            ui.radio_button(im_str!("XY"), &mut settings.grid.plane, GridPlane::XY);
+            ui.same_line(0.0);
+            ui.radio_button(im_str!("XZ"), &mut settings.grid.plane, GridPlane::XZ);
+            ui.same_line(0.0);
+            ui.radio_button(im_str!("YZ"), &mut settings.grid.plane, GridPlane::YZ);
+            Slider::new(im_str!("Spacing")).range(0.1..=10.0).build(&ui, &mut settings.grid.spacing);
+            Slider::new(im_str!("Major every")).range(1..=20).build(&ui, &mut settings.grid.major_every);
+            Slider::new(im_str!("Fade distance")).range(10.0..=500.0).build(&ui, &mut settings.grid.fade_distance);
+        });
+
+        // Behind the windows, the draw list has to be dropped before the frame is rendered
+        {
+            let display_size = ui.io().display_size;
+            let srgb_target = self.graphic_context.is_srgb_target();
+            let draw_list = ui.get_background_draw_list();
+            for (position, label) in labels {
+                let color = if srgb_target { srgb_to_linear(label.color) } else { label.color };
+                draw_list.add_text([position[0] * display_size[0], position[1] * display_size[1]], color, &label.text);
+            }
+        }
+
+        if let Some(window) = &self.window {
+            self.platform.prepare_render(&ui, window);
+        }
         let draw_data = ui.render();
 
         self.imgui_renderer
@@ -140,27 +225,56 @@ impl RenderSystem {
 impl<'a> System<'a> for RenderSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = (
+        Entities<'a>,
         Read<'a, WinitEventData>,
         Read<'a, DeltaTime>,
         Write<'a, ControlData>,
+        Write<'a, FrameCapture>,
+        Write<'a, RenderSettings>,
+        Write<'a, DebugDraw>,
+        Write<'a, RenderStats>,
         ReadStorage<'a, Player>,
+        ReadStorage<'a, Camera>,
+        ReadStorage<'a, Light>,
         ReadStorage<'a, Transform>,
+        ReadStorage<'a, Parent>,
         ReadStorage<'a, Renderable>,
     );
 
-    fn run(&mut self, (events_storage, delta_time, mut control_data, player_storage, transform_storage, render_storage): Self::SystemData) {
+    fn run(
+        &mut self,
+        (
+            entities,
+            events_storage,
+            delta_time,
+            mut control_data,
+            mut frame_capture,
+            mut render_settings,
+            mut debug_draw,
+            mut render_stats,
+            player_storage,
+            camera_storage,
+            light_storage,
+            transform_storage,
+            parent_storage,
+            render_storage,
+        ): Self::SystemData,
+    ) {
         let mut player_pos = uv::Vec3::default();
-        let mut player_dir = uv::Rotor3::default();
-
         for (_, transform) in (&player_storage, &transform_storage).join() {
             player_pos = transform.pos;
-            player_dir = transform.dir.normalized();
         }
 
         for event in &events_storage.events {
             if let Event::WindowEvent { window_id: _, event } = event {
-                if let WindowEvent::Focused(focused) = event {
-                    self.window_focused = *focused;
+                match event {
+                    WindowEvent::Focused(focused) => self.window_focused = *focused,
+                    WindowEvent::Resized(size) => {
+                        if let Some(window) = self.graphic_context.get_window() {
+                            window.set_reported_size((*size).into());
+                        }
+                    }
+                    _ => (),
                 }
             };
         }
@@ -172,32 +286,230 @@ impl<'a> System<'a> for RenderSystem {
             _ => true,
         };
 
-        self.window.set_cursor_visible(draw_mouse);
+        if let Some(window) = &self.window {
+            window.set_cursor_visible(draw_mouse);
 
-        if control_data.set_mouse {
-            let pos = winit::dpi::PhysicalPosition::new(control_data.last_mouse_pos.0, control_data.last_mouse_pos.1);
-            self.window.set_cursor_position(pos).unwrap();
-            control_data.set_mouse = false;
+            if control_data.set_mouse {
+                let pos = winit::dpi::PhysicalPosition::new(control_data.last_mouse_pos.0, control_data.last_mouse_pos.1);
+                window.set_cursor_position(pos).unwrap();
+                control_data.set_mouse = false;
+            }
         }
 
-        self.camera_pos = player_pos;
-        let mut camera_vecs = [uv::Vec3::new(0.0, 0.0, 1.0), uv::Vec3::new(1.0, 0.0, 0.0)];
-        player_dir.rotate_vecs(&mut camera_vecs);
-        self.camera_dir = camera_vecs[0];
-        self.camera_up = camera_vecs[0].cross(camera_vecs[1]);
-
+        self.graphic_context.set_msaa_samples(render_settings.msaa_samples);
+        self.graphic_context.set_environment(render_settings.environment.as_ref());
+        self.graphic_context.set_background(&render_settings.background);
+        self.graphic_context.set_post_settings(&render_settings.post);
+        self.graphic_context.set_grid_settings(&render_settings.grid);
+        self.graphic_context.set_view_mode(render_settings.view_mode);
         if self.begin_frame() {
-            for renderable in render_storage.join() {
-                renderable
-                    .mesh
-                    .render(self.graphic_context.get_device(), self.graphic_context.get_command_buffer(self.curr_image_index));
+            let mut cameras = (&entities, &camera_storage).join().filter(|(_, camera)| camera.active).collect::<Vec<_>>();
+            cameras.sort_by_key(|(_, camera)| camera.order);
+            let reported_cycles = &self.reported_cycles;
+
+            let instances = (&entities, &render_storage)
+                .join()
+                .map(|(entity, renderable)| {
+                    let instance = InstanceData {
+                        model: world_matrix(entity, &transform_storage, &parent_storage, reported_cycles),
+                        color: renderable.color,
+                    };
+                    let sphere = renderable.mesh.bounds().sphere(&instance.model);
+                    (renderable, instance, sphere)
+                })
+                .collect::<Vec<_>>();
+            let default_material = self.graphic_context.default_material().clone();
+            let batch_instances = |layer_mask: u32, frustum: Option<&Frustum>| {
+                InstanceBatch::group(
+                    instances
+                        .iter()
+                        .filter(|(renderable, _, _)| renderable.layers & layer_mask != 0)
+                        .filter(|(_, _, (center, radius))| match frustum {
+                            Some(frustum) => frustum.intersects_sphere(*center, *radius),
+                            None => true,
+                        })
+                        .map(|(renderable, instance, _)| (&renderable.mesh, renderable.material.as_ref().unwrap_or(&default_material), *instance)),
+                )
+            };
+            // Each camera only draws the renderables inside its frustum, returns the batches along with the culled count
+            let view_batches = |layer_mask: u32, view: &uv::Mat4, proj: &uv::Mat4| {
+                let batches = batch_instances(layer_mask, Some(&Frustum::new(&(*proj * *view))));
+                let kept = batches.iter().map(|batch| batch.instances.len()).sum::<usize>();
+                let shown = instances.iter().filter(|(renderable, _, _)| renderable.layers & layer_mask != 0).count();
+                (batches, shown - kept)
+            };
+
+            let extent = *self.graphic_context.extent();
+            let mut views = cameras
+                .iter()
+                .map(|(entity, camera)| {
+                    let target_extent = camera.target.as_ref().map_or(extent, |target| *target.extent());
+                    let view = view_matrix(&world_matrix(*entity, &transform_storage, &parent_storage, reported_cycles));
+                    let proj = camera.projection_matrix(camera.viewport.aspect(&target_extent));
+                    let (batches, culled) = view_batches(camera.layer_mask, &view, &proj);
+                    RenderView {
+                        target: camera.target.clone(),
+                        viewport: camera.viewport,
+                        view,
+                        proj,
+                        batches,
+                        culled,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            // Without an active camera the world is still drawn from the origin
+            if views.is_empty() {
+                let camera = Camera::default();
+                let view = view_matrix(&uv::Mat4::identity());
+                let proj = camera.projection_matrix(camera.viewport.aspect(&extent));
+                let (batches, culled) = view_batches(camera.layer_mask, &view, &proj);
+                views.push(RenderView {
+                    target: None,
+                    viewport: camera.viewport,
+                    view,
+                    proj,
+                    batches,
+                    culled,
+                });
+            }
+
+            // Shadows are fitted to the first view drawn into the frame
+            let main_view = views.iter().find(|view| view.target.is_none()).unwrap_or(&views[0]);
+            let mut lights = Vec::new();
+            let mut casters = Vec::new();
+            for (entity, light) in (&entities, &light_storage).join() {
+                let light_matrix = world_matrix(entity, &transform_storage, &parent_storage, reported_cycles);
+                if light.cast_shadows {
+                    if let Some(caster) = shadow_caster(lights.len(), light, &light_matrix, main_view, render_settings.shadow_distance) {
+                        casters.push(caster);
+                    }
+                }
+                lights.push(light_data(light, &light_matrix));
+            }
+            self.graphic_context.update_lights(self.curr_image_index, render_settings.ambient_light, &lights, &casters);
+
+            let line_vertices = debug_draw
+                .lines()
+                .iter()
+                .flat_map(|line| {
+                    let vertex = |pos: uv::Vec3| Vertex {
+                        pos: pos.into(),
+                        color: line.color,
+                        ..Vertex::default()
+                    };
+                    vec![vertex(line.from), vertex(line.to)]
+                })
+                .collect::<Vec<_>>();
+            self.graphic_context.set_debug_lines(self.curr_image_index, &line_vertices);
+
+            // Shadow casters outside the cameras' frustums still throw shadows into them
+            *render_stats = self.graphic_context.draw_views(self.curr_image_index, &views, &batch_instances(!0, None));
+            self.graphic_context.begin_overlay(self.curr_image_index);
+            if render_settings.show_ui {
+                let labels = project_labels(debug_draw.labels(), main_view);
+                self.draw_imgui(&delta_time, &player_pos, draw_mouse, &mut render_settings, &render_stats, &labels);
             }
-            self.draw_imgui(&delta_time, &player_pos, draw_mouse);
             self.end_frame();
+
+            if let Some(pixels) = self.graphic_context.read_frame() {
+                let extent = self.graphic_context.extent();
+                frame_capture.width = extent.width;
+                frame_capture.height = extent.height;
+                frame_capture.pixels = pixels;
+            }
+        }
+
+        // Frames skipped while the swapchain is recreated still use up the shapes' lifetimes
+        debug_draw.expire(delta_time.delta.as_secs_f32());
+    }
+}
+
+/// Where each label in front of the camera of `view` is seen, as fractions of the frame's width and height
+fn project_labels<'a>(labels: &'a [DebugLabel], view: &RenderView) -> Vec<([f32; 2], &'a DebugLabel)> {
+    let view_proj = view.proj * view.view;
+    labels
+        .iter()
+        .filter_map(|label| {
+            let clip = view_proj * label.position.into_homogeneous_point();
+            if clip.w <= 0.0 {
+                return None;
+            }
+            let (x, y) = (clip.x / clip.w * 0.5 + 0.5, clip.y / clip.w * 0.5 + 0.5);
+            if !(0.0..=1.0).contains(&x) || !(0.0..=1.0).contains(&y) {
+                return None;
+            }
+            let viewport = &view.viewport;
+            Some(([viewport.x + x * viewport.width, viewport.y + y * viewport.height], label))
+        })
+        .collect()
+}
+
+/// Model matrix of `entity` combined with those of all its parents
+///
+/// Parent links forming a cycle are followed up to `MAX_HIERARCHY_DEPTH` parents, and reported once per entity.
+fn world_matrix(entity: Entity, transform_storage: &ReadStorage<Transform>, parent_storage: &ReadStorage<Parent>, reported_cycles: &Mutex<HashSet<Entity>>) -> uv::Mat4 {
+    let mut matrix = uv::Mat4::identity();
+    let mut current = Some(entity);
+    for _ in 0..=MAX_HIERARCHY_DEPTH {
+        let entity = match current {
+            Some(entity) => entity,
+            None => return matrix,
+        };
+        if let Some(transform) = transform_storage.get(entity) {
+            matrix = transform.model_matrix() * matrix;
         }
+        current = parent_storage.get(entity).map(|parent| parent.entity);
+    }
+
+    if current.is_some() && reported_cycles.lock().unwrap().insert(entity) {
+        warn!("Parents of {:?} go deeper than {} entities, their Parent links probably form a cycle", entity, MAX_HIERARCHY_DEPTH);
+    }
+    matrix
+}
+
+/// Light placed at the origin of `light_matrix`, shining along its local z axis
+fn light_data(light: &Light, light_matrix: &uv::Mat4) -> LightData {
+    let position = light_matrix.transform_point3(uv::Vec3::zero());
+    let direction = light_matrix.transform_vec3(uv::Vec3::unit_z());
+    match light.kind {
+        LightKind::Directional => LightData::directional(direction, light.color, light.intensity),
+        LightKind::Point { range } => LightData::point(position, range, light.color, light.intensity),
+        LightKind::Spot { range, inner_angle, outer_angle } => LightData::spot(position, direction, range, inner_angle, outer_angle, light.color, light.intensity),
+    }
+}
+
+/// Shadow layers of a light throwing shadows into `view`, point lights don't throw any
+fn shadow_caster(index: usize, light: &Light, light_matrix: &uv::Mat4, view: &RenderView, shadow_distance: f32) -> Option<ShadowCaster> {
+    let position = light_matrix.transform_point3(uv::Vec3::zero());
+    let direction = light_matrix.transform_vec3(uv::Vec3::unit_z());
+    match light.kind {
+        LightKind::Directional => Some(ShadowCaster::directional(index, direction, &view.view, &view.proj, shadow_distance)),
+        LightKind::Point { .. } => None,
+        LightKind::Spot { range, outer_angle, .. } => Some(ShadowCaster::spot(index, position, direction, outer_angle, range)),
     }
 }
 
+/// Looks along the camera's local z axis with its local y axis pointing up, ignoring scale
+/// Decodes an sRGB color to the linear one the target encodes back when the overlay writes it
+fn srgb_to_linear(color: [f32; 3]) -> [f32; 3] {
+    let decode = |channel: f32| {
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    [decode(color[0]), decode(color[1]), decode(color[2])]
+}
+
+fn view_matrix(camera_matrix: &uv::Mat4) -> uv::Mat4 {
+    let eye = camera_matrix.transform_point3(uv::Vec3::zero());
+    let dir = camera_matrix.transform_vec3(uv::Vec3::unit_z()).normalized();
+    let right = camera_matrix.transform_vec3(uv::Vec3::unit_x()).normalized();
+    uv::Mat4::look_at(eye, eye + dir, dir.cross(right))
+}
+
 impl Drop for RenderSystem {
     fn drop(&mut self) {
         trace!("Dropping Renderer");
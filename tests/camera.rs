@@ -0,0 +1,73 @@
+mod common;
+
+use ash::vk;
+use common::assert_near;
+use ultraviolet as uv;
+use voyager::{
+    components::{Camera, Projection},
+    render::Viewport,
+};
+
+/// Normalized device coordinates of a point in view space, which looks down -z
+fn project(proj: &uv::Mat4, point: uv::Vec3) -> uv::Vec3 {
+    let clip = *proj * point.into_homogeneous_point();
+    clip.xyz() / clip.w
+}
+
+#[test]
+fn perspective_maps_near_and_far_to_vulkan_depth() {
+    let camera = Camera {
+        near: 0.5,
+        far: 50.0,
+        ..Camera::default()
+    };
+    let proj = camera.projection_matrix(2.0);
+
+    assert!(project(&proj, uv::Vec3::new(0.0, 0.0, -0.5)).z.abs() < 1e-5);
+    assert!((project(&proj, uv::Vec3::new(0.0, 0.0, -50.0)).z - 1.0).abs() < 1e-5);
+
+    // The top edge of the view is half the field of view above the view direction
+    let fov_y = match camera.projection {
+        Projection::Perspective { fov_y } => fov_y,
+        Projection::Orthographic { .. } => unreachable!(),
+    };
+    let top = project(&proj, uv::Vec3::new(0.0, (fov_y / 2.0).tan() * 10.0, -10.0));
+    assert!((top.y.abs() - 1.0).abs() < 1e-4);
+    // The aspect widens the view, the right edge is twice as far out as the top one
+    let right = project(&proj, uv::Vec3::new((fov_y / 2.0).tan() * 20.0, 0.0, -10.0));
+    assert!((right.x - 1.0).abs() < 1e-4);
+}
+
+#[test]
+fn orthographic_shows_its_height_at_any_distance() {
+    let camera = Camera {
+        projection: Projection::Orthographic { height: 10.0 },
+        near: 1.0,
+        far: 11.0,
+        ..Camera::default()
+    };
+    let proj = camera.projection_matrix(1.5);
+
+    for distance in [1.0f32, 6.0, 11.0].iter() {
+        let corner = project(&proj, uv::Vec3::new(7.5, 5.0, -distance));
+        assert!((corner.x - 1.0).abs() < 1e-5);
+        assert!((corner.y.abs() - 1.0).abs() < 1e-5);
+    }
+    assert_near(project(&proj, uv::Vec3::new(0.0, 0.0, -6.0)), uv::Vec3::new(0.0, 0.0, 0.5));
+}
+
+#[test]
+fn viewport_rect_covers_its_fraction_of_the_target() {
+    let extent = vk::Extent2D { width: 320, height: 240 };
+
+    let right_half = Viewport::new(0.5, 0.0, 0.5, 1.0).rect(&extent);
+    assert_eq!((right_half.offset.x, right_half.offset.y), (160, 0));
+    assert_eq!((right_half.extent.width, right_half.extent.height), (160, 240));
+    assert!((Viewport::new(0.5, 0.0, 0.5, 1.0).aspect(&extent) - 160.0 / 240.0).abs() < 1e-6);
+
+    // Clamped to the target, viewports outside of it cover no pixels
+    let overflowing = Viewport::new(0.75, 0.75, 0.5, 0.5).rect(&extent);
+    assert_eq!((overflowing.extent.width, overflowing.extent.height), (80, 60));
+    let outside = Viewport::new(1.5, 0.0, 0.5, 1.0).rect(&extent);
+    assert_eq!(outside.extent.width, 0);
+}
@@ -0,0 +1,5 @@
+use ultraviolet as uv;
+
+pub fn assert_near(actual: uv::Vec3, expected: uv::Vec3) {
+    assert!((actual - expected).mag() < 1e-5, "{:?} is not {:?}", actual, expected);
+}
@@ -0,0 +1,46 @@
+use ultraviolet as uv;
+use voyager::render::{
+    models::{Bounds, Vertex},
+    Frustum,
+};
+
+fn vertex(x: f32, y: f32, z: f32) -> Vertex {
+    Vertex { pos: [x, y, z], ..Vertex::default() }
+}
+
+#[test]
+fn bounds_enclose_the_vertices() {
+    let vertices = [vertex(1.0, 0.0, 0.0), vertex(3.0, 0.0, 0.0), vertex(2.0, 1.0, -1.0), vertex(2.0, -1.0, 1.0)];
+    let bounds = Bounds::from_vertices(&vertices);
+    assert_eq!(bounds.min, uv::Vec3::new(1.0, -1.0, -1.0));
+    assert_eq!(bounds.max, uv::Vec3::new(3.0, 1.0, 1.0));
+    assert_eq!(bounds.center, uv::Vec3::new(2.0, 0.0, 0.0));
+    // The vertices don't reach the box's corners so the sphere is smaller than the box's diagonal
+    assert!((bounds.radius - 2.0_f32.sqrt()).abs() < 1e-6);
+
+    // Moved and scaled along with the model, by its largest scale
+    let model = uv::Mat4::from_translation(uv::Vec3::new(0.0, 5.0, 0.0)) * uv::Mat4::from_nonuniform_scale(uv::Vec3::new(1.0, 3.0, 2.0));
+    let (center, radius) = bounds.sphere(&model);
+    assert!((center - uv::Vec3::new(2.0, 5.0, 0.0)).mag() < 1e-6);
+    assert!((radius - 3.0 * 2.0_f32.sqrt()).abs() < 1e-5);
+
+    let empty = Bounds::from_vertices(&[]);
+    assert_eq!(empty.radius, 0.0);
+}
+
+#[test]
+fn frustum_keeps_spheres_in_view() {
+    // Looking down -z from the origin
+    let view = uv::Mat4::look_at(uv::Vec3::zero(), -uv::Vec3::unit_z(), uv::Vec3::unit_y());
+    let proj = uv::projection::perspective_vk(1.0, 1.0, 0.1, 100.0);
+    let frustum = Frustum::new(&(proj * view));
+
+    assert!(frustum.intersects_sphere(uv::Vec3::new(0.0, 0.0, -10.0), 1.0));
+    // Behind the camera, beside it and past the far plane
+    assert!(!frustum.intersects_sphere(uv::Vec3::new(0.0, 0.0, 10.0), 1.0));
+    assert!(!frustum.intersects_sphere(uv::Vec3::new(50.0, 0.0, -10.0), 1.0));
+    assert!(!frustum.intersects_sphere(uv::Vec3::new(0.0, 0.0, -200.0), 1.0));
+    // Partly inside
+    assert!(frustum.intersects_sphere(uv::Vec3::new(50.0, 0.0, -10.0), 50.0));
+    assert!(frustum.intersects_sphere(uv::Vec3::new(0.0, 0.0, 0.5), 1.0));
+}
@@ -0,0 +1,53 @@
+use ultraviolet as uv;
+use voyager::debug_draw::DebugDraw;
+
+const WHITE: [f32; 3] = [1.0, 1.0, 1.0];
+
+#[test]
+fn shapes_are_made_of_lines() {
+    let mut debug_draw = DebugDraw::default();
+    debug_draw.aabb(uv::Vec3::new(-1.0, -1.0, -1.0), uv::Vec3::one(), WHITE);
+    assert_eq!(debug_draw.lines().len(), 12);
+
+    // Every edge of the box is axis aligned and two units long
+    for line in debug_draw.lines() {
+        let edge = line.to - line.from;
+        assert!((edge.mag() - 2.0).abs() < 1e-6);
+        assert_eq!([edge.x, edge.y, edge.z].iter().filter(|component| **component != 0.0).count(), 1);
+    }
+
+    debug_draw.arrow(uv::Vec3::zero(), uv::Vec3::unit_x(), WHITE);
+    assert_eq!(debug_draw.lines().len(), 12 + 5);
+
+    // A frustum's far corners are further from the camera than its near ones
+    let view_proj = uv::projection::perspective_vk(1.0, 1.0, 0.1, 10.0);
+    debug_draw.frustum(&view_proj, WHITE);
+    let frustum = &debug_draw.lines()[17..];
+    assert_eq!(frustum.len(), 12);
+    assert!(frustum.iter().all(|line| line.from.z.abs() <= line.to.z.abs() + 1e-4));
+}
+
+#[test]
+fn shapes_expire_after_their_lifetime() {
+    let mut debug_draw = DebugDraw::default();
+    debug_draw.line(uv::Vec3::zero(), uv::Vec3::unit_y(), WHITE);
+    debug_draw.lasting(1.0, |draw| {
+        draw.sphere(uv::Vec3::zero(), 1.0, WHITE);
+        draw.label(uv::Vec3::zero(), "origin", WHITE);
+    });
+    debug_draw.label(uv::Vec3::unit_y(), "up", WHITE);
+
+    // Shapes without a lifetime are only drawn once
+    debug_draw.expire(0.4);
+    let sphere_lines = debug_draw.lines().len();
+    assert!(sphere_lines > 0);
+    assert_eq!(debug_draw.labels().len(), 1);
+    assert_eq!(debug_draw.labels()[0].text, "origin");
+
+    debug_draw.expire(0.4);
+    assert_eq!(debug_draw.lines().len(), sphere_lines);
+
+    debug_draw.expire(0.4);
+    assert!(debug_draw.lines().is_empty());
+    assert!(debug_draw.labels().is_empty());
+}
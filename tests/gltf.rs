@@ -0,0 +1,81 @@
+use std::path::{Path, PathBuf};
+
+use ultraviolet as uv;
+use voyager::render::models::{SceneData, SceneError, VertexAttributes};
+
+const TRIANGLE_GLTF: &str = r#"{
+    "asset": { "version": "2.0" },
+    "scene": 0,
+    "scenes": [{ "nodes": [0] }],
+    "nodes": [
+        { "name": "root", "translation": [1, 2, 3], "children": [1, 2] },
+        { "name": "child", "mesh": 0, "rotation": [0, 0, 0.70710677, 0.70710677] },
+        { "name": "leaf", "scale": [2, 2, 2] }
+    ],
+    "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1, "material": 0 }] }],
+    "materials": [{ "pbrMetallicRoughness": { "baseColorFactor": [1, 0.5, 0, 1] } }],
+    "buffers": [{ "uri": "triangle.bin", "byteLength": 44 }],
+    "bufferViews": [
+        { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+        { "buffer": 0, "byteOffset": 36, "byteLength": 6 }
+    ],
+    "accessors": [
+        { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] },
+        { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }
+    ]
+}"#;
+
+// Every test writes its own copy, tests run in parallel and would otherwise rewrite files another one is reading
+fn write_fixture(test: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("gltf").join(test);
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut buffer = Vec::new();
+    for value in &[0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+    for index in &[0u16, 1, 2, 0] {
+        buffer.extend_from_slice(&index.to_le_bytes());
+    }
+    std::fs::write(dir.join("triangle.bin"), buffer).unwrap();
+
+    let path = dir.join("triangle.gltf");
+    std::fs::write(&path, TRIANGLE_GLTF).unwrap();
+    path
+}
+
+#[test]
+fn node_hierarchy() {
+    let scene = SceneData::load(write_fixture("node_hierarchy")).unwrap();
+
+    let names = scene.nodes.iter().map(|node| node.name.as_deref().unwrap()).collect::<Vec<_>>();
+    assert_eq!(names, ["root", "child", "leaf"]);
+    assert_eq!(scene.nodes.iter().map(|node| node.parent).collect::<Vec<_>>(), [None, Some(0), Some(0)]);
+    assert_eq!(scene.nodes[0].translation, uv::Vec3::new(1.0, 2.0, 3.0));
+    assert_eq!(scene.nodes[1].mesh, Some(0));
+    assert_eq!(scene.nodes[2].scale, uv::Vec3::new(2.0, 2.0, 2.0));
+
+    // A quarter turn around z takes x onto y
+    let rotated = scene.nodes[1].rotation * uv::Vec3::unit_x();
+    assert!((rotated - uv::Vec3::unit_y()).mag() < 1e-5, "{:?}", rotated);
+}
+
+#[test]
+fn primitive_data() {
+    let scene = SceneData::load(write_fixture("primitive_data")).unwrap();
+
+    assert_eq!(scene.meshes.len(), 1);
+    let primitive = &scene.meshes[0][0];
+    assert_eq!(primitive.indices.as_deref(), Some(&[0, 1, 2][..]));
+    assert_eq!(primitive.vertices[1].pos, [1.0, 0.0, 0.0]);
+    assert!(primitive.vertices.iter().all(|vertex| vertex.color == [1.0, 0.5, 0.0]));
+    assert!(primitive.attributes.contains(VertexAttributes::POSITION | VertexAttributes::COLOR));
+    assert!(!primitive.attributes.contains(VertexAttributes::NORMAL));
+}
+
+#[test]
+fn missing_file() {
+    let error = SceneData::load("does/not/exist.gltf").unwrap_err();
+    assert!(matches!(error, SceneError::Import { .. }));
+    assert!(error.to_string().starts_with("does/not/exist.gltf: "), "{}", error);
+}
@@ -0,0 +1,796 @@
+//! Golden image regression tests for the renderer.
+//!
+//! Each scene is rendered headless and its last frame compared against `tests/golden/<name>.png`.
+//! When no Vulkan device is available (e.g. no lavapipe installed) the scenes are skipped, set
+//! `VOYAGER_REQUIRE_VULKAN=1` to fail instead. Scenes without a reference image are skipped as well,
+//! after reporting where their output was written.
+//!
+//! The references are rendered by Mesa's lavapipe so every machine can reproduce them. To (re)write
+//! them after an intended change, bless the scenes on lavapipe and commit the images with the change:
+//!
+//! ```sh
+//! VK_ICD_FILENAMES=/usr/share/vulkan/icd.d/lvp_icd.x86_64.json VOYAGER_REQUIRE_VULKAN=1 VOYAGER_BLESS=1 cargo test --test golden
+//! ```
+
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use lazy_static::lazy_static;
+use specs::{Builder, DispatcherBuilder, World, WorldExt};
+use ultraviolet as uv;
+use voyager::{
+    components::{Camera, FrameCapture, Light, LightKind, Parent, Player, Projection, RenderSettings, Renderable, Transform},
+    debug_draw::DebugDraw,
+    entity_factory::EntityFactory,
+    render::{
+        images::{Background, EnvironmentFactory, RenderTextureFactory, SkyboxFactory, StarfieldSettings},
+        materials::{BlendMode, MaterialData, MaterialFactory, MaterialParams, RenderState, ShaderPair},
+        models::{MeshFactory, Vertex, VertexAttributes},
+        GraphicContext, GridPlane, GridSettings, PostSettings, ViewMode, Viewport,
+    },
+    systems::RenderSystem,
+};
+
+const EXTENT: (u32, u32) = (320, 240);
+
+lazy_static! {
+    // Only one imgui context can be alive at a time so scenes have to render one after another
+    static ref RENDER_LOCK: Mutex<()> = Mutex::new(());
+}
+
+struct Tolerance {
+    /// Largest difference allowed in any color channel before a pixel counts as mismatched
+    channel: u8,
+    /// Fraction of pixels allowed to mismatch, absorbs rasterization differences between drivers
+    mismatched_ratio: f32,
+}
+
+const DEFAULT_TOLERANCE: Tolerance = Tolerance { channel: 2, mismatched_ratio: 0.001 };
+
+/// Everything a scene can create its entities and resources with
+struct Factories {
+    entity: EntityFactory,
+    mesh: MeshFactory,
+    material: MaterialFactory,
+    render_texture: RenderTextureFactory,
+    environment: EnvironmentFactory,
+    skybox: SkyboxFactory,
+}
+
+fn render_scene<F: FnOnce(&mut World, &Factories)>(frames: usize, setup: F) -> Option<FrameCapture> {
+    let _lock = RENDER_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    // Machines without Vulkan skip the scenes, unless they are required to render them like CI does
+    let graphic_context = match GraphicContext::new_headless(EXTENT) {
+        Ok(graphic_context) => graphic_context,
+        Err(error) if std::env::var("VOYAGER_REQUIRE_VULKAN").is_err() => {
+            eprintln!("Skipping golden image test: {}", error);
+            return None;
+        }
+        Err(error) => panic!("{}", error),
+    };
+    let factories = Factories {
+        entity: EntityFactory::new(graphic_context.create_mesh_factory()),
+        mesh: graphic_context.create_mesh_factory(),
+        material: graphic_context.create_material_factory(),
+        render_texture: graphic_context.create_render_texture_factory(),
+        environment: graphic_context.create_environment_factory(),
+        skybox: graphic_context.create_skybox_factory(),
+    };
+
+    let mut world = World::new();
+    let mut dispatcher = DispatcherBuilder::new().with_thread_local(RenderSystem::headless(graphic_context)).build();
+    dispatcher.setup(&mut world);
+
+    // The debug ui shows timings which would differ on every run
+    world.write_resource::<RenderSettings>().show_ui = false;
+
+    setup(&mut world, &factories);
+
+    for _ in 0..frames {
+        dispatcher.dispatch(&world);
+        world.maintain();
+    }
+
+    let capture = std::mem::take(&mut *world.write_resource::<FrameCapture>());
+    Some(capture)
+}
+
+fn reference_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(format!("{}.png", name))
+}
+
+fn output_path(name: &str, suffix: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(format!("{}.{}.png", name, suffix))
+}
+
+fn load_png(path: &Path) -> FrameCapture {
+    let decoder = png::Decoder::new(File::open(path).unwrap());
+    let (info, mut reader) = decoder.read_info().unwrap();
+    assert_eq!(info.color_type, png::ColorType::RGBA, "{} is not an RGBA image", path.display());
+
+    let mut pixels = vec![0; info.buffer_size()];
+    reader.next_frame(&mut pixels).unwrap();
+
+    FrameCapture {
+        width: info.width,
+        height: info.height,
+        pixels,
+    }
+}
+
+/// Highlights mismatched pixels in red over a dimmed copy of the reference
+fn diff_image(reference: &FrameCapture, actual: &FrameCapture, tolerance: &Tolerance) -> (FrameCapture, usize) {
+    let mut mismatched = 0;
+    let mut pixels = Vec::with_capacity(reference.pixels.len());
+
+    for (expected, got) in reference.pixels.chunks(4).zip(actual.pixels.chunks(4)) {
+        let max_delta = expected.iter().zip(got).map(|(a, b)| (*a as i16 - *b as i16).unsigned_abs() as u8).max().unwrap();
+
+        if max_delta > tolerance.channel {
+            mismatched += 1;
+            pixels.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let luma = (expected[0] as u32 * 3 + expected[1] as u32 * 6 + expected[2] as u32) / 10;
+            let dimmed = (luma / 3) as u8;
+            pixels.extend_from_slice(&[dimmed, dimmed, dimmed, 255]);
+        }
+    }
+
+    let diff = FrameCapture {
+        width: reference.width,
+        height: reference.height,
+        pixels,
+    };
+    (diff, mismatched)
+}
+
+fn assert_matches_reference(name: &str, actual: &FrameCapture, tolerance: &Tolerance) {
+    let reference_path = reference_path(name);
+
+    if std::env::var("VOYAGER_BLESS").is_ok() {
+        std::fs::create_dir_all(reference_path.parent().unwrap()).unwrap();
+        actual.save_png(&reference_path).unwrap();
+        eprintln!("Blessed {}", reference_path.display());
+        return;
+    }
+
+    let actual_path = output_path(name, "actual");
+    actual.save_png(&actual_path).unwrap();
+
+    // Scenes nobody blessed yet have nothing to compare against, they are reported and skipped
+    if !reference_path.exists() {
+        eprintln!(
+            "Skipping golden image test {}: missing reference image {}, output written to {}. Bless it on lavapipe as described at the top of tests/golden.rs",
+            name,
+            reference_path.display(),
+            actual_path.display()
+        );
+        return;
+    }
+
+    let reference = load_png(&reference_path);
+    assert_eq!(
+        (reference.width, reference.height),
+        (actual.width, actual.height),
+        "{}: reference size differs from the rendered frame",
+        name
+    );
+
+    let (diff, mismatched) = diff_image(&reference, actual, tolerance);
+    let allowed = (tolerance.mismatched_ratio * (actual.width * actual.height) as f32) as usize;
+    if mismatched > allowed {
+        let diff_path = output_path(name, "diff");
+        diff.save_png(&diff_path).unwrap();
+        panic!(
+            "{}: {} pixels differ from the reference (allowed {}), see {} and {}",
+            name,
+            mismatched,
+            allowed,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+fn create_camera(world: &mut World, pos: [f32; 3], dir: uv::Rotor3) {
+    world
+        .create_entity()
+        .with(Player)
+        .with(Camera::default())
+        .with(Transform {
+            pos: pos.into(),
+            dir,
+            ..Transform::default()
+        })
+        .build();
+}
+
+#[test]
+fn grid_from_player_start() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+        factories.entity.create_grid(world).unwrap();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("grid_from_player_start", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn grid_from_offset_angle() {
+    let capture = render_scene(3, |world, factories| {
+        create_camera(world, [0.6, -0.4, 2.5], uv::Rotor3::from_euler_angles(0.0, 15.0f32.to_radians(), 170.0f32.to_radians()));
+        factories.entity.create_grid(world).unwrap();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("grid_from_offset_angle", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn grid_without_msaa() {
+    let capture = render_scene(3, |world, factories| {
+        world.write_resource::<RenderSettings>().msaa_samples = 1;
+        create_camera(world, [0.6, -0.4, 2.5], uv::Rotor3::from_euler_angles(0.0, 15.0f32.to_radians(), 170.0f32.to_radians()));
+        factories.entity.create_grid(world).unwrap();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("grid_without_msaa", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn post_processing() {
+    let capture = render_scene(3, |world, factories| {
+        // Overexposed so the grid's lines bloom, with every other pass enabled on top
+        world.write_resource::<RenderSettings>().post = PostSettings {
+            exposure: 2.0,
+            bloom: true,
+            bloom_threshold: 0.5,
+            bloom_intensity: 1.0,
+            vignette: true,
+            fxaa: true,
+            color_grading: true,
+            ..PostSettings::default()
+        };
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+        factories.entity.create_grid(world).unwrap();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("post_processing", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn starfield_background() {
+    let capture = render_scene(3, |world, factories| {
+        let starfield = factories.skybox.create_starfield(&StarfieldSettings {
+            seed: 7,
+            nebula_intensity: 0.2,
+            ..StarfieldSettings::default()
+        });
+        world.write_resource::<RenderSettings>().background = Background::Skybox { skybox: starfield, intensity: 1.0 };
+
+        // Turned away from the grid so the sky has to rotate with the camera
+        world
+            .create_entity()
+            .with(Camera::default())
+            .with(Transform {
+                pos: uv::Vec3::new(0.0, 0.0, 4.0),
+                dir: uv::Rotor3::from_euler_angles(0.0, 0.0, 160.0f32.to_radians()),
+                ..Transform::default()
+            })
+            .build();
+        factories.entity.create_grid(world).unwrap();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("starfield_background", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn editor_grid() {
+    let capture = render_scene(3, |world, factories| {
+        world.write_resource::<RenderSettings>().grid = GridSettings {
+            enabled: true,
+            plane: GridPlane::XZ,
+            spacing: 0.5,
+            major_every: 4,
+            fade_distance: 30.0,
+        };
+
+        // Above the XZ plane looking down at it, with the quad standing in it to show the grid is depth tested
+        world
+            .create_entity()
+            .with(Camera::default())
+            .with(Transform {
+                pos: uv::Vec3::new(0.0, 2.0, 5.0),
+                dir: uv::Rotor3::from_euler_angles(0.0, -20.0f32.to_radians(), 180.0f32.to_radians()),
+                ..Transform::default()
+            })
+            .build();
+        factories.entity.create_grid(world).unwrap();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("editor_grid", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn debug_shapes() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+        factories.entity.create_grid(world).unwrap();
+
+        // Kept for every frame rendered, shapes without a lifetime would only show in the first one
+        world.write_resource::<DebugDraw>().lasting(10.0, |draw| {
+            draw.aabb(uv::Vec3::new(-1.5, -1.0, -0.5), uv::Vec3::new(-0.5, 0.0, 0.5), [1.0, 1.0, 0.0]);
+            draw.sphere(uv::Vec3::new(1.0, 0.5, 0.0), 0.5, [0.0, 1.0, 1.0]);
+            draw.arrow(uv::Vec3::new(-1.0, 1.0, 0.0), uv::Vec3::new(0.5, 1.0, 0.5), [1.0, 0.0, 1.0]);
+            draw.axes(&uv::Mat4::from_translation(uv::Vec3::new(0.0, -1.0, 0.0)), 0.5);
+        });
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("debug_shapes", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn view_modes() {
+    let modes = [
+        (ViewMode::Wireframe, "view_mode_wireframe"),
+        (ViewMode::Normals, "view_mode_normals"),
+        (ViewMode::UvChecker, "view_mode_uv_checker"),
+        (ViewMode::Depth, "view_mode_depth"),
+        (ViewMode::Overdraw, "view_mode_overdraw"),
+    ];
+    for (mode, name) in modes.iter() {
+        let capture = render_scene(3, |world, factories| {
+            world.write_resource::<RenderSettings>().view_mode = *mode;
+            factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+
+            // Two overlapping quads, the second one tilted so the normals and depth vary over it
+            factories.entity.create_grid(world).unwrap();
+            let tilted = factories.entity.create_grid(world).unwrap();
+            *world.write_storage::<Transform>().get_mut(tilted).unwrap() = Transform {
+                pos: uv::Vec3::new(0.5, 0.0, 0.5),
+                dir: uv::Rotor3::from_rotation_xz(40.0f32.to_radians()),
+                scale: uv::Vec3::new(0.5, 0.5, 1.0),
+            };
+        });
+
+        if let Some(capture) = capture {
+            assert_matches_reference(name, &capture, &DEFAULT_TOLERANCE);
+        }
+    }
+}
+
+#[test]
+fn transformed_grids() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+
+        let parent = factories.entity.create_grid(world).unwrap();
+        *world.write_storage::<Transform>().get_mut(parent).unwrap() = Transform {
+            pos: uv::Vec3::new(-0.6, 0.0, 0.0),
+            dir: uv::Rotor3::from_rotation_xy(30.0f32.to_radians()),
+            scale: uv::Vec3::new(0.8, 0.5, 1.0),
+        };
+
+        // Offset along the parent's rotated x axis and scaled by both transforms
+        let child = factories.entity.create_grid(world).unwrap();
+        *world.write_storage::<Transform>().get_mut(child).unwrap() = Transform {
+            pos: uv::Vec3::new(1.5, 0.0, 0.0),
+            scale: uv::Vec3::new(0.5, 1.0, 1.0),
+            ..Transform::default()
+        };
+        world.write_storage::<Parent>().insert(child, Parent { entity: parent }).unwrap();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("transformed_grids", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn instanced_grids() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 6.0]);
+
+        // Every grid shares the first one's mesh so they are all recorded as a single instanced draw
+        let first = factories.entity.create_grid(world).unwrap();
+        let mesh = world.read_storage::<Renderable>().get(first).unwrap().mesh.clone();
+        world.write_storage::<Transform>().get_mut(first).unwrap().pos = uv::Vec3::new(-2.0, 0.0, 0.0);
+
+        for (x, color) in [(0.0, [1.0, 0.3, 0.3]), (2.0, [0.3, 0.3, 1.0])].iter().copied() {
+            world
+                .create_entity()
+                .with(Transform {
+                    pos: uv::Vec3::new(x, 0.0, 0.0),
+                    ..Transform::default()
+                })
+                .with(Renderable {
+                    color,
+                    ..Renderable::new(mesh.clone())
+                })
+                .build();
+        }
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("instanced_grids", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn orthographic_camera() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_grid(world).unwrap();
+
+        // An inactive perspective camera placed elsewhere must not be picked
+        world
+            .create_entity()
+            .with(Camera { active: false, ..Camera::default() })
+            .with(Transform {
+                pos: uv::Vec3::new(3.0, 0.0, 1.0),
+                ..Transform::default()
+            })
+            .build();
+
+        world
+            .create_entity()
+            .with(Camera {
+                projection: Projection::Orthographic { height: 3.0 },
+                ..Camera::default()
+            })
+            .with(Transform {
+                pos: uv::Vec3::new(0.0, 0.0, 4.0),
+                dir: uv::Rotor3::from_euler_angles(0.0, 0.0, 180.0f32.to_radians()),
+                ..Transform::default()
+            })
+            .build();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("orthographic_camera", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn split_screen_with_layers() {
+    let capture = render_scene(3, |world, factories| {
+        let grid = factories.entity.create_grid(world).unwrap();
+        let marker = factories.entity.create_grid(world).unwrap();
+        {
+            let mut transforms = world.write_storage::<Transform>();
+            let transform = transforms.get_mut(marker).unwrap();
+            transform.pos = uv::Vec3::new(0.0, 0.0, 0.5);
+            transform.scale = uv::Vec3::new(0.3, 0.3, 1.0);
+        }
+        world.write_storage::<Renderable>().get_mut(marker).unwrap().layers = 0b10;
+
+        let look_down = uv::Rotor3::from_euler_angles(0.0, 0.0, 180.0f32.to_radians());
+        let cameras = [
+            (Viewport::new(0.0, 0.0, 0.5, 1.0), uv::Vec3::new(0.0, 0.0, 4.0), !0),
+            (Viewport::new(0.5, 0.0, 0.5, 1.0), uv::Vec3::new(0.5, 0.0, 3.0), Renderable::DEFAULT_LAYERS),
+        ];
+        for (viewport, pos, layer_mask) in cameras.iter().copied() {
+            world
+                .create_entity()
+                .with(Camera {
+                    viewport,
+                    layer_mask,
+                    ..Camera::default()
+                })
+                .with(Transform {
+                    pos,
+                    dir: look_down,
+                    ..Transform::default()
+                })
+                .build();
+        }
+
+        // Picture in picture drawn last over the left half, following the grid from close up
+        world
+            .create_entity()
+            .with(Camera {
+                viewport: Viewport::new(0.05, 0.05, 0.2, 0.2),
+                order: 1,
+                ..Camera::default()
+            })
+            .with(Parent { entity: grid })
+            .with(Transform {
+                pos: uv::Vec3::new(0.0, 0.0, 2.0),
+                dir: look_down,
+                ..Transform::default()
+            })
+            .build();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("split_screen_with_layers", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn render_texture_on_quad() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+
+        // The monitor camera only sees the grid, never the quad showing what it renders
+        let grid = factories.entity.create_grid(world).unwrap();
+        world.write_storage::<Transform>().get_mut(grid).unwrap().pos = uv::Vec3::new(-0.8, 0.0, 0.0);
+        let render_texture = factories.render_texture.create_render_texture(128, 128);
+        world
+            .create_entity()
+            .with(Camera {
+                layer_mask: Renderable::DEFAULT_LAYERS,
+                target: Some(render_texture.clone()),
+                ..Camera::default()
+            })
+            .with(Transform {
+                pos: uv::Vec3::new(-0.8, 0.3, 2.5),
+                dir: uv::Rotor3::from_euler_angles(0.0, 0.0, 180.0f32.to_radians()),
+                ..Transform::default()
+            })
+            .build();
+
+        let corners = [([-0.5, -0.5], [0.0, 1.0]), ([0.5, -0.5], [1.0, 1.0]), ([0.5, 0.5], [1.0, 0.0]), ([-0.5, 0.5], [0.0, 0.0])];
+        let vertices = corners
+            .iter()
+            .map(|&([x, y], uv)| Vertex {
+                pos: [x, y, 0.0],
+                uv,
+                ..Vertex::default()
+            })
+            .collect::<Vec<_>>();
+        let quad = factories
+            .mesh
+            .create_mesh(&vertices, Some(&[0, 1, 2, 2, 3, 0]), VertexAttributes::POSITION | VertexAttributes::UV | VertexAttributes::COLOR);
+        world
+            .create_entity()
+            .with(Transform {
+                pos: uv::Vec3::new(0.8, 0.0, 0.0),
+                ..Transform::default()
+            })
+            .with(Renderable {
+                layers: 0b10,
+                material: Some(factories.material.create_material(MaterialData {
+                    texture: Some(render_texture.texture().clone()),
+                    ..MaterialData::default()
+                })),
+                ..Renderable::new(Arc::new(quad))
+            })
+            .build();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("render_texture_on_quad", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn materials() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+
+        let tinted = factories.material.create_material(MaterialData {
+            params: MaterialParams {
+                color: [1.0, 0.5, 0.5, 1.0],
+                ..MaterialParams::default()
+            },
+            ..MaterialData::default()
+        });
+        let blended = factories.material.create_material(MaterialData {
+            state: RenderState {
+                blend: BlendMode::Alpha,
+                depth_write: false,
+                ..RenderState::default()
+            },
+            params: MaterialParams {
+                color: [0.2, 0.4, 1.0, 0.5],
+                ..MaterialParams::default()
+            },
+            ..MaterialData::default()
+        });
+
+        // Left grid keeps the default material, the blended one overlaps the tinted one in front of it
+        let placements = [(-1.0, 0.0, None), (0.6, 0.0, Some(tinted)), (1.0, 0.3, Some(blended))];
+        for (x, z, material) in placements.iter().cloned() {
+            let grid = factories.entity.create_grid(world).unwrap();
+            world.write_storage::<Transform>().get_mut(grid).unwrap().pos = uv::Vec3::new(x, 0.0, z);
+            world.write_storage::<Renderable>().get_mut(grid).unwrap().material = material;
+        }
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("materials", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn lights() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+
+        let lit = factories.material.create_material(MaterialData {
+            shaders: ShaderPair::named("lit"),
+            ..MaterialData::default()
+        });
+        for x in [-1.1f32, 0.0, 1.1].iter() {
+            let grid = factories.entity.create_grid(world).unwrap();
+            world.write_storage::<Transform>().get_mut(grid).unwrap().pos = uv::Vec3::new(*x, 0.0, 0.0);
+            world.write_storage::<Renderable>().get_mut(grid).unwrap().material = Some(lit.clone());
+        }
+
+        // A dim directional light from the camera's side, a red point light over the left grid and a blue spot on the right one
+        let lights = [
+            (uv::Vec3::new(0.0, 0.0, 2.0), LightKind::Directional, [1.0, 1.0, 1.0], 0.2),
+            (uv::Vec3::new(-1.1, 0.3, 0.5), LightKind::Point { range: 2.0 }, [1.0, 0.2, 0.2], 2.0),
+            (
+                uv::Vec3::new(1.1, 0.0, 1.0),
+                LightKind::Spot {
+                    range: 3.0,
+                    inner_angle: 10f32.to_radians(),
+                    outer_angle: 20f32.to_radians(),
+                },
+                [0.2, 0.4, 1.0],
+                3.0,
+            ),
+        ];
+        for (pos, kind, color, intensity) in lights.iter().cloned() {
+            // Lights shine along their local z axis, turned around to face the grids
+            let transform = Transform {
+                pos,
+                dir: uv::Rotor3::from_euler_angles(0.0, 0.0, 180.0f32.to_radians()),
+                ..Transform::default()
+            };
+            world
+                .create_entity()
+                .with(transform)
+                .with(Light {
+                    kind,
+                    color,
+                    intensity,
+                    ..Light::default()
+                })
+                .build();
+        }
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("lights", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn pbr_materials() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+
+        // Sky above the horizon and dark ground below it, the side faces split between the two
+        let face_size = 8;
+        let sky = [90, 140, 220, 255].repeat(face_size * face_size);
+        let ground = [60, 45, 30, 255].repeat(face_size * face_size);
+        let side = [[90, 140, 220, 255].repeat(face_size * face_size / 2), [60, 45, 30, 255].repeat(face_size * face_size / 2)].concat();
+        let environment = factories.environment.create_environment(face_size as u32, [&side, &side, &sky, &ground, &side, &side]);
+        let mut render_settings = world.write_resource::<RenderSettings>();
+        render_settings.environment = Some(environment);
+        render_settings.ambient_light = [1.0, 1.0, 1.0];
+        drop(render_settings);
+
+        // A rough dielectric, a shiny dielectric and a polished metal side by side
+        let placements = [(-1.1, 0.0, 0.9), (0.0, 0.0, 0.2), (1.1, 1.0, 0.2)];
+        for (x, metallic, roughness) in placements.iter().cloned() {
+            let material = factories.material.create_material(MaterialData {
+                shaders: ShaderPair::named("pbr"),
+                params: MaterialParams {
+                    color: [0.9, 0.6, 0.3, 1.0],
+                    metallic,
+                    roughness,
+                    ..MaterialParams::default()
+                },
+                ..MaterialData::default()
+            });
+            let grid = factories.entity.create_grid(world).unwrap();
+            world.write_storage::<Transform>().get_mut(grid).unwrap().pos = uv::Vec3::new(x, 0.0, 0.0);
+            world.write_storage::<Renderable>().get_mut(grid).unwrap().material = Some(material);
+        }
+
+        let transform = Transform {
+            pos: uv::Vec3::new(0.0, 0.0, 2.0),
+            dir: uv::Rotor3::from_euler_angles(0.0, 0.0, 180.0f32.to_radians()),
+            ..Transform::default()
+        };
+        world.create_entity().with(transform).with(Light { intensity: 2.0, ..Light::default() }).build();
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("pbr_materials", &capture, &DEFAULT_TOLERANCE);
+    }
+}
+
+#[test]
+fn shadows() {
+    let capture = render_scene(3, |world, factories| {
+        factories.entity.create_player(world, [0.0, 0.0, 4.0]);
+
+        let lit = factories.material.create_material(MaterialData {
+            shaders: ShaderPair::named("lit"),
+            ..MaterialData::default()
+        });
+
+        // A wall behind two grids, catching the shadows they throw
+        let corners = [[-2.0, -1.5], [2.0, -1.5], [2.0, 1.5], [-2.0, 1.5]];
+        let vertices = corners
+            .iter()
+            .map(|&[x, y]| Vertex {
+                pos: [x, y, 0.0],
+                normal: [0.0, 0.0, 1.0],
+                ..Vertex::default()
+            })
+            .collect::<Vec<_>>();
+        let wall = factories
+            .mesh
+            .create_mesh(&vertices, Some(&[0, 1, 2, 2, 3, 0]), VertexAttributes::POSITION | VertexAttributes::NORMAL | VertexAttributes::COLOR);
+        world
+            .create_entity()
+            .with(Transform::default())
+            .with(Renderable {
+                material: Some(lit.clone()),
+                ..Renderable::new(Arc::new(wall))
+            })
+            .build();
+        for x in [-0.8f32, 0.8].iter() {
+            let grid = factories.entity.create_grid(world).unwrap();
+            world.write_storage::<Transform>().get_mut(grid).unwrap().pos = uv::Vec3::new(*x, 0.0, 0.8);
+            world.write_storage::<Renderable>().get_mut(grid).unwrap().material = Some(lit.clone());
+        }
+
+        // A slanted directional light shadowing both grids and a spot light over the right one
+        let lights = [
+            (uv::Vec3::new(0.0, 0.0, 2.0), 0.3, LightKind::Directional, 0.6),
+            (
+                uv::Vec3::new(0.8, 0.4, 2.5),
+                0.0,
+                LightKind::Spot {
+                    range: 5.0,
+                    inner_angle: 20f32.to_radians(),
+                    outer_angle: 30f32.to_radians(),
+                },
+                3.0,
+            ),
+        ];
+        for (pos, pitch, kind, intensity) in lights.iter().cloned() {
+            let transform = Transform {
+                pos,
+                dir: uv::Rotor3::from_euler_angles(0.0, pitch, 180.0f32.to_radians()),
+                ..Transform::default()
+            };
+            let light = Light {
+                kind,
+                intensity,
+                cast_shadows: true,
+                ..Light::default()
+            };
+            world.create_entity().with(transform).with(light).build();
+        }
+    });
+
+    if let Some(capture) = capture {
+        assert_matches_reference("shadows", &capture, &DEFAULT_TOLERANCE);
+    }
+}
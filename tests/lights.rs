@@ -0,0 +1,91 @@
+use ultraviolet as uv;
+use voyager::render::{LightData, ShadowCaster, SHADOW_CASCADES, SHADOW_MAP_SIZE};
+
+#[test]
+fn lights_pack_their_kind_and_premultiplied_color() {
+    let directional = LightData::directional(uv::Vec3::new(0.0, 0.0, -2.0), [1.0, 0.5, 0.25], 2.0);
+    assert_eq!(directional.position(), [0.0; 4]);
+    assert_eq!(directional.direction(), [0.0, 0.0, -1.0, 0.0]);
+    assert_eq!(directional.color(), [2.0, 1.0, 0.5, 1.0]);
+    assert_eq!(directional.shadow()[0], -1.0);
+
+    let point = LightData::point(uv::Vec3::new(1.0, 2.0, 3.0), 10.0, [1.0; 3], 1.0);
+    assert_eq!(point.position(), [1.0, 2.0, 3.0, 10.0]);
+    assert_eq!(point.direction()[3], 1.0);
+}
+
+#[test]
+fn spot_cone_is_stored_as_cosines() {
+    let light = LightData::spot(uv::Vec3::zero(), uv::Vec3::unit_x(), 5.0, 0.2, 0.4, [1.0; 3], 1.0);
+    assert_eq!(light.direction(), [1.0, 0.0, 0.0, 2.0]);
+    assert!((light.cone()[0] - 0.2f32.cos()).abs() < 1e-6);
+    assert!((light.cone()[1] - 0.4f32.cos()).abs() < 1e-6);
+
+    // An outer angle inside the inner one is widened to it
+    let cone = LightData::spot(uv::Vec3::zero(), uv::Vec3::unit_x(), 5.0, 0.4, 0.2, [1.0; 3], 1.0).cone();
+    assert_eq!(cone[0], cone[1]);
+
+    let mut shadowed = light;
+    shadowed.set_shadow(3, 1);
+    assert_eq!(shadowed.shadow(), [3.0, 1.0, 0.0, 0.0]);
+}
+
+/// Whether a world space point lands inside a shadow map layer's clip volume
+fn inside(matrix: &uv::Mat4, point: uv::Vec3) -> bool {
+    let clip = matrix.transform_point3(point);
+    clip.x.abs() <= 1.0 && clip.y.abs() <= 1.0 && (0.0..=1.0).contains(&clip.z)
+}
+
+#[test]
+fn cascades_split_the_view_from_near_to_far() {
+    // Looking down -z from the origin, lit from above
+    let view = uv::Mat4::look_at(uv::Vec3::zero(), -uv::Vec3::unit_z(), uv::Vec3::unit_y());
+    let proj = uv::projection::perspective_vk(1.0, 1.0, 0.1, 1000.0);
+    let caster = ShadowCaster::directional(2, uv::Vec3::new(0.3, -1.0, 0.2), &view, &proj, 60.0);
+    assert_eq!(caster.light, 2);
+    assert_eq!(caster.matrices.len(), SHADOW_CASCADES);
+
+    // The first cascade covers the camera and the last one the end of the shadow distance
+    assert!(inside(&caster.matrices[0], uv::Vec3::new(0.0, 0.0, -0.2)));
+    assert!(inside(&caster.matrices[SHADOW_CASCADES - 1], uv::Vec3::new(0.0, 0.0, -59.0)));
+    assert!(!inside(&caster.matrices[0], uv::Vec3::new(0.0, 0.0, -59.0)));
+
+    // Nearer cascades cover less of the world, so their texels are smaller
+    let size = |matrix: &uv::Mat4| 2.0 / matrix.cols[0].xyz().mag();
+    for pair in caster.matrices.windows(2) {
+        assert!(size(&pair[0]) < size(&pair[1]));
+    }
+}
+
+#[test]
+fn cascades_only_move_in_whole_texels() {
+    let proj = uv::projection::perspective_vk(1.0, 1.0, 0.1, 100.0);
+    let direction = uv::Vec3::new(0.3, -1.0, 0.2);
+    let caster = |x: f32| {
+        let eye = uv::Vec3::new(x, 2.0, 0.0);
+        let view = uv::Mat4::look_at(eye, eye - uv::Vec3::unit_z(), uv::Vec3::unit_y());
+        ShadowCaster::directional(0, direction, &view, &proj, 50.0)
+    };
+    let (before, after) = (caster(0.0), caster(0.37));
+
+    for (first, second) in before.matrices.iter().zip(after.matrices.iter()) {
+        // A fixed world point moves across the shadow map by a whole number of texels
+        let texels = (second.transform_point3(uv::Vec3::zero()) - first.transform_point3(uv::Vec3::zero())) * (SHADOW_MAP_SIZE as f32 / 2.0);
+        assert!((texels.x - texels.x.round()).abs() < 1e-2, "moved {} texels", texels.x);
+        assert!((texels.y - texels.y.round()).abs() < 1e-2, "moved {} texels", texels.y);
+    }
+}
+
+#[test]
+fn spot_shadow_covers_the_cone() {
+    let position = uv::Vec3::new(0.0, 5.0, 0.0);
+    let caster = ShadowCaster::spot(1, position, -uv::Vec3::unit_y(), 0.5, 20.0);
+    assert_eq!(caster.matrices.len(), 1);
+
+    assert!(inside(&caster.matrices[0], uv::Vec3::new(0.0, 0.0, 0.0)));
+    // Just inside the cone's edge, and outside of it
+    assert!(inside(&caster.matrices[0], uv::Vec3::new(0.45f32.tan() * 5.0, 0.0, 0.0)));
+    assert!(!inside(&caster.matrices[0], uv::Vec3::new(0.6f32.tan() * 5.0, 0.0, 0.0)));
+    // Past the range
+    assert!(!inside(&caster.matrices[0], uv::Vec3::new(0.0, -16.0, 0.0)));
+}
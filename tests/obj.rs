@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+
+use voyager::{
+    assets::asset_path,
+    render::models::{ObjError, ObjModel, VertexAttributes},
+};
+
+const CUBE_POSITIONS: &str = "
+v -1 -1 -1
+v 1 -1 -1
+v 1 1 -1
+v -1 1 -1
+v -1 -1 1
+v 1 -1 1
+v 1 1 1
+v -1 1 1
+";
+
+const CUBE_FACES: &str = "
+f 1 4 3 2
+f 5 6 7 8
+f 1 2 6 5
+f 2 3 7 6
+f 3 4 8 7
+f 4 1 5 8
+";
+
+fn write_fixture(name: &str, contents: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("obj");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[test]
+fn grid_asset() {
+    let model = ObjModel::load(asset_path("assets/models/grid.obj")).unwrap();
+
+    assert_eq!(model.vertices.len(), 4);
+    assert_eq!(model.indices, [0, 1, 2, 2, 3, 0]);
+    assert_eq!(model.vertices[0].color, [1.0, 0.0, 0.0]);
+    assert!(model.vertices.iter().all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+    assert!(!model.attributes.contains(VertexAttributes::UV));
+}
+
+#[test]
+fn flat_normals_split_corners() {
+    let path = write_fixture("flat_cube.obj", &format!("{}s off{}", CUBE_POSITIONS, CUBE_FACES));
+    let model = ObjModel::load(path).unwrap();
+
+    assert_eq!(model.indices.len(), 36);
+    assert_eq!(model.vertices.len(), 24);
+    assert!(model.vertices.iter().all(|vertex| vertex.normal.iter().filter(|axis| axis.abs() == 1.0).count() == 1));
+}
+
+#[test]
+fn smooth_normals_share_corners() {
+    let path = write_fixture("smooth_cube.obj", &format!("{}s 1{}", CUBE_POSITIONS, CUBE_FACES));
+    let model = ObjModel::load(path).unwrap();
+
+    assert_eq!(model.indices.len(), 36);
+    assert_eq!(model.vertices.len(), 8);
+    for vertex in &model.vertices {
+        let outward = vertex.pos.iter().zip(&vertex.normal).all(|(pos, normal)| (pos / 3f32.sqrt() - normal).abs() < 1e-5);
+        assert!(outward, "{:?} doesn't point away from the center", vertex);
+    }
+}
+
+#[test]
+fn material_colors() {
+    write_fixture("colored.mtl", "newmtl red\nKd 1 0 0\n\nnewmtl green\nKd 0 1 0\n");
+    let path = write_fixture(
+        "colored.obj",
+        "mtllib colored.mtl\nv 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nvt 1 1\nusemtl red\nf 1/1 2/1 3/2\nusemtl green\nf 3/2 4/1 1/1\n",
+    );
+    let model = ObjModel::load(path).unwrap();
+
+    assert!(model.attributes.contains(VertexAttributes::UV | VertexAttributes::COLOR));
+    assert_eq!(model.vertices.len(), 6);
+    assert_eq!(model.vertices[0].color, [1.0, 0.0, 0.0]);
+    assert_eq!(model.vertices[0].uv, [0.0, 1.0]);
+    assert_eq!(model.vertices[5].color, [0.0, 1.0, 0.0]);
+}
+
+#[test]
+fn errors_point_at_the_line() {
+    let path = write_fixture("broken.obj", "v 0 0 0\nv 1 0 0\n# only two positions\nf 1 2 3\n");
+
+    match ObjModel::load(&path) {
+        Err(ObjError::Parse { line, .. }) => assert_eq!(line, 4),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+
+    let error = ObjModel::load(write_fixture("bad_number.obj", "v 0 zero 0\n")).unwrap_err();
+    assert!(error.to_string().ends_with("bad_number.obj:1: invalid number in `v`: invalid float literal"), "{}", error);
+
+    let error = ObjModel::load(write_fixture("missing_library.obj", "mtllib missing.mtl\n")).unwrap_err();
+    assert!(matches!(error, ObjError::Io { .. }));
+}
@@ -0,0 +1,42 @@
+use voyager::render::images::StarfieldSettings;
+
+fn settings(seed: u64) -> StarfieldSettings {
+    StarfieldSettings {
+        seed,
+        face_size: 32,
+        star_count: 300,
+        ..StarfieldSettings::default()
+    }
+}
+
+fn brightness(texels: &[u8]) -> u64 {
+    texels.chunks(4).map(|texel| texel[..3].iter().map(|channel| *channel as u64).sum::<u64>()).sum()
+}
+
+#[test]
+fn same_settings_generate_the_same_sky() {
+    let sky = settings(7).generate();
+    assert_eq!(sky.len(), 6 * 32 * 32 * 4);
+    assert_eq!(sky, settings(7).generate());
+    assert_ne!(sky, settings(8).generate());
+}
+
+#[test]
+fn stars_and_nebulae_light_up_black_space() {
+    let empty = StarfieldSettings {
+        star_count: 0,
+        nebula_intensity: 0.0,
+        ..settings(1)
+    };
+    let sky = empty.generate();
+    assert_eq!(brightness(&sky), 0);
+    assert!(sky.chunks(4).all(|texel| texel[3] == 255));
+
+    let stars = StarfieldSettings { nebula_intensity: 0.0, ..settings(1) };
+    let more_stars = StarfieldSettings { star_count: 3000, ..stars.clone() };
+    assert!(brightness(&stars.generate()) > 0);
+    assert!(brightness(&more_stars.generate()) > brightness(&stars.generate()));
+
+    let nebulae = StarfieldSettings { star_count: 0, ..settings(1) };
+    assert!(brightness(&nebulae.generate()) > 0);
+}
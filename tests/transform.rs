@@ -0,0 +1,29 @@
+mod common;
+
+use std::f32::consts::FRAC_PI_2;
+
+use common::assert_near;
+use ultraviolet as uv;
+use voyager::components::Transform;
+
+#[test]
+fn model_matrix_scales_then_rotates_then_translates() {
+    let transform = Transform {
+        pos: uv::Vec3::new(10.0, 0.0, 0.0),
+        dir: uv::Rotor3::from_rotation_xy(FRAC_PI_2),
+        scale: uv::Vec3::new(2.0, 1.0, 1.0),
+    };
+    let model = transform.model_matrix();
+
+    // Scaled along x to 2, turned a quarter from x towards y, then moved
+    assert_near(model.transform_point3(uv::Vec3::unit_x()), uv::Vec3::new(10.0, 2.0, 0.0));
+    assert_near(model.transform_point3(uv::Vec3::unit_y()), uv::Vec3::new(9.0, 0.0, 0.0));
+    assert_near(model.transform_point3(uv::Vec3::zero()), transform.pos);
+}
+
+#[test]
+fn default_transform_is_the_identity() {
+    let model = Transform::default().model_matrix();
+    let point = uv::Vec3::new(1.0, -2.0, 3.0);
+    assert_near(model.transform_point3(point), point);
+}
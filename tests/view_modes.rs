@@ -0,0 +1,47 @@
+use ash::vk;
+use voyager::render::{
+    materials::{BlendMode, RenderState, ShaderPair},
+    ViewMode,
+};
+
+fn blended_state() -> RenderState {
+    RenderState {
+        blend: BlendMode::Alpha,
+        depth_write: false,
+        ..RenderState::default()
+    }
+}
+
+#[test]
+fn shaded_keeps_the_material() {
+    assert!(ViewMode::Shaded.replace(&ShaderPair::named("pbr"), &blended_state()).is_none());
+    assert_eq!(ViewMode::default(), ViewMode::Shaded);
+}
+
+#[test]
+fn wireframe_keeps_the_shaders_and_draws_edges() {
+    let shaders = ShaderPair::named("lit");
+    let (replaced, state) = ViewMode::Wireframe.replace(&shaders, &blended_state()).unwrap();
+    assert_eq!(replaced, shaders);
+    assert_eq!(state.polygon_mode, vk::PolygonMode::LINE);
+    assert_eq!(state.cull_mode, vk::CullModeFlags::NONE);
+    assert_eq!(state.blend, BlendMode::Alpha);
+}
+
+#[test]
+fn debug_modes_draw_opaque_with_their_own_shaders() {
+    let modes = [(ViewMode::Normals, "view_normals"), (ViewMode::UvChecker, "view_uv_checker"), (ViewMode::Depth, "view_depth")];
+    for (mode, fragment) in modes.iter() {
+        let (shaders, state) = mode.replace(&ShaderPair::named("pbr"), &blended_state()).unwrap();
+        assert_eq!(shaders.vertex, "view_mode");
+        assert_eq!(shaders.fragment, *fragment);
+        assert_eq!(state.blend, BlendMode::Opaque);
+        assert!(state.depth_write);
+    }
+
+    // Overdraw adds up every surface, hidden ones included
+    let (shaders, state) = ViewMode::Overdraw.replace(&ShaderPair::named("pbr"), &RenderState::default()).unwrap();
+    assert_eq!(shaders.fragment, "view_overdraw");
+    assert_eq!(state.blend, BlendMode::Additive);
+    assert!(!state.depth_test && !state.depth_write);
+}